@@ -0,0 +1,134 @@
+# The Sabbah runtime: the standard library (see src/stdlib), written
+# against raw Linux syscalls. These follow the System V calling convention
+# like any Sabbah function: a str arrives as a pointer and a length in two
+# registers, and is returned in %rax and %rdx.
+# The entry point lives in start.s.
+.section .text
+
+# print(s str)
+.globl _sabbah_print
+.hidden _sabbah_print
+_sabbah_print:
+    mov %rdi, %r8               # what is left to write
+    mov %rsi, %r9               # and how much of it
+.Lprint_loop:
+    test %r9, %r9
+    jz .Lprint_done
+    mov $1, %rax                # write
+    mov $1, %rdi                # stdout
+    mov %r8, %rsi
+    mov %r9, %rdx
+    syscall
+    test %rax, %rax
+    jle .Lprint_done            # give up on errors
+    add %rax, %r8
+    sub %rax, %r9
+    jmp .Lprint_loop
+.Lprint_done:
+    ret
+
+# println(s str)
+.globl _sabbah_println
+.hidden _sabbah_println
+_sabbah_println:
+    sub $8, %rsp                # keep %rsp 16-byte aligned for the calls
+    call _sabbah_print
+    lea .Lnewline(%rip), %rdi
+    mov $1, %rsi
+    call _sabbah_print
+    add $8, %rsp
+    ret
+
+# print_int(n uint64)
+.globl _sabbah_print_int
+.hidden _sabbah_print_int
+_sabbah_print_int:
+    sub $40, %rsp               # room for the digits of any 64-bit number
+    mov %rdi, %rax
+    lea 32(%rsp), %rsi          # digits are written backwards from here
+    mov $10, %rcx
+.Lprint_int_loop:
+    xor %edx, %edx
+    div %rcx
+    add $48, %dl                # '0'
+    dec %rsi
+    mov %dl, (%rsi)
+    test %rax, %rax
+    jnz .Lprint_int_loop
+    mov %rsi, %rdi
+    lea 32(%rsp), %rsi
+    sub %rdi, %rsi
+    call _sabbah_print
+    add $40, %rsp
+    ret
+
+# exit(code uint8)
+.globl _sabbah_exit
+.hidden _sabbah_exit
+_sabbah_exit:
+    mov $60, %rax               # exit; the code is already in %rdi
+    syscall
+
+# panic(message str)
+# Not callable from Sabbah: the compiler calls it when a runtime check
+# fails, with a message saying where. Writes the message to stderr and
+# exits with code 101.
+.globl _sabbah_panic
+.hidden _sabbah_panic
+_sabbah_panic:
+    mov %rdi, %r8
+    mov %rsi, %r9
+.Lpanic_loop:
+    test %r9, %r9
+    jz .Lpanic_exit
+    mov $1, %rax                # write
+    mov $2, %rdi                # stderr
+    mov %r8, %rsi
+    mov %r9, %rdx
+    syscall
+    test %rax, %rax
+    jle .Lpanic_exit            # exit anyway
+    add %rax, %r8
+    sub %rax, %r9
+    jmp .Lpanic_loop
+.Lpanic_exit:
+    mov $60, %rax               # exit
+    mov $101, %rdi
+    syscall
+
+# read_line() -> str
+# Reads one byte at a time, so that nothing after the newline is consumed.
+.globl _sabbah_read_line
+.hidden _sabbah_read_line
+_sabbah_read_line:
+    lea .Lline(%rip), %r8
+    xor %r9d, %r9d              # length so far
+.Lread_line_loop:
+    cmp $4096, %r9
+    jae .Lread_line_done        # the buffer is full
+    xor %eax, %eax              # read
+    xor %edi, %edi              # stdin
+    lea (%r8,%r9), %rsi
+    mov $1, %edx
+    syscall
+    cmp $1, %rax
+    jne .Lread_line_done        # end of file or an error
+    cmpb $10, (%r8,%r9)
+    je .Lread_line_done         # the newline is not part of the line
+    inc %r9
+    jmp .Lread_line_loop
+.Lread_line_done:
+    mov %r8, %rax
+    mov %r9, %rdx
+    ret
+
+.section .rodata
+.Lnewline:
+    .byte 10
+
+.section .bss
+.Lline:
+    .zero 4096
+
+# The stack is not executable
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,13 @@
+# The Sabbah entry point, linked in unless the C runtime provides one
+# (--libc). Sabbah programs start at `main`; _start calls it and exits
+# with whatever it returned.
+.section .text
+.globl _start
+_start:
+    call main
+    mov %rax, %rdi
+    mov $60, %rax
+    syscall
+
+# The stack is not executable
+.section .note.GNU-stack,"",@progbits
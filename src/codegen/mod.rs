@@ -1,22 +1,25 @@
-use crate::{err::ErrorHandler, structure::{AstNodeType, AstNodeValue, Scope}};
-use std::process::exit;
+mod regalloc;
+
+use crate::{err::ErrorHandler, ir::{BinaryOp, BlockId, CastOp, Function, Global, GlobalData, Instruction, IrType, Operand, Program, Terminator, Value}};
+use regalloc::{Allocation, Location};
 
 /// Ooh, my first documentation - I'm excited!
 /// Code generation module for my compiler.
 /// Sorry, ill make this more official looking later.
-/// K so - the Sabbah runtime is stored at ~/.sabbah/runtime.s (sorry)
+/// K so - the Sabbah runtime lives in runtime/runtime.s and is bundled into sabbahc.
 /// The idea is that Sabbah functions are translated to Assembly labels.
 /// And because a language with a _start entrypoint is a bit weird,
 /// I made it so the entrypoint is called `main`.
 /// And the Sabbah runtime calls it in _start.
-/// (That's why you can't have a Sabbah function starting with an underscore.)
+/// Every other function gets a mangled label (see src/mangle), so a Sabbah
+/// function starting with an underscore can't collide with _start anymore.
 /// Andddddddddddddddddddddddddd this is my codegen struct.
 /// Have fun, because this is shit!
 ///
 ///
 
 pub struct CodeGenerator<'a> {
-    input: Scope<'a>,
+    input: Program,
     error_handler: &'a ErrorHandler,
     indent: u16,
 }
@@ -67,12 +70,14 @@ impl Generated {
                 generated.push_str(entry);
             }
         }
+        // Without it, the linker makes the stack executable
+        generated.push_str(".section .note.GNU-stack,\"\",@progbits\n");
         generated
     }
 }
 
 impl<'a> CodeGenerator<'a> {
-    pub fn new(input: Scope<'a>, error_handler: &'a ErrorHandler) -> Self {
+    pub fn new(input: Program, error_handler: &'a ErrorHandler) -> Self {
         Self {
             input,
             error_handler,
@@ -82,33 +87,591 @@ impl<'a> CodeGenerator<'a> {
     pub fn out(&mut self) -> String {
         let mut generated = Generated::new();
 
-        let i: usize = 0;
-        while let Some(statement) = self.input.children.get(i) {
-            let j: usize = 0;
-            while let Some(node) = statement.children.get(j) {
-                match node.node_type {
-                    AstNodeType::FunctionIdent => {
-                        // get full function
-                        j += 1;
-                        let function_name: String;
-                        if let Some(AstNodeType::FunctionIdent) = statement.children.get(j) {
-                            j += 1;
-                            if let Some(AstNodeValue::FunctionIdent(name)) = statement.children.get(j) {
-                                function_name = name;
-                            } else {
-                                self.error_handler.comperr(
-                                    node.line,
-                                    node.column,
-                                    String::from("expected function identifier node to have value"),
-                                    String::from("Please report this error to GitHub: https://github.com/AmosBarSinai/sabbahc/issues")
-                                );
+        for global in &self.input.globals {
+            global_data(global, &mut generated);
+        }
+
+        for function in &self.input.functions {
+            self.function(function, &mut generated);
+        }
+
+        generated.to_string()
+    }
+    fn function(&self, function: &Function, generated: &mut Generated) {
+        let allocation = regalloc::allocate(function);
+        let text = &mut generated.text.entries;
+
+        // Everything else stays local to the object file; main is the entry point
+        if function.exported || function.name == "main" {
+            text.push(format!(".globl {}\n", function.name));
+        }
+        text.push(format!("{}:\n", function.name));
+        text.push(String::from("    push %rbp\n    mov %rsp, %rbp\n"));
+        for register in &allocation.callee_saved {
+            text.push(format!("    push {}\n", register));
+        }
+        if allocation.frame_size > 0 {
+            text.push(format!("    sub ${}, %rsp\n", allocation.frame_size));
+        }
+        // Move the arguments where the allocator wants them. They are pushed
+        // first and popped afterwards, since the destination of one argument
+        // may be the register another one arrives in.
+        let register_params = function.params.len() - function.stack_params;
+        for register in &ARGUMENT_REGISTERS[..register_params] {
+            text.push(format!("    push {}\n", register));
+        }
+        // C callers leave the bits above an argument's type undefined
+        for param in function.params[..register_params].iter().rev() {
+            if function.exported {
+                text.push(String::from("    pop %rax\n"));
+                text.push(truncate(function.value_types[param.0 as usize]));
+                text.push(format!("    mov %rax, {}\n", location(&allocation, *param)));
+            } else {
+                text.push(format!("    pop {}\n", location(&allocation, *param)));
+            }
+        }
+        for (i, param) in function.params.iter().enumerate().skip(register_params) {
+            // Stack arguments sit above the return address and saved %rbp
+            let offset = 16 + (i - register_params) * 8;
+            text.push(format!("    mov {}(%rbp), %rax\n", offset));
+            if function.exported {
+                text.push(truncate(function.value_types[param.0 as usize]));
+            }
+            text.push(format!("    mov %rax, {}\n", location(&allocation, *param)));
+        }
+
+        for block in &function.blocks {
+            text.push(format!("{}:\n", block_label(function, block.id)));
+            for instruction in &block.instructions {
+                match instruction {
+                    Instruction::Copy { dest, src, .. } => {
+                        text.push(format!("    mov {}, %rax\n", operand(&allocation, src)));
+                        text.push(format!("    mov %rax, {}\n", location(&allocation, *dest)));
+                    }
+                    Instruction::Binary { dest, ty, op, lhs, rhs } => {
+                        text.push(format!("    mov {}, %rax\n", operand(&allocation, lhs)));
+                        text.push(format!("    mov {}, %rcx\n", operand(&allocation, rhs)));
+                        if let BinaryOp::SDiv | BinaryOp::SRem = op {
+                            text.push(sign_extend(*ty));
+                        }
+                        if let Some(code) = overflow(*op, *ty) {
+                            text.push(code);
+                            text.push(format!("    mov %rax, {}\n", location(&allocation, *dest)));
+                            continue;
+                        }
+                        text.push(String::from(match op {
+                            BinaryOp::Add => "    add %rcx, %rax\n",
+                            BinaryOp::Sub => "    sub %rcx, %rax\n",
+                            BinaryOp::Mul => "    imul %rcx, %rax\n",
+                            BinaryOp::UDiv => "    xor %edx, %edx\n    div %rcx\n",
+                            BinaryOp::URem => "    xor %edx, %edx\n    div %rcx\n    mov %rdx, %rax\n",
+                            BinaryOp::SDiv => "    cqo\n    idiv %rcx\n",
+                            BinaryOp::SRem => "    cqo\n    idiv %rcx\n    mov %rdx, %rax\n",
+                            BinaryOp::Shl => "    shl %cl, %rax\n",
+                            BinaryOp::LShr => "    shr %cl, %rax\n",
+                            BinaryOp::And => "    and %rcx, %rax\n",
+                            BinaryOp::Or => "    or %rcx, %rax\n",
+                            BinaryOp::ULt => "    cmp %rcx, %rax\n    setb %al\n    movzbl %al, %eax\n",
+                            BinaryOp::ULe => "    cmp %rcx, %rax\n    setbe %al\n    movzbl %al, %eax\n",
+                            BinaryOp::UAddOverflow
+                            | BinaryOp::SAddOverflow
+                            | BinaryOp::USubOverflow
+                            | BinaryOp::SSubOverflow
+                            | BinaryOp::UMulOverflow
+                            | BinaryOp::SMulOverflow => "",
+                        }));
+                        text.push(truncate(*ty));
+                        text.push(format!("    mov %rax, {}\n", location(&allocation, *dest)));
+                    }
+                    Instruction::Cast { dest, op, from, to, src } => {
+                        text.push(format!("    mov {}, %rax\n", operand(&allocation, src)));
+                        if *op == CastOp::SExt {
+                            text.push(String::from(match from {
+                                IrType::I8 => "    movsbq %al, %rax\n",
+                                IrType::I16 => "    movswq %ax, %rax\n",
+                                IrType::I32 => "    movslq %eax, %rax\n",
+                                IrType::I64 => "",
+                            }));
+                        }
+                        // Zero-extending is free, as every value already is
+                        if *op != CastOp::ZExt {
+                            text.push(truncate(*to));
+                        }
+                        text.push(format!("    mov %rax, {}\n", location(&allocation, *dest)));
+                    }
+                    Instruction::Call { dests, function: callee, args } => {
+                        let (stack_args, returned_in_memory) = self.convention(callee);
+                        if returned_in_memory > 0 {
+                            // The result goes to an area reserved above the arguments,
+                            // sized to keep %rsp 16-byte aligned
+                            let area = (returned_in_memory * 8).next_multiple_of(16);
+                            text.push(format!("    sub ${}, %rsp\n", area));
+                            self.call(&allocation, callee, args, stack_args, true, text);
+                            for (i, dest) in dests.iter().enumerate() {
+                                text.push(format!("    mov {}(%rsp), %rax\n", i * 8));
+                                text.push(format!("    mov %rax, {}\n", location(&allocation, *dest)));
+                            }
+                            text.push(format!("    add ${}, %rsp\n", area));
+                            continue;
+                        }
+                        self.call(&allocation, callee, args, stack_args, false, text);
+                        let external = self.input.externs.iter().any(|external| external.name == *callee);
+                        for (dest, register) in dests.iter().zip(RETURN_REGISTERS) {
+                            if external {
+                                // C leaves the bits above the return type undefined
+                                text.push(truncate(function.value_types[dest.0 as usize]));
+                            }
+                            text.push(format!("    mov {}, {}\n", register, location(&allocation, *dest)));
+                        }
+                    }
+                    Instruction::Load { dest, ty, global } => {
+                        let load = match ty {
+                            IrType::I8 => "movzbl",
+                            IrType::I16 => "movzwl",
+                            IrType::I32 | IrType::I64 => "mov",
+                        };
+                        text.push(format!("    {} {}(%rip), {}\n", load, global, accumulator(*ty)));
+                        text.push(format!("    mov %rax, {}\n", location(&allocation, *dest)));
+                    }
+                    Instruction::Address { dest, global } => {
+                        text.push(format!("    lea {}(%rip), %rax\n", global));
+                        text.push(format!("    mov %rax, {}\n", location(&allocation, *dest)));
+                    }
+                    Instruction::Alloca { dest, .. } => {
+                        text.push(format!("    lea -{}(%rbp), %rax\n", allocation.slots[dest]));
+                        text.push(format!("    mov %rax, {}\n", location(&allocation, *dest)));
+                    }
+                    Instruction::LoadAt { dest, ty, address } => {
+                        let load = match ty {
+                            IrType::I8 => "movzbl",
+                            IrType::I16 => "movzwl",
+                            IrType::I32 | IrType::I64 => "mov",
+                        };
+                        text.push(format!("    mov {}, %rcx\n", operand(&allocation, address)));
+                        text.push(format!("    {} (%rcx), {}\n", load, accumulator(*ty)));
+                        text.push(format!("    mov %rax, {}\n", location(&allocation, *dest)));
+                    }
+                    Instruction::StoreAt { ty, address, src } => {
+                        text.push(format!("    mov {}, %rcx\n", operand(&allocation, address)));
+                        text.push(format!("    mov {}, %rax\n", operand(&allocation, src)));
+                        text.push(format!("    mov {}, (%rcx)\n", sized_accumulator(*ty)));
+                    }
+                    Instruction::Store { ty, global, src } => {
+                        text.push(format!("    mov {}, %rax\n", operand(&allocation, src)));
+                        text.push(format!("    mov {}, {}(%rip)\n", sized_accumulator(*ty), global));
+                    }
+                    Instruction::Syscall { dest, ty, args } => {
+                        for arg in args {
+                            text.push(format!("    mov {}, %rax\n    push %rax\n", operand(&allocation, arg)));
+                        }
+                        for register in SYSCALL_REGISTERS[..args.len()].iter().rev() {
+                            text.push(format!("    pop {}\n", register));
+                        }
+                        text.push(String::from("    syscall\n"));
+                        text.push(truncate(*ty));
+                        text.push(format!("    mov %rax, {}\n", location(&allocation, *dest)));
+                    }
+                    Instruction::Asm { template, inputs, outputs } => {
+                        self.asm(function, &allocation, template, inputs, outputs, text);
+                    }
+                    // Moved into place by the blocks jumping here
+                    Instruction::Phi { .. } => {}
+                }
+            }
+            match &block.terminator {
+                Terminator::Return(values) => {
+                    match values.split_first() {
+                        Some((address, values)) if function.returns_in_memory() => {
+                            // Write the result where the caller asked, and hand back the address like C does
+                            text.push(format!("    mov {}, %rcx\n", operand(&allocation, address)));
+                            for (i, value) in values.iter().enumerate() {
+                                text.push(format!("    mov {}, %rax\n", operand(&allocation, value)));
+                                text.push(format!("    mov %rax, {}(%rcx)\n", i * 8));
                             }
+                            text.push(String::from("    mov %rcx, %rax\n"));
+                        }
+                        _ => {
+                            for (value, register) in values.iter().zip(RETURN_REGISTERS) {
+                                text.push(format!("    mov {}, {}\n", operand(&allocation, value), register));
+                            }
+                        }
+                    }
+                    if !allocation.callee_saved.is_empty() {
+                        text.push(format!("    lea -{}(%rbp), %rsp\n", allocation.callee_saved.len() * 8));
+                        for register in allocation.callee_saved.iter().rev() {
+                            text.push(format!("    pop {}\n", register));
                         }
                     }
+                    text.push(String::from("    leave\n    ret\n"));
                 }
+                Terminator::Jump(target) => {
+                    phi_moves(function, &allocation, block.id, *target, text);
+                    text.push(format!("    jmp {}\n", block_label(function, *target)));
+                }
+                Terminator::Branch { condition, then_block, else_block } => {
+                    // Nothing since an overflow operation right before has
+                    // touched the flags, so it can jump on the one it set
+                    let flag = match block.instructions.last() {
+                        Some(Instruction::Binary { dest, op, .. }) if *condition == Operand::Value(*dest) => overflow_flag(*op),
+                        _ => None,
+                    };
+                    match flag {
+                        Some(flag) => text.push(format!("    j{} {}\n", flag, block_label(function, *then_block))),
+                        None => {
+                            text.push(format!("    mov {}, %rax\n", operand(&allocation, condition)));
+                            text.push(String::from("    test %rax, %rax\n"));
+                            text.push(format!("    jnz {}\n", block_label(function, *then_block)));
+                        }
+                    }
+                    text.push(format!("    jmp {}\n", block_label(function, *else_block)));
+                }
+                Terminator::Switch { value, cases, default } => {
+                    text.push(format!("    mov {}, %rax\n", operand(&allocation, value)));
+                    switch(function, block.id, cases, *default, text, &mut generated.rodata.entries);
+                }
+                Terminator::Unreachable => {}
             }
         }
+    }
+    /// Splices an asm block into the text. Every operand gets an 8-byte
+    /// stack slot, outputs first, so `{name}` becomes `offset(%rsp)` no matter
+    /// where the allocator put the value. Inputs are copied in before the
+    /// block runs and outputs copied out after it.
+    fn asm(
+        &self,
+        function: &Function,
+        allocation: &Allocation,
+        template: &[String],
+        inputs: &[(String, Operand)],
+        outputs: &[(String, Value)],
+        text: &mut Vec<String>,
+    ) {
+        let slots = inputs.len() + outputs.len();
+        // Keep %rsp 16-byte aligned in case the block calls something
+        let padding = if slots % 2 == 1 { 8 } else { 0 };
+        if padding > 0 {
+            text.push(format!("    sub ${}, %rsp\n", padding));
+        }
+        for (_, input) in inputs.iter().rev() {
+            text.push(format!("    mov {}, %rax\n    push %rax\n", operand(allocation, input)));
+        }
+        if !outputs.is_empty() {
+            text.push(format!("    sub ${}, %rsp\n", outputs.len() * 8));
+        }
+        let names = outputs.iter().map(|(name, _)| name).chain(inputs.iter().map(|(name, _)| name));
+        let slots_by_name: Vec<(String, String)> =
+            names.enumerate().map(|(i, name)| (format!("{{{}}}", name), format!("{}(%rsp)", i * 8))).collect();
+        for line in template {
+            let mut line = line.clone();
+            for (placeholder, slot) in &slots_by_name {
+                line = line.replace(placeholder, slot);
+            }
+            text.push(format!("    {}\n", line));
+        }
+        for (i, (_, dest)) in outputs.iter().enumerate() {
+            text.push(format!("    mov {}(%rsp), %rax\n", i * 8));
+            // The block may have written more than the output's width
+            text.push(truncate(function.value_types[dest.0 as usize]));
+            text.push(format!("    mov %rax, {}\n", location(allocation, *dest)));
+        }
+        if slots > 0 {
+            text.push(format!("    add ${}, %rsp\n", slots * 8 + padding));
+        }
+    }
+    /// How a call to `callee` passes its arguments and result: how many of
+    /// the arguments go on the stack, and how many scalars it returns in
+    /// memory (none if it returns in registers). Runtime functions only take
+    /// and return scalars in registers.
+    fn convention(&self, callee: &str) -> (usize, usize) {
+        if let Some(function) = self.input.functions.iter().find(|function| function.name == callee) {
+            let returned = if function.returns_in_memory() { function.return_types.len() } else { 0 };
+            return (function.stack_params, returned);
+        }
+        if let Some(external) = self.input.externs.iter().find(|external| external.name == callee) {
+            let returned = if external.returns_in_memory() { external.return_types.len() } else { 0 };
+            return (external.stack_params, returned);
+        }
+        (0, 0)
+    }
+    /// Emits a System V call. Arguments are pushed and then popped into the
+    /// argument registers, so reading one argument never sees a register
+    /// already overwritten by another. Nothing needs saving around the call:
+    /// values live across calls are only ever given callee-saved registers.
+    /// A callee returning in memory gets the address of the area reserved
+    /// right above the stack arguments in %rdi, before the other arguments.
+    fn call(
+        &self,
+        allocation: &Allocation,
+        callee: &str,
+        args: &[Operand],
+        stack_args: usize,
+        returns_in_memory: bool,
+        text: &mut Vec<String>,
+    ) {
+        let register_args = args.len() - stack_args;
+        let registers = if returns_in_memory { &ARGUMENT_REGISTERS[1..] } else { &ARGUMENT_REGISTERS[..] };
+        // %rsp must be 16-byte aligned at the call instruction
+        let padding = if stack_args % 2 == 1 { 8 } else { 0 };
+        if padding > 0 {
+            text.push(format!("    sub ${}, %rsp\n", padding));
+        }
+        for arg in args[register_args..].iter().rev() {
+            text.push(format!("    mov {}, %rax\n    push %rax\n", operand(allocation, arg)));
+        }
+        for arg in &args[..register_args] {
+            text.push(format!("    mov {}, %rax\n    push %rax\n", operand(allocation, arg)));
+        }
+        for register in registers[..register_args].iter().rev() {
+            text.push(format!("    pop {}\n", register));
+        }
+        if returns_in_memory {
+            text.push(format!("    lea {}(%rsp), %rdi\n", stack_args * 8 + padding));
+        }
+        text.push(format!("    call {}\n", callee));
+        if stack_args > 0 {
+            text.push(format!("    add ${}, %rsp\n", stack_args * 8 + padding));
+        }
+    }
+}
 
-        generated.to_string()
+/// Moves the operands of `target`'s phis that come from `from` into place.
+/// Like call arguments, they are all pushed before any is popped, since one
+/// phi may be given the register another one's operand is in.
+fn phi_moves(function: &Function, allocation: &Allocation, from: BlockId, target: BlockId, text: &mut Vec<String>) {
+    let Some(target) = function.blocks.iter().find(|block| block.id == target) else {
+        return;
+    };
+    let moves: Vec<(Value, Operand)> = target
+        .instructions
+        .iter()
+        .filter_map(|instruction| match instruction {
+            Instruction::Phi { dest, incoming, .. } => {
+                incoming.iter().find(|(block, _)| *block == from).map(|(_, src)| (*dest, *src))
+            }
+            _ => None,
+        })
+        .collect();
+    for (_, src) in &moves {
+        text.push(format!("    mov {}, %rax\n    push %rax\n", operand(allocation, src)));
+    }
+    for (dest, _) in moves.iter().rev() {
+        text.push(format!("    pop {}\n", location(allocation, *dest)));
+    }
+}
+
+/// Jumps on the value in %rax. Dense cases go through a table in .rodata
+/// holding each target's offset from the table, which needs no relocations
+/// in a shared library; sparse ones are compared one range at a time.
+fn switch(
+    function: &Function,
+    from: BlockId,
+    cases: &[(u64, u64, BlockId)],
+    default: BlockId,
+    text: &mut Vec<String>,
+    rodata: &mut Vec<String>,
+) {
+    let (Some(low), Some(high)) = (cases.iter().map(|case| case.0).min(), cases.iter().map(|case| case.1).max()) else {
+        text.push(format!("    jmp {}\n", block_label(function, default)));
+        return;
+    };
+    let span = high - low;
+    if cases.len() >= JUMP_TABLE_CASES && span < cases.len() as u64 * JUMP_TABLE_DENSITY {
+        let table = format!("{}_table", block_label(function, from));
+        if low != 0 {
+            text.push(immediate("sub", low, "%rax"));
+        }
+        text.push(immediate("cmp", span, "%rax"));
+        text.push(format!("    ja {}\n", block_label(function, default)));
+        text.push(format!("    lea {}(%rip), %rcx\n", table));
+        text.push(String::from("    movslq (%rcx,%rax,4), %rax\n    add %rcx, %rax\n    jmp *%rax\n"));
+        let mut entries = format!("    .balign 4\n{}:\n", table);
+        for value in low..=high {
+            let target = cases.iter().find(|(low, high, _)| (*low..=*high).contains(&value)).map_or(default, |case| case.2);
+            entries.push_str(&format!("    .long {} - {}\n", block_label(function, target), table));
+        }
+        rodata.push(entries);
+        return;
+    }
+    for (low, high, target) in cases {
+        if low == high {
+            text.push(immediate("cmp", *low, "%rax"));
+            text.push(format!("    je {}\n", block_label(function, *target)));
+        } else {
+            // value - low <= high - low, unsigned, holds exactly for values in the range
+            text.push(String::from("    mov %rax, %rdx\n"));
+            text.push(immediate("sub", *low, "%rdx"));
+            text.push(immediate("cmp", high - low, "%rdx"));
+            text.push(format!("    jbe {}\n", block_label(function, *target)));
+        }
+    }
+    text.push(format!("    jmp {}\n", block_label(function, default)));
+}
+
+/// `instruction $value, register`, going through %rcx for values that do not
+/// fit in the sign-extended 32-bit immediate most instructions take.
+fn immediate(instruction: &str, value: u64, register: &str) -> String {
+    if value <= i32::MAX as u64 {
+        format!("    {} ${}, {}\n", instruction, value, register)
+    } else {
+        format!("    mov ${}, %rcx\n    {} %rcx, {}\n", value, instruction, register)
     }
-}
\ No newline at end of file
+}
+
+/// Places a global in .rodata if it is immutable, in .bss if it starts out
+/// as zero and in .data otherwise.
+fn global_data(global: &Global, generated: &mut Generated) {
+    let (align, contents, zero) = match &global.data {
+        GlobalData::Scalar(ty, value) => {
+            let size = ty.bits() / 8;
+            let contents = if *value == 0 {
+                format!("    .zero {}\n", size)
+            } else {
+                format!("    {} {}\n", directive(*ty), value)
+            };
+            (size, contents, *value == 0)
+        }
+        GlobalData::Array(ty, values) => {
+            let size = ty.bits() / 8;
+            if values.iter().all(|value| *value == 0) {
+                (size, format!("    .zero {}\n", size * values.len() as u32), true)
+            } else {
+                let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                (size, format!("    {} {}\n", directive(*ty), values.join(", ")), false)
+            }
+        }
+        GlobalData::Bytes(bytes) => {
+            // Octal escapes are the only ones `as` understands for arbitrary bytes
+            let escaped: String = bytes
+                .iter()
+                .map(|byte| match byte {
+                    b'"' | b'\\' => format!("\\{}", *byte as char),
+                    b' '..=b'~' => (*byte as char).to_string(),
+                    _ => format!("\\{:03o}", byte),
+                })
+                .collect();
+            if bytes.iter().all(|byte| *byte == 0) {
+                (1, format!("    .zero {}\n", bytes.len()), true)
+            } else {
+                (1, format!("    .ascii \"{}\"\n", escaped), false)
+            }
+        }
+    };
+    let section = if !global.mutable {
+        &mut generated.rodata
+    } else if zero {
+        &mut generated.bss
+    } else {
+        &mut generated.data
+    };
+    section.entries.push(format!("    .balign {}\n{}:\n{}", align, global.name, contents));
+}
+
+/// The data directive for a value of type `ty`.
+fn directive(ty: IrType) -> &'static str {
+    match ty {
+        IrType::I8 => ".byte",
+        IrType::I16 => ".word",
+        IrType::I32 => ".long",
+        IrType::I64 => ".quad",
+    }
+}
+
+/// %rax at the width that zero-extends into all of it when written.
+fn accumulator(ty: IrType) -> &'static str {
+    match ty {
+        IrType::I64 => "%rax",
+        _ => "%eax",
+    }
+}
+
+/// The part of %rax a value of type `ty` is stored from.
+fn sized_accumulator(ty: IrType) -> &'static str {
+    match ty {
+        IrType::I8 => "%al",
+        IrType::I16 => "%ax",
+        IrType::I32 => "%eax",
+        IrType::I64 => "%rax",
+    }
+}
+
+/// A switch with at least this many cases jumps through a table...
+const JUMP_TABLE_CASES: usize = 4;
+/// ...unless the table would need more than this many entries per case.
+const JUMP_TABLE_DENSITY: u64 = 3;
+
+const ARGUMENT_REGISTERS: [&str; 6] = ["%rdi", "%rsi", "%rdx", "%rcx", "%r8", "%r9"];
+const RETURN_REGISTERS: [&str; 2] = ["%rax", "%rdx"];
+/// The syscall number goes in %rax; the kernel clobbers %rcx and %r11
+const SYSCALL_REGISTERS: [&str; 7] = ["%rax", "%rdi", "%rsi", "%rdx", "%r10", "%r8", "%r9"];
+
+fn block_label(function: &Function, block: BlockId) -> String {
+    format!(".L{}_{}", function.name, block)
+}
+
+fn location(allocation: &Allocation, value: Value) -> String {
+    match allocation.location(value) {
+        Location::Register(register) => register.to_string(),
+        Location::Stack(offset) => format!("-{}(%rbp)", offset),
+    }
+}
+
+fn operand(allocation: &Allocation, operand: &Operand) -> String {
+    match operand {
+        Operand::Value(value) => location(allocation, *value),
+        Operand::Const(constant) => format!("${}", constant),
+    }
+}
+
+/// Sign-extends both operands of a signed division, in %rax and %rcx, to 64 bits.
+fn sign_extend(ty: IrType) -> String {
+    String::from(match ty {
+        IrType::I8 => "    movsbq %al, %rax\n    movsbq %cl, %rcx\n",
+        IrType::I16 => "    movswq %ax, %rax\n    movswq %cx, %rcx\n",
+        IrType::I32 => "    movslq %eax, %rax\n    movslq %ecx, %rcx\n",
+        IrType::I64 => "",
+    })
+}
+
+/// The condition code of the flag an overflow operation sets when it
+/// overflows: carry for unsigned integers, overflow for signed ones.
+fn overflow_flag(op: BinaryOp) -> Option<&'static str> {
+    match op {
+        BinaryOp::UAddOverflow | BinaryOp::USubOverflow | BinaryOp::UMulOverflow => Some("c"),
+        BinaryOp::SAddOverflow | BinaryOp::SSubOverflow | BinaryOp::SMulOverflow => Some("o"),
+        _ => None,
+    }
+}
+
+/// Sets %rax to 1 if `op`, on %rax and %rcx, overflows `ty`, or else to 0.
+/// The operation is done at the type's own width, so that the flags see
+/// it overflow, and they are left as it set them.
+fn overflow(op: BinaryOp, ty: IrType) -> Option<String> {
+    let flag = overflow_flag(op)?;
+    let (lhs, rhs) = match ty {
+        IrType::I8 => ("%al", "%cl"),
+        IrType::I16 => ("%ax", "%cx"),
+        IrType::I32 => ("%eax", "%ecx"),
+        IrType::I64 => ("%rax", "%rcx"),
+    };
+    let instruction = match op {
+        BinaryOp::UAddOverflow | BinaryOp::SAddOverflow => format!("add {}, {}", rhs, lhs),
+        BinaryOp::USubOverflow | BinaryOp::SSubOverflow => format!("sub {}, {}", rhs, lhs),
+        // The high half of the product goes to %ah or %rdx, and the flags
+        // say whether it was needed
+        BinaryOp::UMulOverflow => format!("mul {}", rhs),
+        // imul only has a two-operand form from 16 bits up
+        BinaryOp::SMulOverflow if ty == IrType::I8 => format!("imul {}", rhs),
+        _ => format!("imul {}, {}", rhs, lhs),
+    };
+    Some(format!("    {}\n    set{} %al\n    movzbl %al, %eax\n", instruction, flag))
+}
+
+/// Values are kept zero-extended to 64 bits, so results are truncated to their type.
+fn truncate(ty: IrType) -> String {
+    String::from(match ty {
+        IrType::I8 => "    movzbl %al, %eax\n",
+        IrType::I16 => "    movzwl %ax, %eax\n",
+        IrType::I32 => "    mov %eax, %eax\n",
+        IrType::I64 => "",
+    })
+}
@@ -0,0 +1,263 @@
+//! Linear-scan register allocation over a function's virtual registers.
+//!
+//! %rax, %rcx and %rdx are never allocated: the code generator uses them as
+//! scratch registers (and `div` clobbers %rdx anyway). Values that are live
+//! across a call (or a syscall, which clobbers the same registers) only get
+//! callee-saved registers, so nothing has to be saved around calls; every
+//! other value prefers the caller-saved ones, which are free to use.
+//! Whatever does not fit is spilled to the stack frame.
+//!
+//! An asm block may write any register, so values live across one are
+//! always spilled, and a function with one saves every callee-saved
+//! register for its caller.
+//!
+//! The stack slots of `alloca`s go below the spilled values.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ir::{Function, Instruction, Operand, Value};
+
+pub const CALLER_SAVED: [&str; 6] = ["%rsi", "%rdi", "%r8", "%r9", "%r10", "%r11"];
+pub const CALLEE_SAVED: [&str; 5] = ["%rbx", "%r12", "%r13", "%r14", "%r15"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Register(&'static str),
+    /// Offset below %rbp
+    Stack(u64),
+}
+
+pub struct Allocation {
+    /// Indexed by `Value`
+    pub locations: Vec<Location>,
+    /// Callee-saved registers the function uses, which it must preserve
+    pub callee_saved: Vec<&'static str>,
+    /// Bytes of spill and alloca slots below the saved registers
+    pub frame_size: u64,
+    /// The offset below %rbp of each alloca's slot, by the value holding its address
+    pub slots: HashMap<Value, u64>,
+}
+
+impl Allocation {
+    pub fn location(&self, value: Value) -> Location {
+        self.locations[value.0 as usize]
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Interval {
+    value: Value,
+    start: usize,
+    end: usize,
+}
+
+pub fn allocate(function: &Function) -> Allocation {
+    let (intervals, calls, asm_blocks) = live_intervals(function);
+    let mut order: Vec<Interval> = intervals.iter().flatten().copied().collect();
+    order.sort_by_key(|interval| (interval.start, interval.value));
+
+    let mut registers: Vec<Option<&'static str>> = vec![None; function.value_types.len()];
+    let mut spilled: Vec<Value> = Vec::new();
+    let mut active: Vec<Interval> = Vec::new();
+    for interval in order {
+        // Expire intervals that ended before this one starts
+        active.retain(|other| other.end >= interval.start);
+
+        let crosses = |positions: &[usize]| positions.iter().any(|&at| interval.start < at && at < interval.end);
+        let pool: Vec<&'static str> = if crosses(&asm_blocks) {
+            Vec::new()
+        } else if crosses(&calls) {
+            CALLEE_SAVED.to_vec()
+        } else {
+            CALLER_SAVED.iter().chain(CALLEE_SAVED.iter()).copied().collect()
+        };
+        let taken: HashSet<&'static str> =
+            active.iter().filter_map(|other| registers[other.value.0 as usize]).collect();
+        if let Some(register) = pool.iter().find(|register| !taken.contains(*register)) {
+            registers[interval.value.0 as usize] = Some(register);
+            active.push(interval);
+            continue;
+        }
+
+        // Out of registers: spill whichever interval lives longest
+        let victim = active
+            .iter()
+            .enumerate()
+            .filter(|(_, other)| pool.contains(&registers[other.value.0 as usize].unwrap()))
+            .max_by_key(|(_, other)| other.end)
+            .map(|(i, other)| (i, *other));
+        match victim {
+            Some((i, victim)) if victim.end > interval.end => {
+                registers[interval.value.0 as usize] = registers[victim.value.0 as usize].take();
+                spilled.push(victim.value);
+                active.remove(i);
+                active.push(interval);
+            }
+            _ => spilled.push(interval.value),
+        }
+    }
+
+    let mut callee_saved: Vec<&'static str> = CALLEE_SAVED
+        .iter()
+        .copied()
+        .filter(|register| !asm_blocks.is_empty() || registers.contains(&Some(*register)))
+        .collect();
+    callee_saved.sort_by_key(|register| CALLEE_SAVED.iter().position(|r| r == register));
+
+    let saved_size = callee_saved.len() as u64 * 8;
+    let mut locations: Vec<Location> = registers
+        .iter()
+        .map(|register| Location::Register(register.unwrap_or("%rax")))
+        .collect();
+    spilled.sort();
+    for (slot, value) in spilled.iter().enumerate() {
+        locations[value.0 as usize] = Location::Stack(saved_size + (slot as u64 + 1) * 8);
+    }
+    // %rbp is 16-byte aligned, so an offset that is a multiple of a slot's
+    // alignment (at most 16) gives an aligned address
+    let mut bottom = saved_size + spilled.len() as u64 * 8;
+    let mut slots: HashMap<Value, u64> = HashMap::new();
+    for block in &function.blocks {
+        for instruction in &block.instructions {
+            if let Instruction::Alloca { dest, size, align } = instruction {
+                bottom = (bottom + size).next_multiple_of(*align);
+                slots.insert(*dest, bottom);
+            }
+        }
+    }
+    // Keep %rsp 16-byte aligned: the return address and saved %rbp take 16 bytes
+    let frame_size = bottom.next_multiple_of(16) - saved_size;
+
+    Allocation { locations, callee_saved, frame_size, slots }
+}
+
+/// Numbers every instruction and terminator in block order and computes, for
+/// each value, the range of positions over which it is live. Also returns the
+/// positions of calls and syscalls, and those of asm blocks.
+///
+/// A phi's operands are used, and its destination written, at the end of
+/// the block each operand comes from, where the code generator moves them.
+fn live_intervals(function: &Function) -> (Vec<Option<Interval>>, Vec<usize>, Vec<usize>) {
+    let block_count = function.blocks.len();
+    let index_of = |id| function.blocks.iter().position(|block| block.id == id).unwrap();
+    // (block, destination, operand) for each phi operand coming from a block
+    let mut phi_moves: Vec<(usize, Value, Operand)> = Vec::new();
+    for block in &function.blocks {
+        for instruction in &block.instructions {
+            if let Instruction::Phi { dest, incoming, .. } = instruction {
+                phi_moves.extend(incoming.iter().map(|(from, operand)| (index_of(*from), *dest, *operand)));
+            }
+        }
+    }
+    // The operands read where the instruction is
+    let local_operands = |instruction: &Instruction| match instruction {
+        Instruction::Phi { .. } => Vec::new(),
+        _ => instruction.operands(),
+    };
+
+    // Per-block uses (before any definition) and definitions
+    let mut uses: Vec<HashSet<Value>> = vec![HashSet::new(); block_count];
+    let mut defs: Vec<HashSet<Value>> = vec![HashSet::new(); block_count];
+    for (i, block) in function.blocks.iter().enumerate() {
+        let operands = block
+            .instructions
+            .iter()
+            .map(|instruction| (local_operands(instruction), instruction.dests()))
+            .chain(std::iter::once((block.terminator.operands(), Vec::new())));
+        for (operands, dests) in operands {
+            for operand in operands {
+                if let Operand::Value(value) = operand
+                    && !defs[i].contains(&value)
+                {
+                    uses[i].insert(value);
+                }
+            }
+            defs[i].extend(dests);
+        }
+    }
+    for (i, _, operand) in &phi_moves {
+        if let Operand::Value(value) = operand
+            && !defs[*i].contains(value)
+        {
+            uses[*i].insert(*value);
+        }
+    }
+
+    // Backwards dataflow until nothing changes
+    let mut live_in: Vec<HashSet<Value>> = vec![HashSet::new(); block_count];
+    let mut live_out: Vec<HashSet<Value>> = vec![HashSet::new(); block_count];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in (0..block_count).rev() {
+            let mut out: HashSet<Value> = HashSet::new();
+            for successor in function.blocks[i].terminator.successors() {
+                out.extend(live_in[index_of(successor)].iter().copied());
+            }
+            let mut inn: HashSet<Value> = uses[i].clone();
+            inn.extend(out.difference(&defs[i]).copied());
+            if inn != live_in[i] || out != live_out[i] {
+                live_in[i] = inn;
+                live_out[i] = out;
+                changed = true;
+            }
+        }
+    }
+
+    let mut intervals: Vec<Option<Interval>> = vec![None; function.value_types.len()];
+    let mut extend = |value: Value, position: usize| {
+        let interval = &mut intervals[value.0 as usize];
+        match interval {
+            Some(interval) => {
+                interval.start = interval.start.min(position);
+                interval.end = interval.end.max(position);
+            }
+            None => *interval = Some(Interval { value, start: position, end: position }),
+        }
+    };
+    // Parameters are defined on entry, at position 0
+    for param in &function.params {
+        extend(*param, 0);
+    }
+    let mut calls: Vec<usize> = Vec::new();
+    let mut asm_blocks: Vec<usize> = Vec::new();
+    let mut position = 1;
+    for (i, block) in function.blocks.iter().enumerate() {
+        let block_start = position;
+        for value in &live_in[i] {
+            extend(*value, block_start);
+        }
+        for instruction in &block.instructions {
+            for operand in local_operands(instruction) {
+                if let Operand::Value(value) = operand {
+                    extend(value, position);
+                }
+            }
+            for dest in instruction.dests() {
+                extend(dest, position);
+            }
+            match instruction {
+                Instruction::Call { .. } | Instruction::Syscall { .. } => calls.push(position),
+                Instruction::Asm { .. } => asm_blocks.push(position),
+                _ => {}
+            }
+            position += 1;
+        }
+        for operand in block.terminator.operands() {
+            if let Operand::Value(value) = operand {
+                extend(value, position);
+            }
+        }
+        for (_, dest, operand) in phi_moves.iter().filter(|(from, _, _)| *from == i) {
+            if let Operand::Value(value) = operand {
+                extend(*value, position);
+            }
+            extend(*dest, position);
+        }
+        for value in &live_out[i] {
+            extend(*value, position);
+        }
+        position += 1;
+    }
+    (intervals, calls, asm_blocks)
+}
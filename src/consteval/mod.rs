@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+
+use crate::{
+    err::{ErrorHandler, REPORT},
+    structure::{BinaryOperator, ConstDeclaration, Expression, ExpressionContent},
+    typing::{Type, TypeKind, BOOL},
+};
+
+/// A constant's value and declared type.
+#[derive(Debug, Clone, Copy)]
+pub struct Constant<'a> {
+    pub value: u64,
+    pub ty: &'a Type,
+}
+
+/// Evaluates `const` declarations at compile time by interpreting their
+/// expression AST.
+///
+/// Unlike at runtime, where arithmetic wraps, a constant expression that
+/// overflows its type or divides by zero is a compile error.
+pub struct ConstEvaluator<'s, 'a> {
+    error_handler: &'s ErrorHandler,
+    /// Top-level constants, which may be used before they are declared
+    declarations: HashMap<String, &'s ConstDeclaration<'a>>,
+    values: HashMap<String, Constant<'a>>,
+    /// Constants currently being evaluated, to catch cycles
+    in_progress: Vec<String>,
+}
+
+impl<'s, 'a> ConstEvaluator<'s, 'a> {
+    pub fn new(error_handler: &'s ErrorHandler) -> Self {
+        Self { error_handler, declarations: HashMap::new(), values: HashMap::new(), in_progress: Vec::new() }
+    }
+    pub fn declare(&mut self, declaration: &'s ConstDeclaration<'a>) {
+        if self.declarations.insert(declaration.name.clone(), declaration).is_some() {
+            self.error_handler.err(
+                declaration.line,
+                declaration.column,
+                format!("Duplicate constant '{}'", declaration.name),
+                None,
+            );
+        }
+    }
+    /// The top-level constant `name`, evaluating it on first use.
+    pub fn value(&mut self, name: &str) -> Option<Constant<'a>> {
+        if let Some(constant) = self.values.get(name) {
+            return Some(*constant);
+        }
+        let declaration = *self.declarations.get(name)?;
+        if self.in_progress.iter().any(|other| other == name) {
+            self.error_handler.err(
+                declaration.line,
+                declaration.column,
+                format!("Constant '{}' depends on itself", name),
+                None,
+            );
+        }
+        self.in_progress.push(name.to_string());
+        let value = self.evaluate(&declaration.value, declaration.const_type, &HashMap::new());
+        self.in_progress.pop();
+        let constant = Constant { value, ty: declaration.const_type };
+        self.values.insert(name.to_string(), constant);
+        Some(constant)
+    }
+    /// Evaluates `expr` as a value of type `ty`, returning its bits. `locals`
+    /// holds the constants declared so far in the enclosing function, which
+    /// shadow top-level ones.
+    ///
+    /// The only pointer constant is `null`.
+    pub fn evaluate(&mut self, expr: &Expression<'a>, ty: &Type, locals: &HashMap<String, Constant<'a>>) -> u64 {
+        if ty.kind() == TypeKind::Pointer {
+            if expr.content != ExpressionContent::Null {
+                self.error_handler.err(
+                    expr.line,
+                    expr.column,
+                    format!("A constant {} can only be null", ty),
+                    Some(String::from("Take addresses at runtime, in a function")),
+                );
+            }
+            return 0;
+        }
+        let value = self.integer(expr, ty, locals);
+        let bits = value as u64;
+        match ty.size() {
+            Some(size @ 1..=7) => bits & ((1u64 << (size * 8)) - 1),
+            _ => bits,
+        }
+    }
+    /// Evaluates an array literal of type `ty` element by element, returning
+    /// the bits of each integer or pointer in it, in memory order.
+    pub fn array(&mut self, expr: &Expression<'a>, ty: &Type, locals: &HashMap<String, Constant<'a>>) -> Vec<u64> {
+        let (Some(element), Some(length)) = (ty.element(), ty.length()) else {
+            return vec![self.evaluate(expr, ty, locals)];
+        };
+        let ExpressionContent::ArrayLiteral(values) = &expr.content else {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("A constant {} must be an array literal", ty),
+                None,
+            );
+        };
+        if values.len() as u64 != length {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Expected {} elements (found {})", length, values.len()),
+                None,
+            );
+        }
+        values.iter().flat_map(|value| self.array(value, element, locals)).collect()
+    }
+    /// Evaluates `expr` as a number, wide enough for any integer type.
+    fn integer(&mut self, expr: &Expression<'a>, ty: &Type, locals: &HashMap<String, Constant<'a>>) -> i128 {
+        let (min, max) = self.range(expr, ty);
+        match &expr.content {
+            ExpressionContent::IntLiteral(value) if ty.kind() == TypeKind::Bool => self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Expected bool (found {})", value),
+                Some(String::from("Write `true` or `false`")),
+            ),
+            ExpressionContent::IntLiteral(value) => {
+                let value = *value as i128;
+                if value > max {
+                    self.error_handler.err(
+                        expr.line,
+                        expr.column,
+                        format!("Literal {} does not fit in {}", value, ty),
+                        None,
+                    );
+                }
+                value
+            }
+            ExpressionContent::BoolLiteral(value) if ty.kind() == TypeKind::Bool => *value as i128,
+            ExpressionContent::BoolLiteral(_) => self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Expected {} (found bool)", ty),
+                None,
+            ),
+            ExpressionContent::Cast(value, to) => {
+                if *to != ty {
+                    self.error_handler.err(
+                        expr.line,
+                        expr.column,
+                        format!("Expected {} (found {})", ty, to),
+                        None,
+                    );
+                }
+                let (value, from) = self.operand(value, to, locals);
+                if !from.casts_to(to) {
+                    self.error_handler.err(
+                        expr.line,
+                        expr.column,
+                        format!("Cannot cast {} to {}", from, to),
+                        Some(String::from("Only integers, bool and pointers can be cast")),
+                    );
+                }
+                // Keep the bits that fit, read as the new type
+                let unused = 64 - 8 * to.size().unwrap_or(8) as u32;
+                let bits = (value as u64) << unused;
+                match to.kind() {
+                    TypeKind::Signed => ((bits as i64) >> unused) as i128,
+                    _ => (bits >> unused) as i128,
+                }
+            }
+            ExpressionContent::StrLiteral(_) => self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Expected {} (found str)", ty),
+                None,
+            ),
+            ExpressionContent::Variable(name) => match locals.get(name).copied().or_else(|| self.value(name)) {
+                Some(constant) if constant.ty != ty => self.error_handler.err(
+                    expr.line,
+                    expr.column,
+                    format!("Expected {} (found {})", ty, constant.ty),
+                    (constant.ty.is_integer() && ty.is_integer()).then(|| format!("Convert it with `as {}`", ty)),
+                ),
+                Some(constant) => signed(constant),
+                None => self.error_handler.err(
+                    expr.line,
+                    expr.column,
+                    format!("'{}' is not a constant", name),
+                    Some(String::from("Constant expressions may only use literals and other constants")),
+                ),
+            },
+            ExpressionContent::FieldAccess(..) | ExpressionContent::StructLiteral(..) => self.error_handler.err(
+                expr.line,
+                expr.column,
+                String::from("Structs cannot be used in a constant expression"),
+                Some(String::from("Constant expressions may only use literals and other constants")),
+            ),
+            ExpressionContent::Variant(..) => self.error_handler.err(
+                expr.line,
+                expr.column,
+                String::from("Enums cannot be used in a constant expression"),
+                Some(String::from("Constant expressions may only use literals and other constants")),
+            ),
+            ExpressionContent::Match(..) => self.error_handler.err(
+                expr.line,
+                expr.column,
+                String::from("Cannot match in a constant expression"),
+                Some(String::from("Constant expressions may only use literals and other constants")),
+            ),
+            ExpressionContent::AddressOf(_) | ExpressionContent::Deref(_) => self.error_handler.err(
+                expr.line,
+                expr.column,
+                String::from("Pointers cannot be used in a constant expression"),
+                Some(String::from("Constant expressions may only use literals and other constants")),
+            ),
+            ExpressionContent::Null => self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Expected {} (found null)", ty),
+                None,
+            ),
+            ExpressionContent::ArrayLiteral(_) | ExpressionContent::Index(..) | ExpressionContent::Slice(..) => {
+                self.error_handler.err(
+                    expr.line,
+                    expr.column,
+                    String::from("Arrays cannot be used in a constant expression"),
+                    Some(String::from("Constant expressions may only use literals and other constants")),
+                )
+            }
+            ExpressionContent::Call(name, _) => self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Cannot call '{}' in a constant expression", name),
+                Some(String::from("Constant expressions may only use literals and other constants")),
+            ),
+            ExpressionContent::Binary(operator, ..) if ty.kind() == TypeKind::Bool => self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Cannot use '{}' on bool", operator),
+                None,
+            ),
+            ExpressionContent::Binary(operator, lhs, rhs) => {
+                let lhs = self.integer(lhs, ty, locals);
+                let rhs = self.integer(rhs, ty, locals);
+                let result = match operator {
+                    BinaryOperator::Add => lhs.checked_add(rhs),
+                    BinaryOperator::Subtract => lhs.checked_sub(rhs),
+                    BinaryOperator::Multiply => lhs.checked_mul(rhs),
+                    BinaryOperator::Divide | BinaryOperator::Remainder if rhs == 0 => {
+                        self.error_handler.err(
+                            expr.line,
+                            expr.column,
+                            String::from("Division by zero in constant expression"),
+                            None,
+                        );
+                    }
+                    // Both round towards zero, like the div and idiv instructions
+                    BinaryOperator::Divide => lhs.checked_div(rhs),
+                    BinaryOperator::Remainder => lhs.checked_rem(rhs),
+                };
+                match result.filter(|result| (min..=max).contains(result)) {
+                    Some(result) => result,
+                    None => self.error_handler.err(
+                        expr.line,
+                        expr.column,
+                        format!("Constant expression {} {} {} overflows", lhs, operator, rhs),
+                        Some(format!("The result must be between {} and {}", min, max)),
+                    ),
+                }
+            }
+        }
+    }
+    /// The value and type of what is cast to `to`. Literals are of the type
+    /// they are cast to.
+    fn operand(&mut self, expr: &Expression<'a>, to: &'a Type, locals: &HashMap<String, Constant<'a>>) -> (i128, &'a Type) {
+        match &expr.content {
+            ExpressionContent::Variable(name) => match locals.get(name).copied().or_else(|| self.value(name)) {
+                Some(constant) => (signed(constant), constant.ty),
+                None => self.error_handler.err(
+                    expr.line,
+                    expr.column,
+                    format!("'{}' is not a constant", name),
+                    Some(String::from("Constant expressions may only use literals and other constants")),
+                ),
+            },
+            ExpressionContent::BoolLiteral(value) => (*value as i128, &BOOL),
+            ExpressionContent::Cast(_, ty) => (self.integer(expr, ty, locals), ty),
+            _ => (self.integer(expr, to, locals), to),
+        }
+    }
+    /// The smallest and largest values of `ty`.
+    fn range(&self, expr: &Expression<'a>, ty: &Type) -> (i128, i128) {
+        if ty.kind() == TypeKind::Bool {
+            return (0, 1);
+        }
+        if !ty.is_integer() {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Constants cannot be of type {}", ty),
+                Some(String::from("Only integers can be evaluated at compile time")),
+            );
+        }
+        let bits = match ty.size() {
+            Some(size @ 1..=8) => size * 8,
+            _ => self.error_handler.comperr(
+                expr.line,
+                expr.column,
+                format!("cannot evaluate a constant of type {}", ty),
+                Some(String::from(REPORT)),
+            ),
+        };
+        match ty.kind() {
+            TypeKind::Signed => (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1),
+            _ => (0, (1i128 << bits) - 1),
+        }
+    }
+}
+
+/// The number a constant's bits stand for.
+fn signed(constant: Constant) -> i128 {
+    match (constant.ty.kind(), constant.ty.size()) {
+        (TypeKind::Signed, Some(size @ 1..=8)) => {
+            let unused = 64 - size * 8;
+            (((constant.value << unused) as i64) >> unused) as i128
+        }
+        _ => constant.value as i128,
+    }
+}
@@ -0,0 +1,374 @@
+//! Debug dumps of the compiler's intermediate stages, for `--emit`.
+//! The plain variants reuse the Display impls; the JSON variants are meant
+//! for tooling, so they spell out every field.
+
+use std::path::Path;
+
+use crate::structure::{AstNode, AstNodeType, AstNodeValue, Expression, ExpressionContent, Pattern, Scope, Statement};
+use crate::tokenizer::{Token, TokenValue};
+use crate::typing::{self, Type, TypeKind, BUILTIN_TYPES};
+
+pub fn tokens(tokens: &[Token]) -> String {
+    let mut out = String::new();
+    for token in tokens {
+        out.push_str(&format!("{}:{} {}\n", token.line, token.column, token));
+    }
+    out
+}
+
+pub fn ast(scope: &Scope) -> String {
+    format!("{}\n", scope)
+}
+
+pub fn tokens_json(tokens: &[Token]) -> String {
+    let entries: Vec<String> = tokens
+        .iter()
+        .map(|token| {
+            let value = match &token.value {
+                Some(TokenValue::FunctionIdent(ident)) => string(ident),
+                Some(TokenValue::TypeIdent(ty)) => type_json(ty),
+                Some(TokenValue::IntLiteral(value)) => value.to_string(),
+                Some(TokenValue::StrLiteral(value)) => string(value),
+                Some(TokenValue::CharLiteral(value)) => string(&value.to_string()),
+                Some(TokenValue::Ident(ident)) => string(ident),
+                None => String::from("null"),
+            };
+            format!(
+                "{{\"type\":{},\"line\":{},\"column\":{},\"value\":{}}}",
+                string(&token.token_type.to_string()),
+                token.line,
+                token.column,
+                value
+            )
+        })
+        .collect();
+    format!("[{}]\n", entries.join(","))
+}
+
+pub fn ast_json(scope: &Scope) -> String {
+    format!("{}\n", scope_json(scope))
+}
+
+fn scope_json(scope: &Scope) -> String {
+    let statements: Vec<String> = scope.children.iter().map(statement_json).collect();
+    format!("{{\"statements\":[{}]}}", statements.join(","))
+}
+
+fn statement_json(statement: &Statement) -> String {
+    let nodes: Vec<String> = statement.children.iter().map(node_json).collect();
+    format!("{{\"nodes\":[{}]}}", nodes.join(","))
+}
+
+fn node_json(node: &AstNode) -> String {
+    let value = match &node.value {
+        Some(AstNodeValue::Scope(scope)) => scope_json(scope),
+        Some(AstNodeValue::Expression(expr)) => expression_json(expr),
+        Some(AstNodeValue::FunctionIdent(ident)) => string(ident),
+        Some(AstNodeValue::TypeParams(names)) => {
+            let names: Vec<String> = names.iter().map(|name| string(name)).collect();
+            format!("[{}]", names.join(","))
+        }
+        Some(AstNodeValue::ParamTypeTuple(tuple)) => {
+            let params: Vec<String> = tuple
+                .params
+                .iter()
+                .map(|param| {
+                    format!("{{\"name\":{},\"type\":{}}}", string(&param.name), type_json(param.param_type))
+                })
+                .collect();
+            format!("[{}]", params.join(","))
+        }
+        Some(AstNodeValue::TypeIdent(ty)) => type_json(ty),
+        Some(AstNodeValue::Const(declaration)) => format!(
+            "{{\"name\":{},\"type\":{},\"value\":{}}}",
+            string(&declaration.name),
+            type_json(declaration.const_type),
+            expression_json(&declaration.value)
+        ),
+        Some(AstNodeValue::Let(binding)) => format!(
+            "{{\"name\":{},\"type\":{},\"value\":{}}}",
+            string(&binding.name),
+            binding.binding_type.map_or(String::from("null"), type_json),
+            expression_json(&binding.value)
+        ),
+        Some(AstNodeValue::Static(declaration)) => format!(
+            "{{\"name\":{},\"mutable\":{},\"type\":{},\"value\":{}}}",
+            string(&declaration.name),
+            declaration.mutable,
+            type_json(declaration.static_type),
+            declaration.value.as_ref().map_or(String::from("null"), expression_json)
+        ),
+        Some(AstNodeValue::Assignment(assignment)) => format!(
+            "{{\"target\":{},\"value\":{}}}",
+            expression_json(&assignment.target),
+            expression_json(&assignment.value)
+        ),
+        Some(AstNodeValue::Asm(block)) => {
+            let template: Vec<String> = block.template.iter().map(|line| string(line)).collect();
+            let inputs: Vec<String> = block
+                .inputs
+                .iter()
+                .map(|input| format!("{{\"name\":{},\"value\":{}}}", string(&input.name), expression_json(&input.value)))
+                .collect();
+            let outputs: Vec<String> = block.outputs.iter().map(|output| string(&output.target)).collect();
+            format!(
+                "{{\"template\":[{}],\"inputs\":[{}],\"outputs\":[{}]}}",
+                template.join(","),
+                inputs.join(","),
+                outputs.join(",")
+            )
+        }
+        Some(AstNodeValue::Abi(abi)) => string(abi),
+        Some(AstNodeValue::Attribute(name)) => string(name),
+        Some(AstNodeValue::Struct(ty)) | Some(AstNodeValue::Enum(ty)) => type_json(ty),
+        None => String::from("null"),
+    };
+    format!(
+        "{{\"type\":{},\"line\":{},\"column\":{},\"value\":{}}}",
+        string(&format!("{:?}", node.node_type)),
+        node.line,
+        node.column,
+        value
+    )
+}
+
+fn expression_json(expr: &Expression) -> String {
+    let content = match &expr.content {
+        ExpressionContent::IntLiteral(value) => format!("{{\"int\":{}}}", value),
+        ExpressionContent::StrLiteral(value) => format!("{{\"str\":{}}}", string(value)),
+        ExpressionContent::Variable(name) => format!("{{\"variable\":{}}}", string(name)),
+        ExpressionContent::Call(name, args) => {
+            let args: Vec<String> = args.iter().map(expression_json).collect();
+            format!("{{\"call\":{},\"args\":[{}]}}", string(name), args.join(","))
+        }
+        ExpressionContent::Binary(operator, lhs, rhs) => format!(
+            "{{\"binary\":{},\"lhs\":{},\"rhs\":{}}}",
+            string(&operator.to_string()),
+            expression_json(lhs),
+            expression_json(rhs)
+        ),
+        ExpressionContent::FieldAccess(value, field) => {
+            format!("{{\"field\":{},\"value\":{}}}", string(field), expression_json(value))
+        }
+        ExpressionContent::StructLiteral(name, fields) => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|field| format!("{{\"name\":{},\"value\":{}}}", string(&field.name), expression_json(&field.value)))
+                .collect();
+            format!("{{\"struct\":{},\"fields\":[{}]}}", string(name), fields.join(","))
+        }
+        ExpressionContent::Variant(name, variant, values) => {
+            let values: Vec<String> = values.iter().map(expression_json).collect();
+            format!("{{\"enum\":{},\"variant\":{},\"values\":[{}]}}", string(name), string(variant), values.join(","))
+        }
+        ExpressionContent::Match(value, arms) => {
+            let arms: Vec<String> = arms
+                .iter()
+                .map(|arm| format!("{{\"pattern\":{},\"value\":{}}}", pattern_json(&arm.pattern), expression_json(&arm.value)))
+                .collect();
+            format!("{{\"match\":{},\"arms\":[{}]}}", expression_json(value), arms.join(","))
+        }
+        ExpressionContent::AddressOf(name) => format!("{{\"address_of\":{}}}", string(name)),
+        ExpressionContent::Deref(pointer) => format!("{{\"deref\":{}}}", expression_json(pointer)),
+        ExpressionContent::Null => String::from("{\"null\":true}"),
+        ExpressionContent::BoolLiteral(value) => format!("{{\"bool\":{}}}", value),
+        ExpressionContent::Cast(value, ty) => {
+            format!("{{\"cast\":{},\"to\":{}}}", expression_json(value), type_json(ty))
+        }
+        ExpressionContent::ArrayLiteral(values) => {
+            let values: Vec<String> = values.iter().map(expression_json).collect();
+            format!("{{\"array\":[{}]}}", values.join(","))
+        }
+        ExpressionContent::Index(value, index) => {
+            format!("{{\"index\":{},\"value\":{}}}", expression_json(index), expression_json(value))
+        }
+        ExpressionContent::Slice(value, start, end) => {
+            let bound = |bound: &Option<Box<Expression>>| bound.as_deref().map(expression_json).unwrap_or(String::from("null"));
+            format!("{{\"slice\":{},\"start\":{},\"end\":{}}}", expression_json(value), bound(start), bound(end))
+        }
+    };
+    format!(
+        "{{\"type\":{},\"line\":{},\"column\":{},\"content\":{}}}",
+        expr.eval_type.map_or(String::from("null"), type_json),
+        expr.line,
+        expr.column,
+        content
+    )
+}
+
+fn pattern_json(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Wildcard => String::from("{\"wildcard\":true}"),
+        Pattern::Binding(name) => format!("{{\"binding\":{}}}", string(name)),
+        Pattern::Int(value) => format!("{{\"int\":{}}}", value),
+        Pattern::Bool(value) => format!("{{\"bool\":{}}}", value),
+        Pattern::Range(low, high) => format!("{{\"low\":{},\"high\":{}}}", low, high),
+        Pattern::Variant(name, variant, bindings) => {
+            let bindings: Vec<String> = bindings.iter().map(|binding| string(binding)).collect();
+            format!("{{\"enum\":{},\"variant\":{},\"bindings\":[{}]}}", string(name), string(variant), bindings.join(","))
+        }
+    }
+}
+
+fn type_json(ty: &Type) -> String {
+    let size = match ty.size() {
+        Some(size) => size.to_string(),
+        None => String::from("null"),
+    };
+    if ty.kind() == TypeKind::Enum {
+        let variants: Vec<String> = ty
+            .variants()
+            .iter()
+            .map(|variant| {
+                let fields: Vec<String> = variant
+                    .fields
+                    .iter()
+                    .map(|field| format!("{{\"type\":{},\"offset\":{}}}", type_json(&field.ty), field.offset))
+                    .collect();
+                format!(
+                    "{{\"name\":{},\"discriminant\":{},\"fields\":[{}]}}",
+                    string(&variant.name),
+                    variant.discriminant,
+                    fields.join(",")
+                )
+            })
+            .collect();
+        return format!(
+            "{{\"name\":{},\"size\":{},\"align\":{},\"tag\":{},\"variants\":[{}]}}",
+            string(ty.name()),
+            size,
+            ty.align(),
+            type_json(ty.tag()),
+            variants.join(",")
+        );
+    }
+    if ty.kind() != TypeKind::Struct {
+        return format!("{{\"name\":{},\"size\":{}}}", string(ty.name()), size);
+    }
+    let fields: Vec<String> = ty
+        .fields()
+        .iter()
+        .map(|field| format!("{{\"name\":{},\"type\":{},\"offset\":{}}}", string(&field.name), type_json(&field.ty), field.offset))
+        .collect();
+    format!(
+        "{{\"name\":{},\"size\":{},\"align\":{},\"fields\":[{}]}}",
+        string(ty.name()),
+        size,
+        ty.align(),
+        fields.join(",")
+    )
+}
+
+/// A C header declaring every `export`ed function, for `--emit=header`.
+/// `path` is where it will be written, which names the include guard.
+pub fn header(scope: &Scope, path: &str) -> String {
+    let stem = Path::new(path).file_stem().and_then(|stem| stem.to_str()).unwrap_or("sabbah");
+    let guard: String = stem
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    let mut out = format!("/* Generated by sabbahc. */\n#ifndef {guard}_H\n#define {guard}_H\n\n");
+    out.push_str("#include <stdint.h>\n\n#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+    let exported = scope
+        .children
+        .iter()
+        .filter(|statement| {
+            let mut nodes = statement.children.iter().skip_while(|node| node.node_type == AstNodeType::Attribute);
+            nodes.next().is_some_and(|node| node.node_type == AstNodeType::ExportKeyword)
+        });
+    let mut prototypes: Vec<String> = Vec::new();
+    // Structs the prototypes use, each after those it contains
+    let mut structs: Vec<&Type> = Vec::new();
+    for statement in exported {
+        let mut name = "";
+        let mut params: Vec<String> = Vec::new();
+        let mut return_type = String::from("void");
+        for node in &statement.children {
+            match &node.value {
+                Some(AstNodeValue::FunctionIdent(ident)) => name = ident,
+                Some(AstNodeValue::ParamTypeTuple(tuple)) => {
+                    for param in &tuple.params {
+                        declare_structs(param.param_type, &mut structs);
+                        params.push(format!("{} {}", c_type(param.param_type), param.name));
+                    }
+                }
+                Some(AstNodeValue::TypeIdent(ty)) => {
+                    declare_structs(ty, &mut structs);
+                    return_type = c_type(ty);
+                }
+                _ => {}
+            }
+        }
+        if params.is_empty() {
+            params.push(String::from("void"));
+        }
+        prototypes.push(format!("{} {}({});\n", return_type, name, params.join(", ")));
+    }
+    for ty in &structs {
+        out.push_str(&format!("typedef struct {} {{\n", ty));
+        for field in ty.fields() {
+            out.push_str(&format!("    {} {};\n", c_type(&field.ty), field.name));
+        }
+        out.push_str(&format!("}} {};\n\n", ty));
+    }
+    for prototype in prototypes {
+        out.push_str(&prototype);
+    }
+    out.push_str(&format!("\n#ifdef __cplusplus\n}}\n#endif\n\n#endif /* {guard}_H */\n"));
+    out
+}
+
+/// Adds the structs `ty` is made of to `structs`, in an order C can declare them in.
+fn declare_structs<'t>(ty: &'t Type, structs: &mut Vec<&'t Type>) {
+    if ty.kind() != TypeKind::Struct || structs.contains(&ty) {
+        return;
+    }
+    for field in ty.fields() {
+        declare_structs(&field.ty, structs);
+    }
+    structs.push(ty);
+}
+
+/// The `<stdint.h>` type matching an integer type, a struct's typedef, or
+/// a pointer to an integer.
+/// Lowering has already rejected exported functions using anything else.
+fn c_type(ty: &Type) -> String {
+    if ty.kind() == TypeKind::Struct {
+        return ty.name().to_string();
+    }
+    if let Some(pointee) = ty.pointee_name() {
+        let pointee = match pointee.strip_prefix('*') {
+            Some(inner) => c_type(typing::pointer(inner)),
+            None => c_type(&BUILTIN_TYPES[pointee]),
+        };
+        return format!("{} *", pointee);
+    }
+    String::from(match (ty.kind(), ty.size()) {
+        (TypeKind::Unsigned, Some(1)) => "uint8_t",
+        (TypeKind::Unsigned, Some(2)) => "uint16_t",
+        (TypeKind::Unsigned, Some(4)) => "uint32_t",
+        (TypeKind::Unsigned, Some(8)) => "uint64_t",
+        (TypeKind::Signed, Some(1)) => "int8_t",
+        (TypeKind::Signed, Some(2)) => "int16_t",
+        (TypeKind::Signed, Some(4)) => "int32_t",
+        (TypeKind::Signed, Some(8)) => "int64_t",
+        _ => panic!("{} has no C equivalent", ty),
+    })
+}
+
+/// Quotes and escapes `s` as a JSON string.
+fn string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -2,35 +2,49 @@ use std::process::exit;
 
 use terminal_size::{Height, Width, terminal_size};
 
+/// Suggestion attached to internal compiler errors.
+pub const REPORT: &str = "Please report this error to GitHub: https://github.com/AmosBarSinai/sabbahc/issues";
+
 struct Diagnostic {
     pub file: String,
     pub line: usize,
     pub column: usize,
     pub message: String,
     pub suggestion: Option<String>,
-    pub comperr: bool
+    pub comperr: bool,
+    /// Warnings are reported, but compilation goes on
+    pub warning: bool,
+    /// A second location that explains the error, such as an earlier definition
+    pub note: Option<(usize, usize, String)>,
 }
 
 impl Diagnostic {
     pub fn out(self, source_code: &str) {
         // ANSI color codes
         const RED: &str = "\x1b[31m";
+        const YELLOW: &str = "\x1b[33m";
         const BOLD: &str = "\x1b[1m";
         const RESET: &str = "\x1b[0m";
 
         if self.comperr {
-            print!("[internal compiler error]");
+            eprint!("[internal compiler error] ");
         }
 
+        let (color, label) = if self.warning { (YELLOW, "warning") } else { (RED, "error") };
         eprintln!(
-            "{}{}error:{} {} at {}:{}:{}",
-            BOLD, RED, RESET, self.message, self.file, self.line, self.column
+            "{}{}{}:{} {} at {}:{}:{}",
+            BOLD, color, label, RESET, self.message, self.file, self.line, self.column
         );
 
         render_snippet(source_code, (self.line, self.column));
 
+        if let Some((line, column, note)) = self.note {
+            eprintln!("{}note:{} {} at {}:{}:{}", BOLD, RESET, note, self.file, line, column);
+            render_snippet(source_code, (line, column));
+        }
+
         if let Some(suggestion) = self.suggestion {
-            println!("{}fix:{} {}", BOLD, RESET, suggestion);
+            eprintln!("{}fix:{} {}", BOLD, RESET, suggestion);
         }
     }
 }
@@ -67,14 +81,14 @@ fn render_snippet(source_code: &str, problem: (usize, usize)) {
     let start = problem.0.saturating_sub(3);
     let end = (problem.0 + 2).min(num_lines);
 
-    println!(
+    eprintln!(
         "{}┼{}─",
         "─".repeat(line_num_width + 1),
         "─".repeat(width - line_num_width - 4)
     );
 
     if start > 0 {
-        println!("{} │ ...", " ".repeat(line_num_width));
+        eprintln!("{} │ ...", " ".repeat(line_num_width));
     }
 
     for i in start..end {
@@ -83,19 +97,19 @@ fn render_snippet(source_code: &str, problem: (usize, usize)) {
             line.truncate(width - line_num_width - 4);
             line.push_str("...");
         }
-        println!("{:>width$} │ {}", i + 1, line, width = line_num_width);
+        eprintln!("{:>width$} │ {}", i + 1, line, width = line_num_width);
         if i + 1 == problem.0 {
-            println!(
+            eprintln!(
                 "{} │ {}{}",
                 " ".repeat(line_num_width),
-                " ".repeat(problem.1 - 2),
+                " ".repeat(problem.1.saturating_sub(2)),
                 "^"
             );
         }
     }
 
     if end < num_lines {
-        println!("{} │ ...", " ".repeat(line_num_width));
+        eprintln!("{} │ ...", " ".repeat(line_num_width));
     }
 }
 
@@ -112,7 +126,40 @@ impl ErrorHandler {
             filename: filename.to_string(),
         }
     }
-    pub fn err(&self, line: usize, column: usize, message: String, suggestion: Option<String>) {
+    pub fn err(&self, line: usize, column: usize, message: String, suggestion: Option<String>) -> ! {
+        Diagnostic {
+            file: self.filename.clone(),
+            line,
+            column,
+            message,
+            suggestion,
+            comperr: false,
+            warning: false,
+            note: None,
+        }.out(&self.source_code);
+        exit(1);
+    }
+    pub fn warn(&self, line: usize, column: usize, message: String, suggestion: Option<String>) {
+        Diagnostic {
+            file: self.filename.clone(),
+            line,
+            column,
+            message,
+            suggestion,
+            comperr: false,
+            warning: true,
+            note: None,
+        }.out(&self.source_code);
+    }
+    /// Like `err`, but also points at a second location, e.g. "first defined here".
+    pub fn err_with_note(
+        &self,
+        line: usize,
+        column: usize,
+        message: String,
+        note: (usize, usize, String),
+        suggestion: Option<String>,
+    ) -> ! {
         Diagnostic {
             file: self.filename.clone(),
             line,
@@ -120,17 +167,21 @@ impl ErrorHandler {
             message,
             suggestion,
             comperr: false,
+            warning: false,
+            note: Some(note),
         }.out(&self.source_code);
         exit(1);
     }
-    pub fn comperr(&self, line: usize, column: usize, message: String, suggestion: Option<String>) {
+    pub fn comperr(&self, line: usize, column: usize, message: String, suggestion: Option<String>) -> ! {
         Diagnostic {
             file: self.filename.clone(),
             line,
             column,
             message,
             suggestion,
-            comperr: true
+            comperr: true,
+            warning: false,
+            note: None,
         }.out(&self.source_code);
         exit(1);
     }
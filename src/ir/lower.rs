@@ -0,0 +1,2781 @@
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    consteval::{ConstEvaluator, Constant},
+    stdlib::{is_reserved, ARITHMETIC, BUILTIN_FUNCTIONS, LEN, PANIC, SYSCALL},
+    err::{ErrorHandler, REPORT},
+    ir::*,
+    mangle::{mangle, mangle_generic, NO_MANGLE},
+    structure::{
+        AsmBlock, AstNode, AstNodeType, AstNodeValue, BinaryOperator, ConstDeclaration, Expression, ExpressionContent,
+        FieldInit, MatchArm, Param, Pattern, Scope, Statement, StaticDeclaration,
+    },
+    typing::{self, Field, Type, TypeKind, TypeTable, Variant, BOOL, BUILTIN_TYPES, INT64, STR, UINT64, UINT8},
+};
+
+/// Lowers the AST produced by the parser into IR.
+///
+/// Every Sabbah value is flattened into one IR value per scalar in its
+/// type's layout: integers are a single value, a `str` is a pointer and a
+/// length, and a struct is the scalars of its fields, one after another.
+/// An enum is its tag followed by the scalars of every variant's fields,
+/// which overlap in memory; those of the variants a value is not are zero.
+///
+/// Calls follow the System V ABI, so that C can call Sabbah and the other
+/// way around. Integers and strs are passed one scalar per register. A
+/// struct is packed into one i64 per eightbyte of its memory layout; up to
+/// 16 bytes it goes in registers, bigger ones go on the stack and are
+/// returned through a hidden pointer. An argument whose eightbytes do not
+/// all fit in the registers left goes on the stack as a whole.
+///
+/// A local whose address is taken with `&` lives in a stack slot instead,
+/// and is loaded from it whenever it is read.
+///
+/// A generic function is lowered once for each list of types it is called
+/// with, which are inferred from the types of the arguments. One that is
+/// never called is not lowered at all, so errors in its body are only
+/// reported once something calls it.
+pub struct Lowerer<'a> {
+    input: &'a Scope<'a>,
+    /// To find the types pointers point to
+    types: &'a TypeTable,
+    /// Whether indices and slice bounds are checked at runtime
+    bounds_checks: bool,
+    /// What `+`, `-` and `*` do when the result does not fit its type
+    overflow: Overflow,
+    error_handler: &'a ErrorHandler,
+    /// Whether the program needs a `main`; libraries do not
+    executable: bool,
+    signatures: HashMap<String, Signature<'a>>,
+    /// The definitions of generic functions, by name
+    generics: HashMap<String, &'a Statement<'a>>,
+    /// The copies of generic functions called so far, in the order they
+    /// were first called
+    instances: RefCell<Vec<Instance<'a>>>,
+    /// How many copies of generic functions deep the function being lowered
+    /// is, 0 unless it is one
+    depth: Cell<usize>,
+    constants: RefCell<ConstEvaluator<'a, 'a>>,
+    statics: HashMap<String, Static<'a>>,
+    /// Contents of the string literals seen so far, each stored once
+    strings: RefCell<Vec<String>>,
+}
+
+#[derive(Clone)]
+struct Signature<'a> {
+    /// The assembly label to call
+    symbol: String,
+    params: Vec<&'a Type>,
+    return_type: Option<&'a Type>,
+    /// Empty unless the function is generic
+    type_params: Vec<String>,
+}
+
+/// The copy of a generic function made for one list of types.
+#[derive(Clone)]
+struct Instance<'a> {
+    symbol: String,
+    name: String,
+    /// What each type parameter stands for, in order
+    types: Vec<&'a Type>,
+    /// 1 if it was called for by a function that is not a copy, otherwise
+    /// one more than the copy that called for it
+    depth: usize,
+}
+
+struct Static<'a> {
+    /// The assembly label, mangled like a function's
+    symbol: String,
+    ty: &'a Type,
+    ir_type: IrType,
+    mutable: bool,
+}
+
+/// The parts of a function definition statement.
+struct FunctionHeader<'s, 'a> {
+    name: String,
+    params: Vec<&'s Param<'a>>,
+    return_type: Option<&'a Type>,
+    ir_return_types: Vec<IrType>,
+    /// Missing for extern functions
+    body: Option<&'s Scope<'a>>,
+    /// The first node after the attributes, for error locations
+    start: &'s AstNode<'a>,
+    no_mangle: bool,
+    /// The `<T, ...>` of a generic function
+    type_params: Option<&'s AstNode<'a>>,
+    /// The types a copy of a generic function is made for
+    type_args: Vec<&'a Type>,
+}
+
+impl FunctionHeader<'_, '_> {
+    /// The assembly label of the function. C functions and those called
+    /// from C go by their plain name, and so does the entry point.
+    fn symbol(&self) -> String {
+        if self.no_mangle || self.name == "main" || self.start.node_type != AstNodeType::FunctionKeyword {
+            self.name.clone()
+        } else if !self.type_args.is_empty() {
+            let types: Vec<&str> = self.type_args.iter().map(|ty| ty.name()).collect();
+            mangle_generic(&[&self.name], &types)
+        } else {
+            mangle(&[&self.name])
+        }
+    }
+}
+
+/// Integer arguments go in %rdi, %rsi, %rdx, %rcx, %r8 and %r9.
+const ARGUMENT_REGISTERS: usize = 6;
+
+/// How deep copies of generic functions may call for further copies, so that
+/// one calling itself for ever larger types is an error rather than endless.
+const MAX_INSTANCE_DEPTH: usize = 64;
+
+/// A parameter, `let` or match binding, flattened into one IR value per
+/// scalar of its type, or kept in memory at `address` if its address is taken.
+struct Variable<'a> {
+    ty: &'a Type,
+    values: Vec<Value>,
+    address: Option<Value>,
+}
+
+/// A `let` of literals only, like `let n = 2 * 10;`. Like a literal, it
+/// takes its type from where it is used; the first use fixes the type for
+/// the rest.
+struct Untyped<'a> {
+    value: Expression<'a>,
+    /// The type, and where it was inferred
+    inferred: Option<(&'a Type, usize, usize)>,
+}
+
+/// What integer arithmetic does when its result does not fit its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Panics at runtime, the default
+    Checked,
+    /// Keeps the low bits of the result
+    Wrapping,
+    /// Clamps the result to the type's minimum or maximum
+    Saturating,
+}
+
+/// A lowered expression: its type and the operands holding its scalars.
+struct Lowered<'a> {
+    ty: &'a Type,
+    operands: Vec<Operand>,
+}
+
+/// Keeps track of the block currently being filled while lowering one function.
+struct FunctionBuilder<'a> {
+    /// The Sabbah name of the function, which `function` knows only by its symbol
+    name: String,
+    function: Function,
+    return_type: Option<&'a Type>,
+    current: Option<(BlockId, Vec<Instruction>)>,
+    next_block: u32,
+    variables: HashMap<String, Variable<'a>>,
+    /// Names the function takes the address of with `&`
+    addressed: HashSet<String>,
+    /// The constants declared in the function so far
+    constants: HashMap<String, Constant<'a>>,
+    /// `let` bindings whose type is not known until they are used
+    untyped: HashMap<String, Untyped<'a>>,
+    /// Names bound with `let`, typed or not
+    lets: HashSet<String>,
+    /// What falling off the end of the body returns
+    fallthrough: Vec<Operand>,
+    /// The hidden parameter of a function returning in memory
+    return_address: Option<Value>,
+}
+
+/// A lowered function, and the block that falls off the end of its body
+/// into the implicit epilogue, if any.
+struct Finished {
+    function: Function,
+    fallthrough: Option<BlockId>,
+}
+
+impl<'a> FunctionBuilder<'a> {
+    fn new(name: String, function: Function, return_type: Option<&'a Type>) -> Self {
+        Self {
+            name,
+            function,
+            return_type,
+            current: Some((BlockId(0), Vec::new())),
+            next_block: 1,
+            variables: HashMap::new(),
+            addressed: HashSet::new(),
+            constants: HashMap::new(),
+            untyped: HashMap::new(),
+            lets: HashSet::new(),
+            fallthrough: Vec::new(),
+            return_address: None,
+        }
+    }
+    fn push(&mut self, instruction: Instruction) {
+        if self.current.is_none() {
+            // Code after a terminator is unreachable, but still gets a block of its own
+            let id = self.new_block();
+            self.current = Some((id, Vec::new()));
+        }
+        self.current.as_mut().unwrap().1.push(instruction);
+    }
+    fn new_block(&mut self) -> BlockId {
+        self.next_block += 1;
+        BlockId(self.next_block - 1)
+    }
+    /// Ends the current block, returning its id.
+    fn terminate(&mut self, terminator: Terminator) -> BlockId {
+        let (id, instructions) = match self.current.take() {
+            Some(current) => current,
+            None => (self.new_block(), Vec::new()),
+        };
+        self.function.blocks.push(BasicBlock { id, instructions, terminator });
+        id
+    }
+    fn finish(mut self) -> Finished {
+        let mut fallthrough = None;
+        if let Some((id, _)) = self.current {
+            // Falling off the end of the body
+            fallthrough = Some(id);
+            let operands = std::mem::take(&mut self.fallthrough);
+            self.terminate(Terminator::Return(operands));
+        }
+        Finished { function: self.function, fallthrough }
+    }
+}
+
+impl<'a> Lowerer<'a> {
+    pub fn new(
+        input: &'a Scope<'a>,
+        types: &'a TypeTable,
+        error_handler: &'a ErrorHandler,
+        executable: bool,
+        bounds_checks: bool,
+        overflow: Overflow,
+    ) -> Self {
+        Self {
+            input,
+            types,
+            bounds_checks,
+            overflow,
+            error_handler,
+            executable,
+            signatures: HashMap::new(),
+            generics: HashMap::new(),
+            instances: RefCell::new(Vec::new()),
+            depth: Cell::new(0),
+            constants: RefCell::new(ConstEvaluator::new(error_handler)),
+            statics: HashMap::new(),
+            strings: RefCell::new(Vec::new()),
+        }
+    }
+    pub fn lower(&mut self) -> Program {
+        let input: &'a Scope<'a> = self.input;
+        let mut functions: Vec<&'a Statement<'a>> = Vec::new();
+        let mut externs: Vec<&'a Statement<'a>> = Vec::new();
+        // Both of the above, in source order
+        let mut declarations: Vec<&'a Statement<'a>> = Vec::new();
+        let mut constants: Vec<&'a ConstDeclaration<'a>> = Vec::new();
+        let mut statics: Vec<&'a StaticDeclaration<'a>> = Vec::new();
+        for statement in &input.children {
+            match statement.children.iter().find(|node| node.node_type != AstNodeType::Attribute) {
+                Some(node) if matches!(node.node_type, AstNodeType::FunctionKeyword | AstNodeType::ExportKeyword) => {
+                    functions.push(statement);
+                    declarations.push(statement);
+                }
+                Some(node) if node.node_type == AstNodeType::ExternKeyword => {
+                    externs.push(statement);
+                    declarations.push(statement);
+                }
+                Some(AstNode { value: Some(AstNodeValue::Const(declaration)), .. }) => {
+                    constants.push(declaration);
+                }
+                Some(AstNode { value: Some(AstNodeValue::Static(declaration)), .. }) => {
+                    statics.push(declaration);
+                }
+                // Laid out by the parser already
+                Some(node) if matches!(node.node_type, AstNodeType::Struct | AstNodeType::Enum) => {}
+                Some(node) => {
+                    self.error_handler.err(
+                        node.line,
+                        node.column,
+                        format!("Unexpected {:?} at the top level", node.node_type),
+                        Some(String::from(
+                            "Only function definitions and declarations, structs, enums, constants and statics may appear at the top level",
+                        )),
+                    );
+                }
+                None => {}
+            }
+        }
+        // Evaluate every constant up front, even unused ones, so that their errors are reported
+        for declaration in &constants {
+            self.constants.get_mut().declare(declaration);
+        }
+        for declaration in &constants {
+            self.constants.get_mut().value(&declaration.name);
+        }
+        for builtin in BUILTIN_FUNCTIONS.iter() {
+            let signature = Signature {
+                symbol: builtin.symbol.to_string(),
+                params: builtin.params.clone(),
+                return_type: builtin.return_type,
+                type_params: Vec::new(),
+            };
+            self.signatures.insert(builtin.name.to_string(), signature);
+        }
+        // Collect every signature first so functions can call ones defined later
+        let mut program = Program { externs: Vec::new(), globals: Vec::new(), functions: Vec::new() };
+        // Where each function was first defined, by name, and each function
+        // or static, by symbol
+        let mut defined: HashMap<String, &'a AstNode<'a>> = HashMap::new();
+        let mut symbols: HashMap<String, (String, usize, usize)> = HashMap::new();
+        for statement in &declarations {
+            let header = self.header(statement);
+            let (line, column) = (header.start.line, header.start.column);
+            if header.name == SYSCALL
+                || header.name == LEN
+                || ARITHMETIC.contains(&header.name.as_str())
+                || BUILTIN_FUNCTIONS.iter().any(|builtin| builtin.name == header.name) {
+                self.error_handler.err(
+                    line,
+                    column,
+                    format!("Function '{}' is already defined by the standard library", header.name),
+                    None,
+                );
+            }
+            if let Some(first) = defined.insert(header.name.clone(), header.start) {
+                self.error_handler.err_with_note(
+                    line,
+                    column,
+                    format!("Function '{}' is already defined", header.name),
+                    (first.line, first.column, format!("'{}' is first defined here", header.name)),
+                    Some(String::from("Rename one of them")),
+                );
+            }
+            let symbol = header.symbol();
+            if is_reserved(&symbol) {
+                self.error_handler.err(
+                    line,
+                    column,
+                    format!("Symbol '{}' is reserved by the runtime", symbol),
+                    Some(String::from("Rename the function, or leave out #[no_mangle] and export so its symbol is mangled")),
+                );
+            }
+            if let Some((other, first_line, first_column)) = symbols.insert(symbol.clone(), (header.name.clone(), line, column)) {
+                self.error_handler.err_with_note(
+                    line,
+                    column,
+                    format!("Function '{}' has the same symbol as '{}' ({})", header.name, other, symbol),
+                    (first_line, first_column, format!("'{}' is defined here", other)),
+                    Some(String::from("Rename one of them")),
+                );
+            }
+            let type_params = self.check_generic(&header);
+            if !type_params.is_empty() {
+                self.generics.insert(header.name.clone(), statement);
+            }
+            match header.start.node_type {
+                AstNodeType::ExternKeyword => program.externs.push(self.extern_function(&header)),
+                AstNodeType::ExportKeyword => self.check_c_signature(&header, "Exported"),
+                _ => {}
+            }
+            let params = header.params.iter().map(|param| param.param_type).collect();
+            let signature = Signature { symbol, params, return_type: header.return_type, type_params };
+            self.signatures.insert(header.name, signature);
+        }
+        for declaration in &statics {
+            // A function of the same name is reported when the static is lowered
+            if self.signatures.contains_key(&declaration.name) {
+                continue;
+            }
+            let (line, column) = (declaration.line, declaration.column);
+            let symbol = static_symbol(&declaration.name);
+            if is_reserved(&symbol) {
+                self.error_handler.err(
+                    line,
+                    column,
+                    format!("Symbol '{}' is reserved by the runtime", symbol),
+                    Some(String::from("Rename the static")),
+                );
+            }
+            if let Some((other, first_line, first_column)) = symbols.insert(symbol.clone(), (declaration.name.clone(), line, column)) {
+                self.error_handler.err_with_note(
+                    line,
+                    column,
+                    format!("Static '{}' has the same symbol as '{}' ({})", declaration.name, other, symbol),
+                    (first_line, first_column, format!("'{}' is defined here", other)),
+                    Some(String::from("Rename one of them")),
+                );
+            }
+        }
+        if self.executable {
+            self.entry_point(&functions);
+        }
+        for declaration in &statics {
+            program.globals.push(self.global(declaration));
+        }
+        for statement in functions.iter().filter(|statement| self.header(statement).type_params.is_none()) {
+            program.functions.push(self.function(statement, &[]));
+        }
+        // Lowering a copy of a generic function can call for more copies
+        let mut lowered = 0;
+        loop {
+            let next = self.instances.borrow().get(lowered).cloned();
+            let Some(instance) = next else {
+                break;
+            };
+            self.depth.set(instance.depth);
+            program.functions.push(self.instance(&instance));
+            lowered += 1;
+        }
+        for (i, string) in self.strings.take().into_iter().enumerate() {
+            program.globals.push(Global {
+                name: string_name(i),
+                mutable: false,
+                data: GlobalData::Bytes(string.into_bytes()),
+            });
+        }
+        program
+    }
+    fn global(&mut self, declaration: &'a StaticDeclaration<'a>) -> Global {
+        let name = &declaration.name;
+        if self.statics.contains_key(name)
+            || self.signatures.contains_key(name)
+            || self.constants.get_mut().value(name).is_some()
+        {
+            self.error_handler.err(
+                declaration.line,
+                declaration.column,
+                format!("'{}' is already defined", name),
+                None,
+            );
+        }
+        let ty = declaration.static_type;
+        // Arrays of integers and pointers are laid out element by element
+        let mut scalar = ty;
+        while let Some(element) = scalar.element() {
+            scalar = element;
+        }
+        let ir_type = match scalar.kind() {
+            TypeKind::Unsigned | TypeKind::Signed | TypeKind::Bool => scalar.size().and_then(IrType::from_size),
+            TypeKind::Pointer => Some(IrType::I64),
+            TypeKind::Str | TypeKind::Struct | TypeKind::Enum | TypeKind::Array | TypeKind::Slice | TypeKind::Parameter => None,
+        };
+        let Some(ir_type) = ir_type else {
+            self.error_handler.err(
+                declaration.line,
+                declaration.column,
+                format!("Statics cannot be of type {}", ty),
+                None,
+            );
+        };
+        let init = match &declaration.value {
+            Some(value) => self.constants.get_mut().array(value, ty, &HashMap::new()),
+            None if declaration.mutable => vec![0; (ty.size().unwrap_or(0) / scalar.size().unwrap_or(1)) as usize],
+            None => self.error_handler.err(
+                declaration.line,
+                declaration.column,
+                format!("Immutable static '{}' needs a value", name),
+                Some(String::from("Give it a value, or declare it with `static mut`")),
+            ),
+        };
+        let symbol = static_symbol(name);
+        self.statics.insert(name.clone(), Static { symbol: symbol.clone(), ty, ir_type, mutable: declaration.mutable });
+        let data = match ty.kind() {
+            TypeKind::Array => GlobalData::Array(ir_type, init),
+            _ => GlobalData::Scalar(ir_type, init[0]),
+        };
+        Global { name: symbol, mutable: declaration.mutable, data }
+    }
+    fn header<'s>(&self, statement: &'s Statement<'a>) -> FunctionHeader<'s, 'a> {
+        // A function statement looks like: [attributes] [export] fk fi tup [ti] scope,
+        // or [attributes] extern fk fi tup [ti] for a declaration
+        let attributes = statement.children.iter().take_while(|node| node.node_type == AstNodeType::Attribute).count();
+        let start = &statement.children[attributes];
+        let mut no_mangle = false;
+        for node in &statement.children[..attributes] {
+            match &node.value {
+                Some(AstNodeValue::Attribute(name)) if name == NO_MANGLE => no_mangle = true,
+                Some(AstNodeValue::Attribute(name)) => self.error_handler.err(
+                    node.line,
+                    node.column,
+                    format!("Unknown attribute '{}'", name),
+                    Some(format!("The only attribute is #[{}]", NO_MANGLE)),
+                ),
+                _ => self.error_handler.comperr(
+                    node.line,
+                    node.column,
+                    String::from("expected attribute node to have a name"),
+                    Some(String::from(REPORT)),
+                ),
+            }
+        }
+        let mut name: Option<String> = None;
+        let mut params: Vec<&'s Param<'a>> = Vec::new();
+        let mut return_type: Option<&'a Type> = None;
+        let mut ir_return_types: Vec<IrType> = Vec::new();
+        let mut body: Option<&'s Scope<'a>> = None;
+        let type_params = statement.children.iter().find(|node| node.node_type == AstNodeType::TypeParams);
+        for node in &statement.children[attributes + 1..] {
+            match &node.value {
+                Some(AstNodeValue::FunctionIdent(ident)) => name = Some(ident.clone()),
+                Some(AstNodeValue::ParamTypeTuple(tuple)) => params = tuple.params.iter().collect(),
+                Some(AstNodeValue::TypeIdent(ty)) => {
+                    return_type = Some(ty);
+                    // Only the copies of a generic function are laid out
+                    if type_params.is_none() {
+                        ir_return_types = self.abi_layout(ty);
+                    }
+                }
+                Some(AstNodeValue::Scope(scope)) => body = Some(scope),
+                _ => {}
+            }
+        }
+        let Some(name) = name else {
+            self.error_handler.comperr(
+                start.line,
+                start.column,
+                String::from("expected function to have an identifier node"),
+                Some(String::from(REPORT)),
+            );
+        };
+        FunctionHeader {
+            name,
+            params,
+            return_type,
+            ir_return_types,
+            body,
+            start,
+            no_mangle,
+            type_params,
+            type_args: Vec::new(),
+        }
+    }
+    /// Checks the type parameters of a generic function, returning them,
+    /// or none if the function is not generic.
+    fn check_generic(&self, header: &FunctionHeader<'_, 'a>) -> Vec<String> {
+        let Some(node) = header.type_params else {
+            return Vec::new();
+        };
+        let Some(AstNodeValue::TypeParams(names)) = &node.value else {
+            self.error_handler.comperr(
+                node.line,
+                node.column,
+                String::from("expected type parameters node to have names"),
+                Some(String::from(REPORT)),
+            );
+        };
+        let plain = match header.start.node_type {
+            AstNodeType::ExternKeyword => Some("Extern"),
+            AstNodeType::ExportKeyword => Some("Exported"),
+            _ if header.no_mangle => Some("#[no_mangle]"),
+            _ => None,
+        };
+        if let Some(what) = plain {
+            self.error_handler.err(
+                node.line,
+                node.column,
+                format!("{} functions cannot be generic", what),
+                Some(String::from("Each copy of a generic function has a mangled symbol of its own")),
+            );
+        }
+        // Type arguments are only ever inferred from the arguments
+        for name in names {
+            let params = std::slice::from_ref(name);
+            if header.params.iter().all(|param| substituted(param.param_type.name(), params, &HashMap::new()).is_some()) {
+                self.error_handler.err(
+                    node.line,
+                    node.column,
+                    format!("Type parameter '{}' is not used by any parameter of '{}'", name, header.name),
+                    Some(String::from("Every type parameter must appear in a parameter's type, so that calls can infer it")),
+                );
+            }
+        }
+        names.clone()
+    }
+    /// Checks that there is a `main`, which takes nothing and returns an
+    /// exit code, or nothing to exit with code 0. By now it is the only one.
+    fn entry_point(&self, functions: &[&'a Statement<'a>]) {
+        let main = functions.iter().map(|statement| self.header(statement)).find(|header| header.name == "main");
+        let Some(main) = main else {
+            self.error_handler.err(
+                1,
+                1,
+                String::from("Program has no main function"),
+                Some(String::from(
+                    "Define the entry point with `f main() -> uint8 { ... }`, or build a library with --emit=staticlib or sharedlib",
+                )),
+            );
+        };
+        if let Some(node) = main.type_params {
+            self.error_handler.err(
+                node.line,
+                node.column,
+                String::from("main cannot be generic"),
+                None,
+            );
+        }
+        if let Some(param) = main.params.first() {
+            self.error_handler.err(
+                param.line,
+                param.column,
+                String::from("main cannot take parameters"),
+                Some(String::from("Read input with read_line() instead")),
+            );
+        }
+        if let Some(ty) = main.return_type.filter(|ty| !ty.is_integer()) {
+            self.error_handler.err(
+                main.start.line,
+                main.start.column,
+                format!("main cannot return a {}", ty),
+                Some(String::from("Return an exit code with `-> uint8`, or leave out the return type to exit with 0")),
+            );
+        }
+    }
+    /// Checks an `extern "C"` declaration.
+    fn extern_function(&self, header: &FunctionHeader<'_, 'a>) -> ExternFunction {
+        if header.body.is_some() {
+            self.error_handler.err(
+                header.start.line,
+                header.start.column,
+                format!("Extern function '{}' cannot have a body", header.name),
+                Some(String::from("Its definition comes from a library, such as libc with --libc")),
+            );
+        }
+        self.check_c_signature(header, "C");
+        let types: Vec<&Type> = header.params.iter().map(|param| param.param_type).collect();
+        let mut params: Vec<IrType> = Vec::new();
+        let mut stack_params: Vec<IrType> = Vec::new();
+        for (ty, on_stack) in types.iter().zip(self.on_stack(&types, header.return_type)) {
+            if on_stack {
+                stack_params.extend(self.abi_layout(ty));
+            } else {
+                params.extend(self.abi_layout(ty));
+            }
+        }
+        let stack_count = stack_params.len();
+        params.extend(stack_params);
+        ExternFunction {
+            name: header.name.clone(),
+            params,
+            stack_params: stack_count,
+            return_types: header.ir_return_types.clone(),
+        }
+    }
+    /// Functions called from or exported to C may only use types C has.
+    fn check_c_signature(&self, header: &FunctionHeader<'_, 'a>, what: &str) {
+        for param in &header.params {
+            if !has_c_equivalent(param.param_type) {
+                self.error_handler.err(
+                    param.line,
+                    param.column,
+                    format!("{} functions cannot take a {}", what, param.param_type),
+                    Some(String::from("Only integers, pointers to them, and structs made of those have a C equivalent")),
+                );
+            }
+        }
+        if let Some(ty) = header.return_type.filter(|ty| !has_c_equivalent(ty)) {
+            self.error_handler.err(
+                header.start.line,
+                header.start.column,
+                format!("{} functions cannot return a {}", what, ty),
+                Some(String::from("Only integers, pointers to them, and structs made of those have a C equivalent")),
+            );
+        }
+    }
+    /// Lowers the copy of a generic function made for `instance.types`.
+    fn instance(&self, instance: &Instance<'a>) -> Function {
+        let generic = self.generics.get(&instance.name).expect(REPORT);
+        let params = &self.signatures.get(&instance.name).expect(REPORT).type_params;
+        let bindings: HashMap<String, String> =
+            params.iter().cloned().zip(instance.types.iter().map(|ty| ty.name().to_string())).collect();
+        let mut statement: Statement<'a> = (*generic).clone();
+        statement.children.retain(|node| node.node_type != AstNodeType::TypeParams);
+        let types = self.types;
+        statement.map_types(&|ty| {
+            let name = substituted(ty.name(), params, &bindings).expect(REPORT);
+            types.resolve(&name).expect(REPORT)
+        });
+        self.function(&statement, &instance.types)
+    }
+    fn function(&self, statement: &Statement<'a>, type_args: &[&'a Type]) -> Function {
+        let mut header = self.header(statement);
+        header.type_args = type_args.to_vec();
+        let Some(body) = header.body else {
+            self.error_handler.err(
+                header.start.line,
+                header.start.column,
+                format!("Function '{}' has no body", header.name),
+                Some(String::from("Declare functions defined elsewhere with `extern \"C\"`")),
+            );
+        };
+        // Without a return type, main exits with code 0
+        let implicit_exit = header.name == "main" && header.return_type.is_none();
+        let return_types = if implicit_exit { vec![IrType::I8] } else { header.ir_return_types.clone() };
+        let mut function = Function::new(header.symbol(), return_types);
+        function.exported = header.start.node_type == AstNodeType::ExportKeyword;
+        let mut builder = FunctionBuilder::new(header.name.clone(), function, header.return_type);
+        if implicit_exit {
+            builder.fallthrough = vec![Operand::Const(0)];
+        }
+        for inner in &body.children {
+            for node in &inner.children {
+                addressed_in_node(node, &mut builder.addressed);
+            }
+        }
+        if header.return_type.is_some_and(|ty| self.in_memory(ty)) {
+            let address = builder.function.new_value(IrType::I64);
+            builder.function.params.push(address);
+            builder.return_address = Some(address);
+        }
+        // Every parameter arrives as it is passed, and structs are unpacked once all have
+        let types: Vec<&Type> = header.params.iter().map(|param| param.param_type).collect();
+        let mut passed: Vec<Vec<Value>> = Vec::new();
+        let mut stack_params: Vec<Value> = Vec::new();
+        for (ty, on_stack) in types.iter().zip(self.on_stack(&types, header.return_type)) {
+            let values: Vec<Value> =
+                self.abi_layout(ty).into_iter().map(|ty| builder.function.new_value(ty)).collect();
+            if on_stack {
+                stack_params.extend(&values);
+            } else {
+                builder.function.params.extend(&values);
+            }
+            passed.push(values);
+        }
+        builder.function.stack_params = stack_params.len();
+        builder.function.params.extend(stack_params);
+        for (param, passed) in header.params.iter().zip(passed) {
+            let operands: Vec<Operand> =
+                self.unpack(param.param_type, &passed, &mut builder).into_iter().map(Operand::Value).collect();
+            let variable = self.local(&param.name, param.param_type, &operands, &mut builder);
+            if builder.variables.insert(param.name.clone(), variable).is_some() {
+                self.error_handler.err(
+                    param.line,
+                    param.column,
+                    format!("Duplicate parameter '{}'", param.name),
+                    None,
+                );
+            }
+        }
+        // Warn once about each run of statements that follows a return
+        let mut warned = false;
+        for inner in &body.children {
+            let Some(first) = inner.children.first() else {
+                continue;
+            };
+            if builder.current.is_none() && !warned {
+                self.error_handler.warn(
+                    first.line,
+                    first.column,
+                    String::from("Unreachable statement"),
+                    Some(String::from("It comes after a return, so it never runs")),
+                );
+                warned = true;
+            }
+            for node in &inner.children {
+                self.node(node, &mut builder);
+            }
+            if builder.current.is_some() {
+                warned = false;
+            }
+        }
+        let Finished { function, fallthrough } = builder.finish();
+        // Unit functions fall off the end into an implicit `ret`, but a value
+        // has to come from an explicit return on every path
+        if let (Some(ty), Some(block)) = (header.return_type, fallthrough)
+            && function.reachable().contains(&block)
+        {
+            self.error_handler.err(
+                header.start.line,
+                header.start.column,
+                format!("Not all paths in '{}' return a value", header.name),
+                Some(format!("End the function with `return` and a {}", ty)),
+            );
+        }
+        function
+    }
+    fn node(&self, node: &AstNode<'a>, builder: &mut FunctionBuilder<'a>) {
+        match (&node.node_type, &node.value) {
+            (AstNodeType::ReturnKeyword, Some(AstNodeValue::Expression(expr))) => {
+                let Some(return_type) = builder.return_type else {
+                    self.error_handler.err(
+                        node.line,
+                        node.column,
+                        format!("Function '{}' does not return a value", builder.name),
+                        Some(String::from("Declare a return type with `-> type`")),
+                    );
+                };
+                let values = self.typed(expr, return_type, builder);
+                let mut values = self.pack(return_type, values, builder);
+                if let Some(address) = builder.return_address {
+                    values.insert(0, Operand::Value(address));
+                }
+                builder.terminate(Terminator::Return(values));
+            }
+            (AstNodeType::Const, Some(AstNodeValue::Const(declaration))) => {
+                if builder.variables.contains_key(&declaration.name)
+                    || builder.constants.contains_key(&declaration.name)
+                {
+                    self.error_handler.err(
+                        declaration.line,
+                        declaration.column,
+                        format!("'{}' is already defined", declaration.name),
+                        None,
+                    );
+                }
+                let value =
+                    self.constants.borrow_mut().evaluate(&declaration.value, declaration.const_type, &builder.constants);
+                builder.constants.insert(declaration.name.clone(), Constant { value, ty: declaration.const_type });
+            }
+            (AstNodeType::Let, Some(AstNodeValue::Let(binding))) => {
+                if builder.variables.contains_key(&binding.name)
+                    || builder.constants.contains_key(&binding.name)
+                    || builder.untyped.contains_key(&binding.name)
+                {
+                    self.error_handler.err(
+                        binding.line,
+                        binding.column,
+                        format!("'{}' is already defined", binding.name),
+                        Some(String::from("Bindings cannot be shadowed; pick another name")),
+                    );
+                }
+                builder.lets.insert(binding.name.clone());
+                // Kept as written until a use gives it a type, unless it has to be in memory
+                if binding.binding_type.is_none() && is_literal(&binding.value) && !builder.addressed.contains(&binding.name) {
+                    builder.untyped.insert(binding.name.clone(), Untyped { value: binding.value.clone(), inferred: None });
+                    return;
+                }
+                let lowered = match binding.binding_type {
+                    Some(ty) => Lowered { ty, operands: self.typed(&binding.value, ty, builder) },
+                    None => self.expression(&binding.value, builder),
+                };
+                let variable = self.local(&binding.name, lowered.ty, &lowered.operands, builder);
+                builder.variables.insert(binding.name.clone(), variable);
+            }
+            (AstNodeType::Assignment, Some(AstNodeValue::Assignment(assignment))) => match &assignment.target.content {
+                ExpressionContent::Variable(target) => {
+                    let global = self.assignable(target, node, builder);
+                    let operands = self.typed(&assignment.value, global.ty, builder);
+                    if global.ty.kind() == TypeKind::Array {
+                        let address = builder.function.new_value(IrType::I64);
+                        builder.push(Instruction::Address { dest: address, global: global.symbol.clone() });
+                        self.store(global.ty, Operand::Value(address), operands, builder);
+                    } else {
+                        builder.push(Instruction::Store { ty: global.ir_type, global: global.symbol.clone(), src: operands[0] });
+                    }
+                }
+                ExpressionContent::Index(value, index) => {
+                    if let Some(root) = root(value).filter(|root| {
+                        !builder.variables.contains_key(*root) && !builder.constants.contains_key(*root)
+                    }) {
+                        self.assignable(root, node, builder);
+                    }
+                    let (address, ty) = self.element(&assignment.target, value, index, builder);
+                    let operands = self.typed(&assignment.value, ty, builder);
+                    self.store(ty, address, operands, builder);
+                }
+                ExpressionContent::Deref(pointer) => {
+                    let lowered = self.expression(pointer, builder);
+                    let ty = self.pointee(pointer, lowered.ty);
+                    let operands = self.typed(&assignment.value, ty, builder);
+                    self.store(ty, lowered.operands[0], operands, builder);
+                }
+                _ => self.error_handler.comperr(
+                    node.line,
+                    node.column,
+                    String::from("expected assignment target to be a variable or dereference"),
+                    Some(String::from(REPORT)),
+                ),
+            },
+            (AstNodeType::Asm, Some(AstNodeValue::Asm(block))) => self.asm(node, block, builder),
+            (AstNodeType::Expression, Some(AstNodeValue::Expression(expr))) => self.statement_expression(expr, builder),
+            _ => {
+                self.error_handler.err(
+                    node.line,
+                    node.column,
+                    format!("Unexpected {:?} inside a function body", node.node_type),
+                    None,
+                );
+            }
+        }
+    }
+    /// The static `target` refers to, if it may be assigned to.
+    fn assignable(&self, target: &str, node: &AstNode<'a>, builder: &FunctionBuilder<'a>) -> &Static<'a> {
+        // Parameters, bindings and local constants shadow globals
+        let shadowed = if builder.lets.contains(target) {
+            Some("let binding")
+        } else if builder.variables.contains_key(target) {
+            Some("parameter")
+        } else if builder.constants.contains_key(target) {
+            Some("constant")
+        } else {
+            None
+        };
+        match (shadowed, self.statics.get(target)) {
+            (None, Some(global)) if global.mutable => global,
+            (None, Some(_)) => self.error_handler.err(
+                node.line,
+                node.column,
+                format!("Cannot assign to immutable static '{}'", target),
+                Some(String::from("Declare it with `static mut`")),
+            ),
+            (None, None) if self.constants.borrow_mut().value(target).is_some() => self.error_handler.err(
+                node.line,
+                node.column,
+                format!("Cannot assign to constant '{}'", target),
+                None,
+            ),
+            (None, None) => self.error_handler.err(
+                node.line,
+                node.column,
+                format!("Unknown variable '{}'", target),
+                None,
+            ),
+            (Some(what), _) => self.error_handler.err(
+                node.line,
+                node.column,
+                format!("Cannot assign to {} '{}'", what, target),
+                None,
+            ),
+        }
+    }
+    /// Lowers an asm block. Outputs are stored to their statics once the block has run.
+    fn asm<'b>(&self, node: &AstNode<'a>, block: &'b AsmBlock<'a>, builder: &mut FunctionBuilder<'a>) {
+        let mut names: Vec<&'b str> = Vec::new();
+        let mut declare = |name: &'b str, line: usize, column: usize| {
+            if names.contains(&name) {
+                self.error_handler.err(line, column, format!("Duplicate asm operand '{}'", name), None);
+            }
+            names.push(name);
+        };
+        let mut inputs: Vec<(String, Operand)> = Vec::new();
+        for input in &block.inputs {
+            declare(&input.name, input.line, input.column);
+            let (operand, _) = self.integer(&input.value, builder);
+            inputs.push((input.name.clone(), operand));
+        }
+        let mut outputs: Vec<(String, Value)> = Vec::new();
+        let mut stores: Vec<Instruction> = Vec::new();
+        for output in &block.outputs {
+            declare(&output.target, output.line, output.column);
+            let global = self.assignable(&output.target, node, builder);
+            let dest = builder.function.new_value(global.ir_type);
+            outputs.push((output.target.clone(), dest));
+            stores.push(Instruction::Store { ty: global.ir_type, global: global.symbol.clone(), src: Operand::Value(dest) });
+        }
+        for line in &block.template {
+            for name in placeholders(line, node, self.error_handler) {
+                if !names.contains(&name) {
+                    self.error_handler.err(
+                        node.line,
+                        node.column,
+                        format!("Unknown asm operand '{{{}}}'", name),
+                        Some(String::from("Bind it with `in name = value;` or `out name;`")),
+                    );
+                }
+            }
+        }
+        builder.push(Instruction::Asm { template: block.template.clone(), inputs, outputs });
+        for store in stores {
+            builder.push(store);
+        }
+    }
+    fn expression(&self, expr: &Expression<'a>, builder: &mut FunctionBuilder<'a>) -> Lowered<'a> {
+        match &expr.content {
+            // Without context, literals are uint8
+            ExpressionContent::IntLiteral(_) => Lowered { ty: &UINT8, operands: self.typed(expr, &UINT8, builder) },
+            ExpressionContent::StrLiteral(value) => {
+                let global = self.intern(value);
+                let dest = builder.function.new_value(IrType::I64);
+                builder.push(Instruction::Address { dest, global });
+                Lowered { ty: &STR, operands: vec![Operand::Value(dest), Operand::Const(value.len() as u64)] }
+            }
+            ExpressionContent::Variable(name) => {
+                if let Some(untyped) = builder.untyped.get(name) {
+                    // Without context, like a literal, it is uint8
+                    let ty: &'a Type = untyped.inferred.map_or(&UINT8, |(ty, ..)| ty);
+                    return Lowered { ty, operands: self.typed(expr, ty, builder) };
+                }
+                if let Some(variable) = builder.variables.get(name) {
+                    let (ty, address) = (variable.ty, variable.address);
+                    let operands = match address {
+                        Some(address) => self.load(ty, Operand::Value(address), builder),
+                        None => variable.values.iter().map(|value| Operand::Value(*value)).collect(),
+                    };
+                    return Lowered { ty, operands };
+                }
+                if let Some(constant) = builder.constants.get(name) {
+                    return Lowered { ty: constant.ty, operands: vec![Operand::Const(constant.value)] };
+                }
+                if let Some(global) = self.statics.get(name).filter(|global| global.ty.kind() == TypeKind::Array) {
+                    let address = builder.function.new_value(IrType::I64);
+                    builder.push(Instruction::Address { dest: address, global: global.symbol.clone() });
+                    return Lowered { ty: global.ty, operands: self.load(global.ty, Operand::Value(address), builder) };
+                }
+                if let Some(global) = self.statics.get(name) {
+                    let dest = builder.function.new_value(global.ir_type);
+                    builder.push(Instruction::Load { dest, ty: global.ir_type, global: global.symbol.clone() });
+                    return Lowered { ty: global.ty, operands: vec![Operand::Value(dest)] };
+                }
+                match self.constants.borrow_mut().value(name) {
+                    Some(constant) => Lowered { ty: constant.ty, operands: vec![Operand::Const(constant.value)] },
+                    None => self.error_handler.err(
+                        expr.line,
+                        expr.column,
+                        format!("Unknown variable '{}'", name),
+                        None,
+                    ),
+                }
+            }
+            ExpressionContent::Call(name, args) => self.call(expr, name, args, true, builder).unwrap(),
+            ExpressionContent::Binary(operator, lhs, rhs) => self.binary(expr, *operator, lhs, rhs, None, builder),
+            ExpressionContent::FieldAccess(value, name) => {
+                let lowered = self.expression(value, builder);
+                let Some(field) = lowered.ty.field(name) else {
+                    self.error_handler.err(
+                        expr.line,
+                        expr.column,
+                        format!("{} has no field '{}'", lowered.ty, name),
+                        None,
+                    );
+                };
+                // The field's scalars follow those of the fields before it
+                let start: usize = lowered
+                    .ty
+                    .fields()
+                    .iter()
+                    .take_while(|other| other.name != *name)
+                    .map(|other| self.layout(&other.ty).len())
+                    .sum();
+                let end = start + self.layout(&field.ty).len();
+                Lowered { ty: &field.ty, operands: lowered.operands[start..end].to_vec() }
+            }
+            ExpressionContent::StructLiteral(_, fields) => self.struct_literal(expr, fields, builder),
+            ExpressionContent::Variant(name, variant, values) => self.variant(expr, name, variant, values, builder),
+            ExpressionContent::Match(value, arms) => self.match_expression(expr, value, arms, true, None, builder).unwrap(),
+            ExpressionContent::AddressOf(name) => self.address_of(expr, name, builder),
+            ExpressionContent::Deref(pointer) => {
+                let lowered = self.expression(pointer, builder);
+                let ty = self.pointee(pointer, lowered.ty);
+                Lowered { ty, operands: self.load(ty, lowered.operands[0], builder) }
+            }
+            ExpressionContent::Null => self.error_handler.err(
+                expr.line,
+                expr.column,
+                String::from("Cannot tell what `null` points to"),
+                Some(String::from("Use it where a pointer is expected, such as an argument")),
+            ),
+            ExpressionContent::BoolLiteral(value) => Lowered { ty: &BOOL, operands: vec![Operand::Const(*value as u64)] },
+            ExpressionContent::Cast(value, ty) => self.cast(value, ty, builder),
+            // Without context, the elements take the type of the first one
+            ExpressionContent::ArrayLiteral(values) => {
+                let first = self.expression(&values[0], builder);
+                let mut operands = first.operands;
+                for value in &values[1..] {
+                    operands.extend(self.typed(value, first.ty, builder));
+                }
+                Lowered { ty: typing::array(first.ty, values.len() as u64), operands }
+            }
+            ExpressionContent::Index(value, index) => {
+                let (address, ty) = self.element(expr, value, index, builder);
+                Lowered { ty, operands: self.load(ty, address, builder) }
+            }
+            ExpressionContent::Slice(value, start, end) => self.slice(expr, value, start.as_deref(), end.as_deref(), builder),
+        }
+    }
+    /// Lowers `value as to`. Integers are zero- or sign-extended, as they
+    /// are unsigned or signed, or truncated to the new width; bools become
+    /// 0 or 1, and pointers are addresses. A literal is of the type it is
+    /// cast to, a uint64 if that is a pointer, or else a uint8.
+    fn cast(&self, value: &Expression<'a>, to: &'a Type, builder: &mut FunctionBuilder<'a>) -> Lowered<'a> {
+        let lowered = if self.untyped(value, builder) {
+            let ty: &'a Type = match to.kind() {
+                TypeKind::Unsigned | TypeKind::Signed => to,
+                TypeKind::Pointer => &UINT64,
+                _ => &UINT8,
+            };
+            Lowered { ty, operands: self.typed(value, ty, builder) }
+        } else {
+            self.expression(value, builder)
+        };
+        let from = lowered.ty;
+        if !from.casts_to(to) {
+            let fix = match (from.is_integer(), to.kind()) {
+                (true, TypeKind::Bool) => String::from("Match on it instead: `match value { 0 => false, _ => true }`"),
+                _ => String::from("Only integers, bool and pointers can be cast"),
+            };
+            self.error_handler.err(value.line, value.column, format!("Cannot cast {} to {}", from, to), Some(fix));
+        }
+        let (from_ir, to_ir) = (self.layout(from)[0], self.layout(to)[0]);
+        let op = match from_ir.bits().cmp(&to_ir.bits()) {
+            Ordering::Less if from.kind() == TypeKind::Signed => CastOp::SExt,
+            Ordering::Less => CastOp::ZExt,
+            Ordering::Greater => CastOp::Trunc,
+            Ordering::Equal => return Lowered { ty: to, operands: lowered.operands },
+        };
+        let dest = builder.function.new_value(to_ir);
+        builder.push(Instruction::Cast { dest, op, from: from_ir, to: to_ir, src: lowered.operands[0] });
+        Lowered { ty: to, operands: vec![Operand::Value(dest)] }
+    }
+    /// Where `expr` lives in memory, if it does: a local kept in a stack
+    /// slot, a static, or what a pointer, element or field refers to.
+    fn place(&self, expr: &Expression<'a>, builder: &mut FunctionBuilder<'a>) -> Option<(Operand, &'a Type)> {
+        match &expr.content {
+            ExpressionContent::Variable(name) => {
+                if let Some(variable) = builder.variables.get(name) {
+                    return variable.address.map(|address| (Operand::Value(address), variable.ty));
+                }
+                if builder.constants.contains_key(name) || builder.untyped.contains_key(name) {
+                    return None;
+                }
+                let global = self.statics.get(name)?;
+                let dest = builder.function.new_value(IrType::I64);
+                builder.push(Instruction::Address { dest, global: global.symbol.clone() });
+                Some((Operand::Value(dest), global.ty))
+            }
+            ExpressionContent::Deref(pointer) => {
+                let lowered = self.expression(pointer, builder);
+                Some((lowered.operands[0], self.pointee(pointer, lowered.ty)))
+            }
+            ExpressionContent::Index(value, index) => Some(self.element(expr, value, index, builder)),
+            ExpressionContent::FieldAccess(value, name) => {
+                let (address, ty) = self.place(value, builder)?;
+                let field = ty.field(name)?;
+                Some((self.offset(address, field.offset, builder), &field.ty))
+            }
+            _ => None,
+        }
+    }
+    /// The address of the first element of an array or slice, how many
+    /// elements there are and their type. An array that is not in memory,
+    /// such as one a call returned, is stored to a stack slot first.
+    fn indexable(&self, value: &Expression<'a>, builder: &mut FunctionBuilder<'a>) -> (Operand, Operand, &'a Type) {
+        let lowered = match self.place(value, builder) {
+            Some((address, ty)) if ty.kind() == TypeKind::Array => {
+                Lowered { ty, operands: vec![address] }
+            }
+            Some((address, ty)) => Lowered { ty, operands: self.load(ty, address, builder) },
+            None => {
+                let lowered = self.expression(value, builder);
+                if lowered.ty.kind() != TypeKind::Array {
+                    lowered
+                } else {
+                    let address = builder.function.new_value(IrType::I64);
+                    let (size, align) = (lowered.ty.size().unwrap_or(0), lowered.ty.align());
+                    builder.push(Instruction::Alloca { dest: address, size, align });
+                    self.store(lowered.ty, Operand::Value(address), lowered.operands, builder);
+                    Lowered { ty: lowered.ty, operands: vec![Operand::Value(address)] }
+                }
+            }
+        };
+        let ty = lowered.ty;
+        match (ty.kind(), self.types.element(ty)) {
+            (TypeKind::Array, Some(element)) => (lowered.operands[0], Operand::Const(ty.length().expect(REPORT)), element),
+            (TypeKind::Slice, Some(element)) => (lowered.operands[0], lowered.operands[1], element),
+            _ => self.error_handler.err(
+                value.line,
+                value.column,
+                format!("Cannot index {}", ty),
+                Some(String::from("Only arrays and slices have elements")),
+            ),
+        }
+    }
+    /// The address of `value[index]`, and its type.
+    fn element(
+        &self,
+        expr: &Expression<'a>,
+        value: &Expression<'a>,
+        index: &Expression<'a>,
+        builder: &mut FunctionBuilder<'a>,
+    ) -> (Operand, &'a Type) {
+        let (pointer, length, element) = self.indexable(value, builder);
+        let index = self.index(index, builder);
+        if let (Operand::Const(index), Operand::Const(length)) = (index, length)
+            && index >= length
+        {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Index {} is out of bounds for length {}", index, length),
+                None,
+            );
+        }
+        self.check(expr, BinaryOp::ULt, index, length, "index out of bounds", builder);
+        (self.advance(pointer, index, element, builder), element)
+    }
+    /// Lowers `value[start..end]` to a slice of the elements from `start`
+    /// up to, but not including, `end`.
+    fn slice(
+        &self,
+        expr: &Expression<'a>,
+        value: &Expression<'a>,
+        start: Option<&Expression<'a>>,
+        end: Option<&Expression<'a>>,
+        builder: &mut FunctionBuilder<'a>,
+    ) -> Lowered<'a> {
+        let (pointer, length, element) = self.indexable(value, builder);
+        let start = start.map_or(Operand::Const(0), |start| self.index(start, builder));
+        let end = end.map_or(length, |end| self.index(end, builder));
+        if let (Operand::Const(start), Operand::Const(end)) = (start, end)
+            && start > end
+        {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Slice starts at {} but ends at {}", start, end),
+                None,
+            );
+        }
+        if let (Operand::Const(end), Operand::Const(length)) = (end, length)
+            && end > length
+        {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Slice end {} is out of bounds for length {}", end, length),
+                None,
+            );
+        }
+        self.check(expr, BinaryOp::ULe, start, end, "slice starts after its end", builder);
+        self.check(expr, BinaryOp::ULe, end, length, "slice end out of bounds", builder);
+        let address = self.advance(pointer, start, element, builder);
+        let count = builder.function.new_value(IrType::I64);
+        builder.push(Instruction::Binary { dest: count, ty: IrType::I64, op: BinaryOp::Sub, lhs: end, rhs: start });
+        Lowered { ty: typing::slice(element.name()), operands: vec![address, Operand::Value(count)] }
+    }
+    /// Lowers an index or slice bound, which must be unsigned.
+    fn index(&self, expr: &Expression<'a>, builder: &mut FunctionBuilder<'a>) -> Operand {
+        if self.untyped(expr, builder) {
+            return self.typed(expr, &UINT64, builder)[0];
+        }
+        let lowered = self.expression(expr, builder);
+        if lowered.ty.kind() != TypeKind::Unsigned {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Expected an unsigned integer (found {})", lowered.ty),
+                Some(String::from("Indices count up from 0")),
+            );
+        }
+        lowered.operands[0]
+    }
+    /// Panics at runtime with `what` and the location of `expr` unless
+    /// `lhs op rhs` holds, where `op` compares. Left out if bounds checks
+    /// are turned off, or if both sides are known, as they were already
+    /// checked at compile time.
+    fn check(
+        &self,
+        expr: &Expression<'a>,
+        op: BinaryOp,
+        lhs: Operand,
+        rhs: Operand,
+        what: &str,
+        builder: &mut FunctionBuilder<'a>,
+    ) {
+        if !self.bounds_checks || matches!((lhs, rhs), (Operand::Const(_), Operand::Const(_))) {
+            return;
+        }
+        let condition = builder.function.new_value(IrType::I64);
+        builder.push(Instruction::Binary { dest: condition, ty: IrType::I64, op, lhs, rhs });
+        self.guard(expr, Operand::Value(condition), true, what, builder);
+    }
+    /// Carries on in a new block if `condition` is `passes`, nonzero being
+    /// true, and otherwise panics at runtime with `what` and the location
+    /// of `expr`.
+    fn guard(&self, expr: &Expression<'a>, condition: Operand, passes: bool, what: &str, builder: &mut FunctionBuilder<'a>) {
+        let (passed, failed) = (builder.new_block(), builder.new_block());
+        let (then_block, else_block) = if passes { (passed, failed) } else { (failed, passed) };
+        builder.terminate(Terminator::Branch { condition, then_block, else_block });
+        builder.current = Some((failed, Vec::new()));
+        let message = format!("panic at {}:{}:{}: {}\n", self.error_handler.filename, expr.line, expr.column, what);
+        let address = builder.function.new_value(IrType::I64);
+        builder.push(Instruction::Address { dest: address, global: self.intern(&message) });
+        let args = vec![Operand::Value(address), Operand::Const(message.len() as u64)];
+        builder.push(Instruction::Call { dests: Vec::new(), function: PANIC.to_string(), args });
+        builder.terminate(Terminator::Unreachable);
+        builder.current = Some((passed, Vec::new()));
+    }
+    /// `address` plus `count` values of type `ty`.
+    fn advance(&self, address: Operand, count: Operand, ty: &Type, builder: &mut FunctionBuilder<'a>) -> Operand {
+        let scaled = builder.function.new_value(IrType::I64);
+        let (lhs, rhs) = (count, Operand::Const(ty.size().unwrap_or(0)));
+        builder.push(Instruction::Binary { dest: scaled, ty: IrType::I64, op: BinaryOp::Mul, lhs, rhs });
+        let dest = builder.function.new_value(IrType::I64);
+        let (lhs, rhs) = (address, Operand::Value(scaled));
+        builder.push(Instruction::Binary { dest, ty: IrType::I64, op: BinaryOp::Add, lhs, rhs });
+        Operand::Value(dest)
+    }
+    /// Lowers `len(value)`: the length of an array, slice or str.
+    fn len(&self, expr: &Expression<'a>, args: &[Expression<'a>], builder: &mut FunctionBuilder<'a>) -> Lowered<'a> {
+        let [value] = args else {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("len takes 1 argument (found {})", args.len()),
+                None,
+            );
+        };
+        let lowered = match self.place(value, builder) {
+            // Known without reading the array
+            Some((_, ty)) if ty.kind() == TypeKind::Array => Lowered { ty, operands: Vec::new() },
+            Some((address, ty)) => Lowered { ty, operands: self.load(ty, address, builder) },
+            None => self.expression(value, builder),
+        };
+        let length = match lowered.ty.kind() {
+            TypeKind::Array => Operand::Const(lowered.ty.length().expect(REPORT)),
+            TypeKind::Slice | TypeKind::Str => lowered.operands[1],
+            _ => self.error_handler.err(
+                value.line,
+                value.column,
+                format!("Cannot take the length of {}", lowered.ty),
+                Some(String::from("Only arrays, slices and strs have a length")),
+            ),
+        };
+        Lowered { ty: &UINT64, operands: vec![length] }
+    }
+    /// Lowers `&name`: the address of a local's stack slot, or of a mutable static.
+    fn address_of(&self, expr: &Expression<'a>, name: &str, builder: &mut FunctionBuilder<'a>) -> Lowered<'a> {
+        if let Some(variable) = builder.variables.get(name) {
+            let Some(address) = variable.address else {
+                self.error_handler.comperr(
+                    expr.line,
+                    expr.column,
+                    format!("'{}' has its address taken but is not in memory", name),
+                    Some(String::from(REPORT)),
+                );
+            };
+            return Lowered { ty: typing::pointer(variable.ty.name()), operands: vec![Operand::Value(address)] };
+        }
+        let constant = builder.constants.contains_key(name);
+        match self.statics.get(name) {
+            Some(global) if !constant && global.mutable => {
+                let dest = builder.function.new_value(IrType::I64);
+                builder.push(Instruction::Address { dest, global: global.symbol.clone() });
+                Lowered { ty: typing::pointer(global.ty.name()), operands: vec![Operand::Value(dest)] }
+            }
+            Some(_) if !constant => self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Cannot take the address of immutable static '{}'", name),
+                Some(String::from("Declare it with `static mut`")),
+            ),
+            _ if constant || self.constants.borrow_mut().value(name).is_some() => self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Cannot take the address of constant '{}'", name),
+                Some(String::from("Constants are not stored anywhere; use a `static mut` instead")),
+            ),
+            _ => self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Unknown variable '{}'", name),
+                None,
+            ),
+        }
+    }
+    /// The type a pointer of type `ty` points to.
+    fn pointee(&self, pointer: &Expression<'a>, ty: &Type) -> &'a Type {
+        match self.types.pointee(ty) {
+            Some(pointee) => pointee,
+            None => self.error_handler.err(
+                pointer.line,
+                pointer.column,
+                format!("Expected a pointer (found {})", ty),
+                None,
+            ),
+        }
+    }
+    /// Loads a value of type `ty` from `address`, a scalar at a time.
+    fn load(&self, ty: &Type, address: Operand, builder: &mut FunctionBuilder<'a>) -> Vec<Operand> {
+        self.scalars(ty)
+            .into_iter()
+            .map(|(offset, ir_type)| {
+                let address = self.offset(address, offset, builder);
+                let dest = builder.function.new_value(ir_type);
+                builder.push(Instruction::LoadAt { dest, ty: ir_type, address });
+                Operand::Value(dest)
+            })
+            .collect()
+    }
+    /// Stores `operands`, a value of type `ty`, to `address`. Structs and
+    /// enums are packed first, since the fields of an enum's variants
+    /// overlap, and written an eightbyte at a time, in smaller pieces where
+    /// fewer than eight bytes are left.
+    fn store(&self, ty: &Type, address: Operand, operands: Vec<Operand>, builder: &mut FunctionBuilder<'a>) {
+        if !is_aggregate(ty) {
+            for ((offset, ir_type), src) in self.scalars(ty).into_iter().zip(operands) {
+                let address = self.offset(address, offset, builder);
+                builder.push(Instruction::StoreAt { ty: ir_type, address, src });
+            }
+            return;
+        }
+        let size = ty.size().unwrap_or(0);
+        for (i, eightbyte) in self.pack(ty, operands, builder).into_iter().enumerate() {
+            let mut offset = i as u64 * 8;
+            let mut src = eightbyte;
+            let mut left = (size - offset).min(8);
+            while left > 0 {
+                let piece = [8, 4, 2, 1].into_iter().find(|piece| *piece <= left).expect(REPORT);
+                let ir_type = IrType::from_size(piece).expect(REPORT);
+                let at = self.offset(address, offset, builder);
+                builder.push(Instruction::StoreAt { ty: ir_type, address: at, src });
+                offset += piece;
+                left -= piece;
+                if left == 0 {
+                    break;
+                }
+                let dest = builder.function.new_value(IrType::I64);
+                builder.push(Instruction::Binary {
+                    dest,
+                    ty: IrType::I64,
+                    op: BinaryOp::LShr,
+                    lhs: src,
+                    rhs: Operand::Const(piece * 8),
+                });
+                src = Operand::Value(dest);
+            }
+        }
+    }
+    /// `address` plus `offset` bytes.
+    fn offset(&self, address: Operand, offset: u64, builder: &mut FunctionBuilder<'a>) -> Operand {
+        if offset == 0 {
+            return address;
+        }
+        let dest = builder.function.new_value(IrType::I64);
+        builder.push(Instruction::Binary { dest, ty: IrType::I64, op: BinaryOp::Add, lhs: address, rhs: Operand::Const(offset) });
+        Operand::Value(dest)
+    }
+    /// Lowers an expression used as a statement, whose value (if any) is dropped.
+    fn statement_expression(&self, expr: &Expression<'a>, builder: &mut FunctionBuilder<'a>) {
+        match &expr.content {
+            ExpressionContent::Call(name, args) => {
+                self.call(expr, name, args, false, builder);
+            }
+            ExpressionContent::Match(value, arms) => {
+                self.match_expression(expr, value, arms, false, None, builder);
+            }
+            _ => {
+                self.expression(expr, builder);
+            }
+        }
+    }
+    /// Lowers `Name { field: value, ... }`. The parser has already looked up
+    /// the struct; every field has to be given exactly once. The values are
+    /// evaluated in the order they are written.
+    fn struct_literal(&self, expr: &Expression<'a>, fields: &[FieldInit<'a>], builder: &mut FunctionBuilder<'a>) -> Lowered<'a> {
+        let ty: &'a Type = expr.eval_type.expect(REPORT);
+        let mut values: Vec<Option<Vec<Operand>>> = vec![None; ty.fields().len()];
+        for init in fields {
+            let Some(index) = ty.fields().iter().position(|field| field.name == init.name) else {
+                self.error_handler.err(
+                    init.line,
+                    init.column,
+                    format!("Struct '{}' has no field '{}'", ty, init.name),
+                    None,
+                );
+            };
+            if values[index].is_some() {
+                self.error_handler.err(
+                    init.line,
+                    init.column,
+                    format!("Field '{}' is given twice", init.name),
+                    None,
+                );
+            }
+            values[index] = Some(self.typed(&init.value, &ty.fields()[index].ty, builder));
+        }
+        let mut operands: Vec<Operand> = Vec::new();
+        for (field, value) in ty.fields().iter().zip(values) {
+            let Some(value) = value else {
+                self.error_handler.err(
+                    expr.line,
+                    expr.column,
+                    format!("Missing field '{}' in {} literal", field.name, ty),
+                    Some(format!("Give every field of {} a value", ty)),
+                );
+            };
+            operands.extend(value);
+        }
+        Lowered { ty, operands }
+    }
+    /// Lowers `Enum::Variant(value, ...)`: the tag, then the fields of every
+    /// variant, which are zero for all but this one.
+    fn variant(
+        &self,
+        expr: &Expression<'a>,
+        name: &str,
+        variant: &str,
+        values: &[Expression<'a>],
+        builder: &mut FunctionBuilder<'a>,
+    ) -> Lowered<'a> {
+        let ty: &'a Type = expr.eval_type.expect(REPORT);
+        if ty.kind() != TypeKind::Enum {
+            self.error_handler.err(expr.line, expr.column, format!("'{}' is not an enum", name), None);
+        }
+        let Some(chosen) = ty.variant(variant) else {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Enum '{}' has no variant '{}'", ty, variant),
+                None,
+            );
+        };
+        if chosen.fields.len() != values.len() {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!(
+                    "Variant '{}::{}' takes {} value(s) but {} were given",
+                    ty,
+                    variant,
+                    chosen.fields.len(),
+                    values.len()
+                ),
+                None,
+            );
+        }
+        let mut operands = vec![Operand::Const(chosen.discriminant)];
+        for other in ty.variants() {
+            if other.name == chosen.name {
+                for (field, value) in other.fields.iter().zip(values) {
+                    operands.extend(self.typed(value, &field.ty, builder));
+                }
+            } else {
+                let count: usize = other.fields.iter().map(|field| self.layout(&field.ty).len()).sum();
+                operands.extend(std::iter::repeat_n(Operand::Const(0), count));
+            }
+        }
+        Lowered { ty, operands }
+    }
+    /// Lowers `match value { pattern => value, ... }` to a switch on the
+    /// value, or on the tag of an enum, with a block per arm. Arms are tried
+    /// in order, so an arm's cases are only the values no earlier arm
+    /// matches; an arm left with none is unreachable. Every value must be
+    /// matched by some arm. The arms join in one block, where a phi per
+    /// scalar picks the result of the arm that ran.
+    ///
+    /// The result has the `expected` type if there is one, or else the
+    /// type of the first arm. Without `needs_result` the arms are lowered
+    /// as statements.
+    fn match_expression(
+        &self,
+        expr: &Expression<'a>,
+        value: &Expression<'a>,
+        arms: &[MatchArm<'a>],
+        needs_result: bool,
+        expected: Option<&'a Type>,
+        builder: &mut FunctionBuilder<'a>,
+    ) -> Option<Lowered<'a>> {
+        let scrutinee = self.expression(value, builder);
+        let ty = scrutinee.ty;
+        let ir_type = self.layout(ty)[0];
+        // Signed integers are offset by the most negative value, so that
+        // they compare like unsigned ones
+        let bias = match ty.kind() {
+            TypeKind::Signed => 1u64 << (ir_type.bits() - 1),
+            _ => 0,
+        };
+        let domain: Vec<(u64, u64)> = match ty.kind() {
+            TypeKind::Unsigned | TypeKind::Signed => vec![(0, ir_type.wrap(u64::MAX))],
+            TypeKind::Bool => vec![(0, 1)],
+            TypeKind::Enum => ty.variants().iter().map(|variant| (variant.discriminant, variant.discriminant)).collect(),
+            _ => self.error_handler.err(
+                value.line,
+                value.column,
+                format!("Cannot match on a {}", ty),
+                Some(String::from("Only integers, bools and enums can be matched on")),
+            ),
+        };
+        let key = if bias == 0 {
+            scrutinee.operands[0]
+        } else {
+            let dest = builder.function.new_value(ir_type);
+            builder.push(Instruction::Binary {
+                dest,
+                ty: ir_type,
+                op: BinaryOp::Add,
+                lhs: scrutinee.operands[0],
+                rhs: Operand::Const(bias),
+            });
+            Operand::Value(dest)
+        };
+
+        // The values each arm matches that no earlier arm does
+        let mut covered: Vec<(u64, u64)> = Vec::new();
+        let mut matched: Vec<Vec<(u64, u64)>> = Vec::new();
+        let mut catch_all: Option<usize> = None;
+        for (i, arm) in arms.iter().enumerate() {
+            let ranges = match &arm.pattern {
+                Pattern::Wildcard | Pattern::Binding(_) => domain.clone(),
+                Pattern::Int(value) if ty.is_integer() => {
+                    let key = self.pattern_key(arm, ty, *value, bias);
+                    vec![(key, key)]
+                }
+                Pattern::Bool(value) if ty.kind() == TypeKind::Bool => vec![(*value as u64, *value as u64)],
+                Pattern::Range(low, high) if ty.is_integer() => {
+                    if low > high {
+                        self.error_handler.err(
+                            arm.line,
+                            arm.column,
+                            format!("Range {}..={} is empty", low, high),
+                            Some(String::from("Put the lower end first")),
+                        );
+                    }
+                    vec![(self.pattern_key(arm, ty, *low, bias), self.pattern_key(arm, ty, *high, bias))]
+                }
+                Pattern::Variant(name, variant, bindings) if name == ty.name() => {
+                    let chosen = self.pattern_variant(arm, ty, variant, bindings);
+                    vec![(chosen.discriminant, chosen.discriminant)]
+                }
+                pattern => self.error_handler.err(
+                    arm.line,
+                    arm.column,
+                    format!("Expected a pattern for {} (found {})", ty, pattern),
+                    None,
+                ),
+            };
+            let new = uncovered(&ranges, &covered);
+            if new.is_empty() {
+                self.error_handler.warn(
+                    arm.line,
+                    arm.column,
+                    String::from("Unreachable match arm"),
+                    Some(String::from("Earlier arms already match every value this one does")),
+                );
+            }
+            if catch_all.is_none() && matches!(arm.pattern, Pattern::Wildcard | Pattern::Binding(_)) {
+                catch_all = Some(i);
+            }
+            covered.extend(new.iter().copied());
+            matched.push(new);
+        }
+        let missing = uncovered(&domain, &covered);
+        if !missing.is_empty() {
+            let names: Vec<String> = match ty.kind() {
+                TypeKind::Enum => ty
+                    .variants()
+                    .iter()
+                    .filter(|variant| !uncovered(&[(variant.discriminant, variant.discriminant)], &covered).is_empty())
+                    .map(|variant| format!("{}::{}", ty, variant.name))
+                    .collect(),
+                _ => missing
+                    .iter()
+                    .map(|&(low, high)| {
+                        let show = |key: u64| match ty.kind() {
+                            TypeKind::Signed => ir_type.sign_extend(key.wrapping_sub(bias)).to_string(),
+                            TypeKind::Bool => (key == 1).to_string(),
+                            _ => key.to_string(),
+                        };
+                        if low == high {
+                            show(low)
+                        } else {
+                            format!("{}..={}", show(low), show(high))
+                        }
+                    })
+                    .collect(),
+            };
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Match on {} does not cover {}", ty, names.join(", ")),
+                Some(String::from("Add arms for them, or a `_` arm for everything else")),
+            );
+        }
+
+        let blocks: Vec<BlockId> = arms.iter().map(|_| builder.new_block()).collect();
+        let mut cases: Vec<(u64, u64, BlockId)> = matched
+            .iter()
+            .zip(&blocks)
+            .flat_map(|(ranges, block)| ranges.iter().map(|&(low, high)| (low, high, *block)))
+            .collect();
+        cases.sort();
+        // Without a catch-all arm, the last case is as good a default as any
+        let default = match catch_all {
+            Some(i) => blocks[i],
+            None => cases.last().map(|&(_, _, block)| block).expect(REPORT),
+        };
+        cases.retain(|&(_, _, block)| block != default);
+        builder.terminate(Terminator::Switch { value: key, cases, default });
+
+        let join = builder.new_block();
+        let mut result_type = expected;
+        let mut results: Vec<(BlockId, Vec<Operand>)> = Vec::new();
+        for (arm, block) in arms.iter().zip(blocks) {
+            builder.current = Some((block, Vec::new()));
+            let bound = self.bind(arm, ty, &scrutinee.operands, builder);
+            let operands = if !needs_result {
+                self.statement_expression(&arm.value, builder);
+                Vec::new()
+            } else if let Some(result_type) = result_type {
+                self.typed(&arm.value, result_type, builder)
+            } else {
+                let lowered = self.expression(&arm.value, builder);
+                result_type = Some(lowered.ty);
+                lowered.operands
+            };
+            for (name, shadowed) in bound {
+                match shadowed {
+                    Some(variable) => builder.variables.insert(name, variable),
+                    None => builder.variables.remove(&name),
+                };
+            }
+            let from = builder.terminate(Terminator::Jump(join));
+            results.push((from, operands));
+        }
+        builder.current = Some((join, Vec::new()));
+        let ty = result_type.filter(|_| needs_result)?;
+        let operands = self
+            .layout(ty)
+            .into_iter()
+            .enumerate()
+            .map(|(i, ir_type)| {
+                let dest = builder.function.new_value(ir_type);
+                let incoming = results.iter().map(|(from, operands)| (*from, operands[i])).collect();
+                builder.push(Instruction::Phi { dest, ty: ir_type, incoming });
+                Operand::Value(dest)
+            })
+            .collect();
+        Some(Lowered { ty, operands })
+    }
+    /// The switch key of an integer pattern, which must fit the matched type.
+    fn pattern_key(&self, arm: &MatchArm<'a>, ty: &Type, value: u64, bias: u64) -> u64 {
+        let ir_type = self.layout(ty)[0];
+        let max = if bias == 0 { ir_type.wrap(u64::MAX) } else { bias - 1 };
+        if value > max {
+            self.error_handler.err(
+                arm.line,
+                arm.column,
+                format!("Pattern {} is out of range for {}", value, ty),
+                None,
+            );
+        }
+        value + bias
+    }
+    /// The variant an `Enum::Variant(a, ...)` pattern names. The names are
+    /// optional, but if given there must be one per field.
+    fn pattern_variant<'t>(&self, arm: &MatchArm<'a>, ty: &'t Type, variant: &str, bindings: &[String]) -> &'t Variant {
+        let Some(chosen) = ty.variant(variant) else {
+            self.error_handler.err(
+                arm.line,
+                arm.column,
+                format!("Enum '{}' has no variant '{}'", ty, variant),
+                None,
+            );
+        };
+        if !bindings.is_empty() && bindings.len() != chosen.fields.len() {
+            self.error_handler.err(
+                arm.line,
+                arm.column,
+                format!(
+                    "Variant '{}::{}' has {} field(s) but {} names were given",
+                    ty,
+                    variant,
+                    chosen.fields.len(),
+                    bindings.len()
+                ),
+                Some(String::from("Name every field, using `_` for those you do not need")),
+            );
+        }
+        for (i, name) in bindings.iter().enumerate() {
+            if name != "_" && bindings[..i].contains(name) {
+                self.error_handler.err(arm.line, arm.column, format!("'{}' is bound twice", name), None);
+            }
+        }
+        chosen
+    }
+    /// Binds the names an arm's pattern gives to the matched value or its
+    /// fields, returning what they shadowed so it can be put back.
+    fn bind(
+        &self,
+        arm: &MatchArm<'a>,
+        ty: &'a Type,
+        operands: &[Operand],
+        builder: &mut FunctionBuilder<'a>,
+    ) -> Vec<(String, Option<Variable<'a>>)> {
+        let mut bound: Vec<(String, Variable<'a>)> = Vec::new();
+        match &arm.pattern {
+            Pattern::Binding(name) => bound.push((name.clone(), self.local(name, ty, operands, builder))),
+            Pattern::Variant(_, variant, bindings) => {
+                // The variant's scalars follow the tag and those of the variants before it
+                let mut start = 1 + ty
+                    .variants()
+                    .iter()
+                    .take_while(|other| other.name != *variant)
+                    .flat_map(|other| &other.fields)
+                    .map(|field| self.layout(&field.ty).len())
+                    .sum::<usize>();
+                let fields = &ty.variant(variant).expect(REPORT).fields;
+                for (name, field) in bindings.iter().zip(fields) {
+                    let end = start + self.layout(&field.ty).len();
+                    if name != "_" {
+                        bound.push((name.clone(), self.local(name, &field.ty, &operands[start..end], builder)));
+                    }
+                    start = end;
+                }
+            }
+            _ => {}
+        }
+        bound
+            .into_iter()
+            .map(|(name, variable)| {
+                let shadowed = builder.variables.insert(name.clone(), variable);
+                (name, shadowed)
+            })
+            .collect()
+    }
+    /// A local holding `operands`, stored in a stack slot if it is an array
+    /// or the function takes its address; otherwise constants are copied
+    /// into values first.
+    fn local(&self, name: &str, ty: &'a Type, operands: &[Operand], builder: &mut FunctionBuilder<'a>) -> Variable<'a> {
+        // Arrays are kept in memory so they can be indexed
+        if builder.addressed.contains(name) || ty.kind() == TypeKind::Array {
+            let address = builder.function.new_value(IrType::I64);
+            builder.push(Instruction::Alloca { dest: address, size: ty.size().unwrap_or(0), align: ty.align() });
+            self.store(ty, Operand::Value(address), operands.to_vec(), builder);
+            return Variable { ty, values: Vec::new(), address: Some(address) };
+        }
+        let values = operands
+            .iter()
+            .zip(self.layout(ty))
+            .map(|(operand, ir_type)| match operand {
+                Operand::Value(value) => *value,
+                constant => {
+                    let dest = builder.function.new_value(ir_type);
+                    builder.push(Instruction::Copy { dest, ty: ir_type, src: *constant });
+                    dest
+                }
+            })
+            .collect();
+        Variable { ty, values, address: None }
+    }
+    /// Lowers `lhs operator rhs`. Without an `expected` type, a literal
+    /// operand takes its type from the other one. Pointer arithmetic keeps
+    /// the type of the pointer whatever is expected, for the caller to check.
+    fn binary(
+        &self,
+        expr: &Expression<'a>,
+        operator: BinaryOperator,
+        lhs: &Expression<'a>,
+        rhs: &Expression<'a>,
+        expected: Option<&'a Type>,
+        builder: &mut FunctionBuilder<'a>,
+    ) -> Lowered<'a> {
+        let (lhs, rhs, ty) = match expected {
+            Some(ty) => {
+                let lowered = match &lhs.content {
+                    _ if self.untyped(lhs, builder) => Lowered { ty, operands: self.typed(lhs, ty, builder) },
+                    ExpressionContent::Binary(operator, inner_lhs, inner_rhs) => {
+                        self.binary(lhs, *operator, inner_lhs, inner_rhs, expected, builder)
+                    }
+                    ExpressionContent::Call(name, args) if ARITHMETIC.contains(&name.as_str()) => {
+                        self.intrinsic(lhs, name, args, expected, builder)
+                    }
+                    ExpressionContent::Variable(_)
+                    | ExpressionContent::Call(..)
+                    | ExpressionContent::FieldAccess(..)
+                    | ExpressionContent::Deref(_)
+                    | ExpressionContent::AddressOf(_) => self.expression(lhs, builder),
+                    _ => Lowered { ty, operands: self.typed(lhs, ty, builder) },
+                };
+                if lowered.ty.kind() == TypeKind::Pointer {
+                    return self.pointer_arithmetic(operator, lhs, lowered, rhs, builder);
+                }
+                self.expect_type(lhs, &lowered, ty);
+                (lowered.operands[0], self.typed(rhs, ty, builder)[0], ty)
+            }
+            None if self.untyped(lhs, builder) => {
+                let (rhs, ty) = self.integer(rhs, builder);
+                (self.typed(lhs, ty, builder)[0], rhs, ty)
+            }
+            None => {
+                let lowered = self.expression(lhs, builder);
+                if lowered.ty.kind() == TypeKind::Pointer {
+                    return self.pointer_arithmetic(operator, lhs, lowered, rhs, builder);
+                }
+                let (lhs, ty) = self.expect_integer(lhs, lowered);
+                (lhs, self.typed(rhs, ty, builder)[0], ty)
+            }
+        };
+        let signed = ty.kind() == TypeKind::Signed;
+        let op = match operator {
+            BinaryOperator::Add => BinaryOp::Add,
+            BinaryOperator::Subtract => BinaryOp::Sub,
+            BinaryOperator::Multiply => BinaryOp::Mul,
+            BinaryOperator::Divide if signed => BinaryOp::SDiv,
+            BinaryOperator::Divide => BinaryOp::UDiv,
+            BinaryOperator::Remainder if signed => BinaryOp::SRem,
+            BinaryOperator::Remainder => BinaryOp::URem,
+        };
+        Lowered { ty, operands: vec![self.arithmetic(expr, op, ty, (lhs, rhs), self.overflow, builder)] }
+    }
+    /// Reports `lhs op rhs`, with both sides known, if `check` says it overflows `ty`.
+    fn overflows(&self, expr: &Expression<'a>, check: BinaryOp, ty: &Type, lhs: u64, rhs: u64) {
+        let ir_type = self.layout(ty)[0];
+        if !ir_type.overflows(check, lhs, rhs) {
+            return;
+        }
+        let (symbol, name) = match check {
+            BinaryOp::UAddOverflow | BinaryOp::SAddOverflow => ('+', "add"),
+            BinaryOp::USubOverflow | BinaryOp::SSubOverflow => ('-', "sub"),
+            _ => ('*', "mul"),
+        };
+        let show = |value: u64| match ty.kind() {
+            TypeKind::Signed => ir_type.sign_extend(value).to_string(),
+            _ => value.to_string(),
+        };
+        self.error_handler.err(
+            expr.line,
+            expr.column,
+            format!("{} {} {} overflows {}", show(lhs), symbol, show(rhs), ty),
+            Some(format!("Use wrapping_{} or saturating_{} if it is meant to overflow", name, name)),
+        );
+    }
+    /// Lowers `wrapping_add(a, b)` and the like: the operation in its name,
+    /// overflowing as its name says. Its operands are typed like those of
+    /// the operator.
+    fn intrinsic(
+        &self,
+        expr: &Expression<'a>,
+        name: &str,
+        args: &[Expression<'a>],
+        expected: Option<&'a Type>,
+        builder: &mut FunctionBuilder<'a>,
+    ) -> Lowered<'a> {
+        let [lhs, rhs] = args else {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Function '{}' takes 2 argument(s) but {} were given", name, args.len()),
+                None,
+            );
+        };
+        let (lhs, rhs, ty) = match expected {
+            Some(ty) => (self.typed(lhs, ty, builder)[0], self.typed(rhs, ty, builder)[0], ty),
+            None if self.untyped(lhs, builder) => {
+                let (rhs, ty) = self.integer(rhs, builder);
+                (self.typed(lhs, ty, builder)[0], rhs, ty)
+            }
+            None => {
+                let (lhs, ty) = self.integer(lhs, builder);
+                (lhs, self.typed(rhs, ty, builder)[0], ty)
+            }
+        };
+        let (overflow, operation) = name.split_once('_').expect(REPORT);
+        let overflow = match overflow {
+            "checked" => Overflow::Checked,
+            "wrapping" => Overflow::Wrapping,
+            _ => Overflow::Saturating,
+        };
+        let op = match operation {
+            "add" => BinaryOp::Add,
+            "sub" => BinaryOp::Sub,
+            _ => BinaryOp::Mul,
+        };
+        Lowered { ty, operands: vec![self.arithmetic(expr, op, ty, (lhs, rhs), overflow, builder)] }
+    }
+    /// `lhs op rhs` as a `ty`. If it is an addition, subtraction or
+    /// multiplication, whose result may not fit `ty`, `overflow` says what
+    /// happens then. Checked division also panics on a zero divisor and on
+    /// MIN / -1. Checked arithmetic on known operands is checked at compile
+    /// time instead.
+    fn arithmetic(
+        &self,
+        expr: &Expression<'a>,
+        op: BinaryOp,
+        ty: &Type,
+        (lhs, rhs): (Operand, Operand),
+        overflow: Overflow,
+        builder: &mut FunctionBuilder<'a>,
+    ) -> Operand {
+        let ir_type = self.layout(ty)[0];
+        let signed = ty.kind() == TypeKind::Signed;
+        if overflow == Overflow::Checked && matches!(op, BinaryOp::UDiv | BinaryOp::URem | BinaryOp::SDiv | BinaryOp::SRem) {
+            self.check_division(expr, op, ty, (lhs, rhs), builder);
+        }
+        let Some(check) = op.overflow(signed).filter(|_| overflow != Overflow::Wrapping) else {
+            return push_binary(op, ir_type, lhs, rhs, builder);
+        };
+        if overflow == Overflow::Checked {
+            match (lhs, rhs) {
+                (Operand::Const(lhs), Operand::Const(rhs)) => self.overflows(expr, check, ty, lhs, rhs),
+                _ => {
+                    let overflowed = push_binary(check, ir_type, lhs, rhs, builder);
+                    let what = match op {
+                        BinaryOp::Add => format!("addition overflowed {}", ty),
+                        BinaryOp::Sub => format!("subtraction overflowed {}", ty),
+                        _ => format!("multiplication overflowed {}", ty),
+                    };
+                    self.guard(expr, overflowed, false, &what, builder);
+                }
+            }
+            return push_binary(op, ir_type, lhs, rhs, builder);
+        }
+        let result = push_binary(op, ir_type, lhs, rhs, builder);
+        let overflowed = push_binary(check, ir_type, lhs, rhs, builder);
+        let (fits, saturated, join) = (builder.new_block(), builder.new_block(), builder.new_block());
+        builder.terminate(Terminator::Branch { condition: overflowed, then_block: saturated, else_block: fits });
+        builder.current = Some((fits, Vec::new()));
+        builder.terminate(Terminator::Jump(join));
+        builder.current = Some((saturated, Vec::new()));
+        let limit = if !signed {
+            // Only subtraction goes below 0
+            Operand::Const(if op == BinaryOp::Sub { 0 } else { ir_type.wrap(u64::MAX) })
+        } else {
+            // The result would have been negative if the left side is, or
+            // for a product, if exactly one side is
+            let sign_bit = Operand::Const(ir_type.bits() as u64 - 1);
+            let mut negative = push_binary(BinaryOp::LShr, ir_type, lhs, sign_bit, builder);
+            if op == BinaryOp::Mul {
+                let rhs_negative = push_binary(BinaryOp::LShr, ir_type, rhs, sign_bit, builder);
+                let signs = push_binary(BinaryOp::Add, ir_type, negative, rhs_negative, builder);
+                negative = push_binary(BinaryOp::And, ir_type, signs, Operand::Const(1), builder);
+            }
+            // One more than the maximum wraps around to the minimum
+            let max = Operand::Const(u64::MAX >> (65 - ir_type.bits()));
+            push_binary(BinaryOp::Add, ir_type, max, negative, builder)
+        };
+        builder.terminate(Terminator::Jump(join));
+        builder.current = Some((join, Vec::new()));
+        let dest = builder.function.new_value(ir_type);
+        builder.push(Instruction::Phi { dest, ty: ir_type, incoming: vec![(fits, result), (saturated, limit)] });
+        Operand::Value(dest)
+    }
+    /// Panics at runtime unless `lhs op rhs`, a division or remainder, has a
+    /// nonzero divisor and, if `ty` is signed, is not MIN / -1, whose
+    /// quotient does not fit. Reported at compile time where the operands
+    /// are known.
+    fn check_division(
+        &self,
+        expr: &Expression<'a>,
+        op: BinaryOp,
+        ty: &Type,
+        (lhs, rhs): (Operand, Operand),
+        builder: &mut FunctionBuilder<'a>,
+    ) {
+        let ir_type = self.layout(ty)[0];
+        let min = 1u64 << (ir_type.bits() - 1);
+        let minus_one = ir_type.wrap(u64::MAX);
+        let symbol = if matches!(op, BinaryOp::UDiv | BinaryOp::SDiv) { '/' } else { '%' };
+        match rhs {
+            Operand::Const(0) => self.error_handler.err(expr.line, expr.column, String::from("Division by zero"), None),
+            Operand::Const(_) => {}
+            Operand::Value(_) => self.guard(expr, rhs, true, "division by zero", builder),
+        }
+        if !matches!(op, BinaryOp::SDiv | BinaryOp::SRem) {
+            return;
+        }
+        match (lhs, rhs) {
+            (Operand::Const(lhs), Operand::Const(rhs)) if lhs == min && rhs == minus_one => self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("{} {} -1 overflows {}", ir_type.sign_extend(min), symbol, ty),
+                None,
+            ),
+            (Operand::Const(lhs), _) if lhs != min => {}
+            (_, Operand::Const(rhs)) if rhs != minus_one => {}
+            _ => {
+                // Both are zero only for MIN / -1
+                let from_min = push_binary(BinaryOp::Sub, ir_type, lhs, Operand::Const(min), builder);
+                let from_minus_one = push_binary(BinaryOp::Add, ir_type, rhs, Operand::Const(1), builder);
+                let overflowed = push_binary(BinaryOp::Or, ir_type, from_min, from_minus_one, builder);
+                let what = match symbol {
+                    '/' => format!("division overflowed {}", ty),
+                    _ => format!("remainder overflowed {}", ty),
+                };
+                self.guard(expr, overflowed, true, &what, builder);
+            }
+        }
+    }
+    /// Lowers `pointer + n`, `pointer - n` and `pointer - pointer`, where
+    /// offsets count values of the pointee's type rather than bytes.
+    fn pointer_arithmetic(
+        &self,
+        operator: BinaryOperator,
+        lhs: &Expression<'a>,
+        pointer: Lowered<'a>,
+        rhs: &Expression<'a>,
+        builder: &mut FunctionBuilder<'a>,
+    ) -> Lowered<'a> {
+        let size = self.pointee(lhs, pointer.ty).size().unwrap_or(1);
+        let op = match operator {
+            BinaryOperator::Add => BinaryOp::Add,
+            BinaryOperator::Subtract => BinaryOp::Sub,
+            _ => self.error_handler.err(
+                lhs.line,
+                lhs.column,
+                format!("Cannot use '{}' on {}", operator, pointer.ty),
+                Some(String::from("Pointers can only be offset with `+` and `-`")),
+            ),
+        };
+        let offset = match self.untyped(rhs, builder) {
+            true => Lowered { ty: &UINT64, operands: self.typed(rhs, &UINT64, builder) },
+            false => self.expression(rhs, builder),
+        };
+        let scaled = builder.function.new_value(IrType::I64);
+        let dest = builder.function.new_value(IrType::I64);
+        if op == BinaryOp::Sub && offset.ty.kind() == TypeKind::Pointer {
+            if offset.ty != pointer.ty {
+                self.error_handler.err(
+                    rhs.line,
+                    rhs.column,
+                    format!("Expected {} (found {})", pointer.ty, offset.ty),
+                    None,
+                );
+            }
+            // The distance between them, in values
+            let lhs = pointer.operands[0];
+            let rhs = offset.operands[0];
+            builder.push(Instruction::Binary { dest: scaled, ty: IrType::I64, op, lhs, rhs });
+            let rhs = Operand::Const(size);
+            builder.push(Instruction::Binary { dest, ty: IrType::I64, op: BinaryOp::SDiv, lhs: Operand::Value(scaled), rhs });
+            return Lowered { ty: &INT64, operands: vec![Operand::Value(dest)] };
+        }
+        if offset.ty.kind() != TypeKind::Unsigned {
+            self.error_handler.err(
+                rhs.line,
+                rhs.column,
+                format!("Expected an unsigned integer (found {})", offset.ty),
+                Some(String::from("Pointers are offset by unsigned integers; subtract to move backwards")),
+            );
+        }
+        let (lhs, rhs) = (offset.operands[0], Operand::Const(size));
+        builder.push(Instruction::Binary { dest: scaled, ty: IrType::I64, op: BinaryOp::Mul, lhs, rhs });
+        let (lhs, rhs) = (pointer.operands[0], Operand::Value(scaled));
+        builder.push(Instruction::Binary { dest, ty: IrType::I64, op, lhs, rhs });
+        Lowered { ty: pointer.ty, operands: vec![Operand::Value(dest)] }
+    }
+    /// Lowers an expression that must be of type `ty`.
+    fn typed(&self, expr: &Expression<'a>, ty: &'a Type, builder: &mut FunctionBuilder<'a>) -> Vec<Operand> {
+        match &expr.content {
+            // Literals fit any integer type they are in range of
+            ExpressionContent::IntLiteral(_) if ty.is_integer() => {
+                let value = self.constants.borrow_mut().evaluate(expr, ty, &builder.constants);
+                return vec![Operand::Const(value)];
+            }
+            ExpressionContent::Variable(name) if ty.is_integer() && builder.untyped.contains_key(name) => {
+                return vec![self.infer(expr, name, ty, builder)];
+            }
+            ExpressionContent::Binary(operator, lhs, rhs) if ty.is_integer() => {
+                let lowered = self.binary(expr, *operator, lhs, rhs, Some(ty), builder);
+                self.expect_type(expr, &lowered, ty);
+                return lowered.operands;
+            }
+            ExpressionContent::Call(name, args) if ty.is_integer() && ARITHMETIC.contains(&name.as_str()) => {
+                return self.intrinsic(expr, name, args, Some(ty), builder).operands;
+            }
+            ExpressionContent::Match(value, arms) => {
+                return self.match_expression(expr, value, arms, true, Some(ty), builder).expect(REPORT).operands;
+            }
+            ExpressionContent::Null if ty.kind() == TypeKind::Pointer => return vec![Operand::Const(0)],
+            ExpressionContent::ArrayLiteral(values) if ty.kind() == TypeKind::Array => {
+                let (element, length) = (ty.element().expect(REPORT), ty.length().expect(REPORT));
+                if values.len() as u64 != length {
+                    self.error_handler.err(
+                        expr.line,
+                        expr.column,
+                        format!("Expected {} elements (found {})", length, values.len()),
+                        None,
+                    );
+                }
+                return values.iter().flat_map(|value| self.typed(value, element, builder)).collect();
+            }
+            _ => {}
+        }
+        let lowered = self.expression(expr, builder);
+        self.expect_type(expr, &lowered, ty);
+        lowered.operands
+    }
+    /// Uses the untyped binding `name` as a `ty`, which it has from now on.
+    fn infer(&self, expr: &Expression<'a>, name: &str, ty: &'a Type, builder: &mut FunctionBuilder<'a>) -> Operand {
+        let untyped = builder.untyped.get_mut(name).expect(REPORT);
+        match untyped.inferred {
+            Some((inferred, line, column)) if inferred != ty => self.error_handler.err_with_note(
+                expr.line,
+                expr.column,
+                format!("Expected {} (found {})", ty, inferred),
+                (line, column, format!("'{}' is inferred to be {} here", name, inferred)),
+                Some(format!("Give '{}' a type with `let {}: type = ...`", name, name)),
+            ),
+            Some(_) => {}
+            None => untyped.inferred = Some((ty, expr.line, expr.column)),
+        }
+        let value = untyped.value.clone();
+        Operand::Const(self.constants.borrow_mut().evaluate(&value, ty, &builder.constants))
+    }
+    /// Whether `expr` takes its type from context: an integer literal, or
+    /// a `let` of one whose type is not inferred yet.
+    fn untyped(&self, expr: &Expression<'a>, builder: &FunctionBuilder<'a>) -> bool {
+        match &expr.content {
+            ExpressionContent::IntLiteral(_) => true,
+            ExpressionContent::Variable(name) => builder.untyped.get(name).is_some_and(|untyped| untyped.inferred.is_none()),
+            _ => false,
+        }
+    }
+    /// Checks that `expr`, lowered already, is of type `ty`.
+    fn expect_type(&self, expr: &Expression<'a>, lowered: &Lowered<'a>, ty: &Type) {
+        if lowered.ty != ty {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Expected {} (found {})", ty, lowered.ty),
+                conversion(lowered.ty, ty),
+            );
+        }
+    }
+    /// Lowers an expression that must be an integer.
+    fn integer(&self, expr: &Expression<'a>, builder: &mut FunctionBuilder<'a>) -> (Operand, &'a Type) {
+        let lowered = self.expression(expr, builder);
+        self.expect_integer(expr, lowered)
+    }
+    /// Checks that `expr`, lowered already, is an integer.
+    fn expect_integer(&self, expr: &Expression<'a>, lowered: Lowered<'a>) -> (Operand, &'a Type) {
+        if !lowered.ty.is_integer() {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Expected an integer (found {})", lowered.ty),
+                None,
+            );
+        }
+        (lowered.operands[0], lowered.ty)
+    }
+    /// Lowers a call; its result is only required when it is used as a value.
+    fn call(
+        &self,
+        expr: &Expression<'a>,
+        name: &str,
+        args: &[Expression<'a>],
+        needs_result: bool,
+        builder: &mut FunctionBuilder<'a>,
+    ) -> Option<Lowered<'a>> {
+        if name == SYSCALL {
+            return Some(self.syscall(expr, args, builder));
+        }
+        if name == LEN {
+            return Some(self.len(expr, args, builder));
+        }
+        if ARITHMETIC.contains(&name) {
+            return Some(self.intrinsic(expr, name, args, None, builder));
+        }
+        let Some(signature) = self.signatures.get(name) else {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Unknown function '{}'", name),
+                None,
+            );
+        };
+        if signature.params.len() != args.len() {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!(
+                    "Function '{}' takes {} argument(s) but {} were given",
+                    name,
+                    signature.params.len(),
+                    args.len()
+                ),
+                None,
+            );
+        }
+        let mut lowered: Vec<Option<Vec<Operand>>> = vec![None; args.len()];
+        let signature = if signature.type_params.is_empty() {
+            signature.clone()
+        } else {
+            self.instantiate(expr, name, signature, args, &mut lowered, builder)
+        };
+        if needs_result && signature.return_type.is_none() {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("Function '{}' does not return a value", name),
+                None,
+            );
+        }
+        let mut register_args: Vec<Operand> = Vec::new();
+        let mut stack_args: Vec<Operand> = Vec::new();
+        let placement = self.on_stack(&signature.params, signature.return_type);
+        for (((arg, ty), on_stack), lowered) in args.iter().zip(&signature.params).zip(placement).zip(lowered) {
+            let operands = match lowered {
+                Some(operands) => operands,
+                None => self.typed(arg, ty, builder),
+            };
+            let passed = self.pack(ty, operands, builder);
+            if on_stack {
+                stack_args.extend(passed);
+            } else {
+                register_args.extend(passed);
+            }
+        }
+        register_args.extend(stack_args);
+        let dests: Vec<Value> = match signature.return_type {
+            Some(ty) => self.abi_layout(ty).into_iter().map(|ty| builder.function.new_value(ty)).collect(),
+            None => Vec::new(),
+        };
+        builder.push(Instruction::Call { dests: dests.clone(), function: signature.symbol.clone(), args: register_args });
+        signature.return_type.map(|ty| {
+            let operands = self.unpack(ty, &dests, builder).into_iter().map(Operand::Value).collect();
+            Lowered { ty, operands }
+        })
+    }
+    /// Infers what the type parameters of the generic function `name` stand
+    /// for from the types of `args`, lowering the arguments that takes into
+    /// `lowered`, and returns the signature of the copy made for them.
+    /// Literals are left until last, so that they can take their type from
+    /// the other arguments.
+    fn instantiate(
+        &self,
+        expr: &Expression<'a>,
+        name: &str,
+        generic: &Signature<'a>,
+        args: &[Expression<'a>],
+        lowered: &mut [Option<Vec<Operand>>],
+        builder: &mut FunctionBuilder<'a>,
+    ) -> Signature<'a> {
+        let params = &generic.type_params;
+        let mut bindings: HashMap<String, String> = HashMap::new();
+        for literals in [false, true] {
+            for (i, (arg, param)) in args.iter().zip(&generic.params).enumerate() {
+                if lowered[i].is_some() {
+                    continue;
+                }
+                if let Some(ty) = substituted(param.name(), params, &bindings) {
+                    lowered[i] = Some(self.typed(arg, self.types.resolve(&ty).expect(REPORT), builder));
+                    continue;
+                }
+                if !literals && self.untyped(arg, builder) {
+                    continue;
+                }
+                let value = self.expression(arg, builder);
+                if !unify(param.name(), value.ty.name(), params, &mut bindings) {
+                    self.error_handler.err(
+                        arg.line,
+                        arg.column,
+                        format!("Expected {} (found {})", param, value.ty),
+                        None,
+                    );
+                }
+                lowered[i] = Some(value.operands);
+            }
+        }
+        let resolve = |ty: &Type| -> &'a Type {
+            let name = substituted(ty.name(), params, &bindings).expect(REPORT);
+            self.types.resolve(&name).expect(REPORT)
+        };
+        let types: Vec<&'a Type> = params.iter().map(|param| resolve(typing::parameter(param))).collect();
+        let names: Vec<&str> = types.iter().map(|ty| ty.name()).collect();
+        let symbol = mangle_generic(&[name], &names);
+        let mut instances = self.instances.borrow_mut();
+        if !instances.iter().any(|instance| instance.symbol == symbol) {
+            let depth = self.depth.get() + 1;
+            if depth > MAX_INSTANCE_DEPTH {
+                self.error_handler.err(
+                    expr.line,
+                    expr.column,
+                    format!("Copies of generic function '{}' nest more than {} deep", name, MAX_INSTANCE_DEPTH),
+                    Some(String::from("A generic function cannot call itself with types made from its own, like a pointer to one")),
+                );
+            }
+            instances.push(Instance { symbol: symbol.clone(), name: name.to_string(), types: types.clone(), depth });
+        }
+        Signature {
+            symbol,
+            params: generic.params.iter().map(|ty| resolve(ty)).collect(),
+            return_type: generic.return_type.map(resolve),
+            type_params: Vec::new(),
+        }
+    }
+    /// Lowers `syscall(n, a1..a6)`. Arguments are flattened like any call's,
+    /// so a str passes its pointer and length as two arguments.
+    fn syscall(&self, expr: &Expression<'a>, args: &[Expression<'a>], builder: &mut FunctionBuilder<'a>) -> Lowered<'a> {
+        let Some((number, rest)) = args.split_first() else {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                String::from("syscall needs a syscall number"),
+                Some(String::from("e.g. syscall(60, 0) exits with code 0")),
+            );
+        };
+        let mut operands = vec![self.integer(number, builder).0];
+        for arg in rest {
+            operands.extend(self.expression(arg, builder).operands);
+        }
+        if operands.len() > 7 {
+            self.error_handler.err(
+                expr.line,
+                expr.column,
+                format!("syscall takes at most 6 arguments but {} were given", operands.len() - 1),
+                Some(String::from("A str counts as two arguments: its pointer and its length")),
+            );
+        }
+        // All of %rax, which is negative on failure
+        let ir_type = self.layout(&INT64)[0];
+        let dest = builder.function.new_value(ir_type);
+        builder.push(Instruction::Syscall { dest, ty: ir_type, args: operands });
+        Lowered { ty: &INT64, operands: vec![Operand::Value(dest)] }
+    }
+    /// The name of the read-only global holding `value`.
+    fn intern(&self, value: &str) -> String {
+        let mut strings = self.strings.borrow_mut();
+        let index = match strings.iter().position(|string| string == value) {
+            Some(index) => index,
+            None => {
+                strings.push(value.to_string());
+                strings.len() - 1
+            }
+        };
+        string_name(index)
+    }
+    /// The IR types of the scalars a value of type `ty` is made of.
+    fn layout(&self, ty: &Type) -> Vec<IrType> {
+        self.scalars(ty).into_iter().map(|(_, ir_type)| ir_type).collect()
+    }
+    /// The scalars of `ty`, each with its offset in the value's memory layout.
+    fn scalars(&self, ty: &Type) -> Vec<(u64, IrType)> {
+        match ty.kind() {
+            TypeKind::Unsigned | TypeKind::Signed | TypeKind::Bool => match ty.size().and_then(IrType::from_size) {
+                Some(ir_type) => vec![(0, ir_type)],
+                None => self.error_handler.comperr(
+                    0,
+                    0,
+                    format!("no IR type for {}", ty),
+                    Some(String::from(REPORT)),
+                ),
+            },
+            TypeKind::Pointer => vec![(0, IrType::I64)],
+            // Pointer and length
+            TypeKind::Str | TypeKind::Slice => vec![(0, IrType::I64), (8, IrType::I64)],
+            TypeKind::Array => {
+                let element = ty.element().expect(REPORT);
+                let size = element.size().unwrap_or(0);
+                let scalars = self.scalars(element);
+                (0..ty.length().expect(REPORT))
+                    .flat_map(|i| scalars.iter().map(move |(offset, ir_type)| (i * size + offset, *ir_type)))
+                    .collect()
+            }
+            TypeKind::Struct => self.field_scalars(ty.fields()),
+            TypeKind::Enum => {
+                let mut scalars = self.scalars(ty.tag());
+                for variant in ty.variants() {
+                    scalars.extend(self.field_scalars(&variant.fields));
+                }
+                scalars
+            }
+            // Replaced before a copy of a generic function is lowered
+            TypeKind::Parameter => self.error_handler.comperr(
+                0,
+                0,
+                format!("type parameter {} was not replaced", ty),
+                Some(String::from(REPORT)),
+            ),
+        }
+    }
+    fn field_scalars(&self, fields: &[Field]) -> Vec<(u64, IrType)> {
+        fields
+            .iter()
+            .flat_map(|field| self.scalars(&field.ty).into_iter().map(|(offset, ir_type)| (field.offset + offset, ir_type)))
+            .collect()
+    }
+    /// Whether System V passes `ty` in memory: structs, enums and arrays over
+    /// 16 bytes.
+    fn in_memory(&self, ty: &Type) -> bool {
+        is_aggregate(ty) && ty.size().unwrap_or(0) > 16
+    }
+    /// The IR types a value of type `ty` is passed and returned as: its
+    /// scalars, or one i64 per eightbyte for a struct, enum or array.
+    fn abi_layout(&self, ty: &Type) -> Vec<IrType> {
+        if is_aggregate(ty) {
+            vec![IrType::I64; ty.size().unwrap_or(0).div_ceil(8) as usize]
+        } else {
+            self.layout(ty)
+        }
+    }
+    /// Which of the parameters of a function go on the stack.
+    fn on_stack(&self, params: &[&Type], return_type: Option<&Type>) -> Vec<bool> {
+        // A result returned in memory takes the first register for its address
+        let mut registers = ARGUMENT_REGISTERS - return_type.is_some_and(|ty| self.in_memory(ty)) as usize;
+        params
+            .iter()
+            .map(|ty| {
+                let needed = self.abi_layout(ty).len();
+                if self.in_memory(ty) || needed > registers {
+                    true
+                } else {
+                    registers -= needed;
+                    false
+                }
+            })
+            .collect()
+    }
+    /// Packs the scalars of a value into the form it is passed in, see `abi_layout`.
+    /// The overlapping fields of an enum's variants are or'ed together,
+    /// which works because the ones a value is not are zero.
+    fn pack(&self, ty: &Type, operands: Vec<Operand>, builder: &mut FunctionBuilder<'a>) -> Vec<Operand> {
+        if !is_aggregate(ty) {
+            return operands;
+        }
+        let mut eightbytes: Vec<Option<Operand>> = vec![None; self.abi_layout(ty).len()];
+        for ((offset, _), operand) in self.scalars(ty).into_iter().zip(operands) {
+            let shift = offset % 8 * 8;
+            let part = if shift == 0 {
+                operand
+            } else {
+                let dest = builder.function.new_value(IrType::I64);
+                builder.push(Instruction::Binary {
+                    dest,
+                    ty: IrType::I64,
+                    op: BinaryOp::Shl,
+                    lhs: operand,
+                    rhs: Operand::Const(shift),
+                });
+                Operand::Value(dest)
+            };
+            let eightbyte = &mut eightbytes[offset as usize / 8];
+            *eightbyte = Some(match *eightbyte {
+                None => part,
+                Some(lhs) => {
+                    let dest = builder.function.new_value(IrType::I64);
+                    builder.push(Instruction::Binary { dest, ty: IrType::I64, op: BinaryOp::Or, lhs, rhs: part });
+                    Operand::Value(dest)
+                }
+            });
+        }
+        // Fields are aligned, so no eightbyte is all padding
+        eightbytes.into_iter().map(|eightbyte| eightbyte.unwrap_or(Operand::Const(0))).collect()
+    }
+    /// Unpacks the scalars of a value from the form it is passed in. Bits
+    /// outside a scalar are masked off, since C leaves padding undefined.
+    fn unpack(&self, ty: &Type, passed: &[Value], builder: &mut FunctionBuilder<'a>) -> Vec<Value> {
+        if !is_aggregate(ty) {
+            return passed.to_vec();
+        }
+        let mut values: Vec<Value> = Vec::new();
+        for (offset, ir_type) in self.scalars(ty) {
+            let eightbyte = passed[offset as usize / 8];
+            let shift = offset % 8 * 8;
+            if ir_type == IrType::I64 {
+                values.push(eightbyte);
+                continue;
+            }
+            // A shift by 0 would be folded away, so the low scalar is masked instead
+            let (op, rhs) = if shift == 0 { (BinaryOp::And, ir_type.wrap(u64::MAX)) } else { (BinaryOp::LShr, shift) };
+            let dest = builder.function.new_value(ir_type);
+            builder.push(Instruction::Binary {
+                dest,
+                ty: ir_type,
+                op,
+                lhs: Operand::Value(eightbyte),
+                rhs: Operand::Const(rhs),
+            });
+            values.push(dest);
+        }
+        values
+    }
+}
+
+/// Whether C has a type like `ty`: integers, pointers to integers, and
+/// structs made of those.
+fn has_c_equivalent(ty: &Type) -> bool {
+    match ty.kind() {
+        TypeKind::Unsigned | TypeKind::Signed => true,
+        // Pointers to integers, through any number of pointers
+        TypeKind::Pointer => BUILTIN_TYPES.get(ty.name().trim_start_matches('*')).is_some_and(|ty| ty.is_integer()),
+        TypeKind::Bool | TypeKind::Str | TypeKind::Enum | TypeKind::Array | TypeKind::Slice | TypeKind::Parameter => false,
+        TypeKind::Struct => ty.fields().iter().all(|field| has_c_equivalent(&field.ty)),
+    }
+}
+
+/// Collects the names a statement takes the address of with `&`.
+fn addressed_in_node(node: &AstNode, names: &mut HashSet<String>) {
+    match &node.value {
+        Some(AstNodeValue::Expression(expr)) => addressed_in(expr, names),
+        Some(AstNodeValue::Let(binding)) => addressed_in(&binding.value, names),
+        Some(AstNodeValue::Assignment(assignment)) => {
+            addressed_in(&assignment.target, names);
+            addressed_in(&assignment.value, names);
+        }
+        Some(AstNodeValue::Asm(block)) => block.inputs.iter().for_each(|input| addressed_in(&input.value, names)),
+        Some(AstNodeValue::Scope(scope)) => {
+            for statement in &scope.children {
+                statement.children.iter().for_each(|node| addressed_in_node(node, names));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the names an expression takes the address of with `&`.
+fn addressed_in(expr: &Expression, names: &mut HashSet<String>) {
+    match &expr.content {
+        ExpressionContent::AddressOf(name) => {
+            names.insert(name.clone());
+        }
+        ExpressionContent::Call(_, args) | ExpressionContent::Variant(_, _, args) => {
+            args.iter().for_each(|arg| addressed_in(arg, names));
+        }
+        ExpressionContent::Binary(_, lhs, rhs) => {
+            addressed_in(lhs, names);
+            addressed_in(rhs, names);
+        }
+        ExpressionContent::FieldAccess(value, _) | ExpressionContent::Deref(value) | ExpressionContent::Cast(value, _) => {
+            addressed_in(value, names)
+        }
+        ExpressionContent::ArrayLiteral(values) => values.iter().for_each(|value| addressed_in(value, names)),
+        ExpressionContent::Index(value, index) => {
+            addressed_in(value, names);
+            addressed_in(index, names);
+        }
+        ExpressionContent::Slice(value, start, end) => {
+            addressed_in(value, names);
+            start.iter().chain(end).for_each(|bound| addressed_in(bound, names));
+        }
+        ExpressionContent::StructLiteral(_, fields) => fields.iter().for_each(|field| addressed_in(&field.value, names)),
+        ExpressionContent::Match(value, arms) => {
+            addressed_in(value, names);
+            arms.iter().for_each(|arm| addressed_in(&arm.value, names));
+        }
+        _ => {}
+    }
+}
+
+/// The parts of the inclusive `ranges` that are not in the inclusive
+/// ranges `covered`.
+fn uncovered(ranges: &[(u64, u64)], covered: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut rest = ranges.to_vec();
+    for &(low, high) in covered {
+        rest = rest
+            .into_iter()
+            .flat_map(|(l, h)| {
+                if h < low || l > high {
+                    return vec![(l, h)];
+                }
+                let mut parts = Vec::new();
+                if l < low {
+                    parts.push((l, low - 1));
+                }
+                if h > high {
+                    parts.push((high + 1, h));
+                }
+                parts
+            })
+            .collect();
+    }
+    rest
+}
+
+/// Pushes `lhs op rhs` as a `ty` and returns its result.
+fn push_binary(op: BinaryOp, ty: IrType, lhs: Operand, rhs: Operand, builder: &mut FunctionBuilder) -> Operand {
+    let dest = builder.function.new_value(ty);
+    builder.push(Instruction::Binary { dest, ty, op, lhs, rhs });
+    Operand::Value(dest)
+}
+
+/// Whether `ty` is laid out in memory from fields or elements: structs,
+/// enums and arrays.
+fn is_aggregate(ty: &Type) -> bool {
+    matches!(ty.kind(), TypeKind::Struct | TypeKind::Enum | TypeKind::Array)
+}
+
+/// The name of the type named `name` once the type parameters `params` in
+/// it are replaced by what `bindings` binds them to, or None if it has one
+/// that is not bound yet.
+fn substituted(name: &str, params: &[String], bindings: &HashMap<String, String>) -> Option<String> {
+    if let Some(pointee) = name.strip_prefix('*') {
+        return Some(format!("*{}", substituted(pointee, params, bindings)?));
+    }
+    if let Some(element) = name.strip_prefix("[]") {
+        return Some(format!("[]{}", substituted(element, params, bindings)?));
+    }
+    if let Some((element, length)) = name.strip_prefix('[').and_then(|name| name.strip_suffix(']')?.rsplit_once("; ")) {
+        return Some(format!("[{}; {}]", substituted(element, params, bindings)?, length));
+    }
+    if params.iter().any(|param| param == name) {
+        return bindings.get(name).cloned();
+    }
+    Some(name.to_string())
+}
+
+/// Matches the type named `actual` against the type named `pattern`,
+/// binding the type parameters `params` in the pattern to the parts of
+/// `actual` they stand for. False if they do not match.
+fn unify(pattern: &str, actual: &str, params: &[String], bindings: &mut HashMap<String, String>) -> bool {
+    if params.iter().any(|param| param == pattern) {
+        return match bindings.get(pattern) {
+            Some(bound) => bound == actual,
+            None => {
+                bindings.insert(pattern.to_string(), actual.to_string());
+                true
+            }
+        };
+    }
+    let array = |name: &str| {
+        let (element, length) = name.strip_prefix('[')?.strip_suffix(']')?.rsplit_once("; ")?;
+        Some((element.to_string(), length.to_string()))
+    };
+    if let (Some(pattern), Some(actual)) = (pattern.strip_prefix('*'), actual.strip_prefix('*')) {
+        return unify(pattern, actual, params, bindings);
+    }
+    if let (Some(pattern), Some(actual)) = (pattern.strip_prefix("[]"), actual.strip_prefix("[]")) {
+        return unify(pattern, actual, params, bindings);
+    }
+    if let (Some((pattern, length)), Some((actual, other))) = (array(pattern), array(actual)) {
+        return length == other && unify(&pattern, &actual, params, bindings);
+    }
+    pattern == actual
+}
+
+/// How to turn a `from` into a `to` where one is expected, if an integer
+/// can be cast to the other.
+fn conversion(from: &Type, to: &Type) -> Option<String> {
+    (from.is_integer() && to.is_integer()).then(|| format!("Convert it with `as {}`", to))
+}
+
+/// Whether `expr` is made of integer literals only.
+fn is_literal(expr: &Expression) -> bool {
+    match &expr.content {
+        ExpressionContent::IntLiteral(_) => true,
+        ExpressionContent::Binary(_, lhs, rhs) => is_literal(lhs) && is_literal(rhs),
+        _ => false,
+    }
+}
+
+/// The variable an element or field belongs to, if it is not behind a pointer.
+fn root<'e>(expr: &'e Expression) -> Option<&'e str> {
+    match &expr.content {
+        ExpressionContent::Variable(name) => Some(name),
+        ExpressionContent::Index(value, _) | ExpressionContent::FieldAccess(value, _) => root(value),
+        _ => None,
+    }
+}
+
+/// The operand names referred to as `{name}` in a line of assembly.
+fn placeholders<'l>(line: &'l str, node: &AstNode, error_handler: &ErrorHandler) -> Vec<&'l str> {
+    let mut names: Vec<&'l str> = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            error_handler.err(
+                node.line,
+                node.column,
+                format!("Unclosed '{{' in asm line {:?}", line),
+                None,
+            );
+        };
+        names.push(&rest[start + 1..start + end]);
+        rest = &rest[start + end + 1..];
+    }
+    names
+}
+
+/// The assembly label of the static named `name`.
+fn static_symbol(name: &str) -> String {
+    mangle(&[name])
+}
+
+/// String literals get names that cannot clash with Sabbah identifiers.
+fn string_name(index: usize) -> String {
+    format!("str.{}", index)
+}
@@ -0,0 +1,624 @@
+//! The intermediate representation sitting between the AST and the backend.
+//!
+//! A program is a list of functions; a function is a list of basic blocks.
+//! Every block holds straight-line three-address instructions and ends in
+//! exactly one terminator, which is the only place control flow happens.
+//! Instructions write to virtual registers (`Value`s), each assigned once;
+//! where control flow merges, a phi picks the value from the block control
+//! came from. Globals, and locals whose address is taken, live in memory
+//! and are only accessed through loads and stores; a local's memory is a
+//! stack slot, reserved for the whole call by an `alloca`.
+
+pub mod lower;
+pub mod opt;
+use std::collections::HashSet;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IrType {
+    I8,
+    I16,
+    I32,
+    I64,
+}
+
+impl IrType {
+    pub fn bits(&self) -> u32 {
+        match self {
+            Self::I8 => 8,
+            Self::I16 => 16,
+            Self::I32 => 32,
+            Self::I64 => 64,
+        }
+    }
+    /// Truncates `value` to this type's width.
+    pub fn wrap(&self, value: u64) -> u64 {
+        match self {
+            Self::I64 => value,
+            _ => value & ((1u64 << self.bits()) - 1),
+        }
+    }
+    /// Reads `value` as a two's complement number of this type's width.
+    pub fn sign_extend(&self, value: u64) -> i64 {
+        let unused = 64 - self.bits();
+        ((value << unused) as i64) >> unused
+    }
+    /// The IR type for a value occupying `size` bytes.
+    pub fn from_size(size: u64) -> Option<IrType> {
+        match size {
+            1 => Some(Self::I8),
+            2 => Some(Self::I16),
+            4 => Some(Self::I32),
+            8 => Some(Self::I64),
+            _ => None,
+        }
+    }
+    /// Whether `lhs op rhs`, where `op` is one of the overflow operations,
+    /// does not fit this type.
+    pub fn overflows(&self, op: BinaryOp, lhs: u64, rhs: u64) -> bool {
+        let signed = matches!(op, BinaryOp::SAddOverflow | BinaryOp::SSubOverflow | BinaryOp::SMulOverflow);
+        let (lhs, rhs, min, max) = if signed {
+            let max = (1i128 << (self.bits() - 1)) - 1;
+            (self.sign_extend(lhs) as i128, self.sign_extend(rhs) as i128, -max - 1, max)
+        } else {
+            (lhs as i128, rhs as i128, 0, (1i128 << self.bits()) - 1)
+        };
+        let result = match op {
+            BinaryOp::UAddOverflow | BinaryOp::SAddOverflow => lhs.checked_add(rhs),
+            BinaryOp::USubOverflow | BinaryOp::SSubOverflow => lhs.checked_sub(rhs),
+            _ => lhs.checked_mul(rhs),
+        };
+        !result.is_some_and(|result| (min..=max).contains(&result))
+    }
+}
+
+/// A virtual register.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Value(pub u32);
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BlockId(pub u32);
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Operand {
+    Value(Value),
+    Const(u64),
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    UDiv,
+    URem,
+    SDiv,
+    SRem,
+    Shl,
+    LShr,
+    And,
+    Or,
+    /// 1 if `lhs < rhs` as unsigned integers, else 0
+    ULt,
+    /// 1 if `lhs <= rhs` as unsigned integers, else 0
+    ULe,
+    /// 1 if `lhs + rhs` does not fit the type as an unsigned integer, else 0
+    UAddOverflow,
+    /// 1 if `lhs + rhs` does not fit the type as a signed integer, else 0
+    SAddOverflow,
+    /// 1 if `lhs - rhs` does not fit the type as an unsigned integer, else 0
+    USubOverflow,
+    /// 1 if `lhs - rhs` does not fit the type as a signed integer, else 0
+    SSubOverflow,
+    /// 1 if `lhs * rhs` does not fit the type as an unsigned integer, else 0
+    UMulOverflow,
+    /// 1 if `lhs * rhs` does not fit the type as a signed integer, else 0
+    SMulOverflow,
+}
+
+impl BinaryOp {
+    /// The operation that says whether this one, if it is an addition,
+    /// subtraction or multiplication, overflows a `signed` or unsigned type.
+    pub fn overflow(&self, signed: bool) -> Option<BinaryOp> {
+        match (self, signed) {
+            (Self::Add, false) => Some(Self::UAddOverflow),
+            (Self::Add, true) => Some(Self::SAddOverflow),
+            (Self::Sub, false) => Some(Self::USubOverflow),
+            (Self::Sub, true) => Some(Self::SSubOverflow),
+            (Self::Mul, false) => Some(Self::UMulOverflow),
+            (Self::Mul, true) => Some(Self::SMulOverflow),
+            _ => None,
+        }
+    }
+}
+
+/// How a cast changes the width of a value.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CastOp {
+    /// Fills the new high bits with zeros
+    ZExt,
+    /// Fills the new high bits with copies of the sign bit
+    SExt,
+    /// Drops the high bits
+    Trunc,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Instruction {
+    Copy { dest: Value, ty: IrType, src: Operand },
+    Binary { dest: Value, ty: IrType, op: BinaryOp, lhs: Operand, rhs: Operand },
+    /// `src`, a `from`, as a `to`
+    Cast { dest: Value, op: CastOp, from: IrType, to: IrType, src: Operand },
+    /// `dests` has one value per scalar the callee returns, which may be
+    /// more than two if it returns them in memory
+    Call { dests: Vec<Value>, function: String, args: Vec<Operand> },
+    Load { dest: Value, ty: IrType, global: String },
+    Store { ty: IrType, global: String, src: Operand },
+    /// The address of a global, as an i64
+    Address { dest: Value, global: String },
+    /// The address of `size` bytes in the function's stack frame, aligned to `align`
+    Alloca { dest: Value, size: u64, align: u64 },
+    /// Reads memory at an address
+    LoadAt { dest: Value, ty: IrType, address: Operand },
+    /// Writes memory at an address
+    StoreAt { ty: IrType, address: Operand, src: Operand },
+    /// `args` holds the syscall number followed by up to six arguments
+    Syscall { dest: Value, ty: IrType, args: Vec<Operand> },
+    /// Inline assembly. `{name}` in the template refers to the input or
+    /// output bound to `name`; outputs are defined once the block has run.
+    Asm { template: Vec<String>, inputs: Vec<(String, Operand)>, outputs: Vec<(String, Value)> },
+    /// The operand paired with the block control came from. Phis come first
+    /// in their block, and every block they name ends in a jump to it.
+    Phi { dest: Value, ty: IrType, incoming: Vec<(BlockId, Operand)> },
+}
+
+impl Instruction {
+    pub fn dests(&self) -> Vec<Value> {
+        match self {
+            Self::Copy { dest, .. }
+            | Self::Binary { dest, .. }
+            | Self::Cast { dest, .. }
+            | Self::Load { dest, .. }
+            | Self::Address { dest, .. }
+            | Self::Alloca { dest, .. }
+            | Self::LoadAt { dest, .. }
+            | Self::Syscall { dest, .. }
+            | Self::Phi { dest, .. } => vec![*dest],
+            Self::Call { dests, .. } => dests.clone(),
+            Self::Asm { outputs, .. } => outputs.iter().map(|(_, value)| *value).collect(),
+            Self::Store { .. } | Self::StoreAt { .. } => Vec::new(),
+        }
+    }
+    pub fn dests_mut(&mut self) -> Vec<&mut Value> {
+        match self {
+            Self::Copy { dest, .. }
+            | Self::Binary { dest, .. }
+            | Self::Cast { dest, .. }
+            | Self::Load { dest, .. }
+            | Self::Address { dest, .. }
+            | Self::Alloca { dest, .. }
+            | Self::LoadAt { dest, .. }
+            | Self::Syscall { dest, .. }
+            | Self::Phi { dest, .. } => vec![dest],
+            Self::Call { dests, .. } => dests.iter_mut().collect(),
+            Self::Asm { outputs, .. } => outputs.iter_mut().map(|(_, value)| value).collect(),
+            Self::Store { .. } | Self::StoreAt { .. } => Vec::new(),
+        }
+    }
+    /// Whether the instruction must be kept even if its results are unused.
+    pub fn has_side_effects(&self) -> bool {
+        matches!(
+            self,
+            Self::Call { .. } | Self::Store { .. } | Self::StoreAt { .. } | Self::Syscall { .. } | Self::Asm { .. }
+        )
+    }
+    pub fn operands(&self) -> Vec<Operand> {
+        match self {
+            Self::Copy { src, .. } | Self::Cast { src, .. } | Self::Store { src, .. } => vec![*src],
+            Self::Binary { lhs, rhs, .. } => vec![*lhs, *rhs],
+            Self::Call { args, .. } | Self::Syscall { args, .. } => args.clone(),
+            Self::Asm { inputs, .. } => inputs.iter().map(|(_, operand)| *operand).collect(),
+            Self::Phi { incoming, .. } => incoming.iter().map(|(_, operand)| *operand).collect(),
+            Self::LoadAt { address, .. } => vec![*address],
+            Self::StoreAt { address, src, .. } => vec![*address, *src],
+            Self::Load { .. } | Self::Address { .. } | Self::Alloca { .. } => Vec::new(),
+        }
+    }
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Self::Copy { src, .. } | Self::Cast { src, .. } | Self::Store { src, .. } => vec![src],
+            Self::Binary { lhs, rhs, .. } => vec![lhs, rhs],
+            Self::Call { args, .. } | Self::Syscall { args, .. } => args.iter_mut().collect(),
+            Self::Asm { inputs, .. } => inputs.iter_mut().map(|(_, operand)| operand).collect(),
+            Self::Phi { incoming, .. } => incoming.iter_mut().map(|(_, operand)| operand).collect(),
+            Self::LoadAt { address, .. } => vec![address],
+            Self::StoreAt { address, src, .. } => vec![address, src],
+            Self::Load { .. } | Self::Address { .. } | Self::Alloca { .. } => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Terminator {
+    /// One operand per scalar the function returns. A function returning in
+    /// memory first gets the address to write them to, its hidden parameter.
+    Return(Vec<Operand>),
+    Jump(BlockId),
+    Branch { condition: Operand, then_block: BlockId, else_block: BlockId },
+    /// Jumps to the block of the case whose inclusive range holds `value`,
+    /// or to `default` if none does. The ranges do not overlap.
+    Switch { value: Operand, cases: Vec<(u64, u64, BlockId)>, default: BlockId },
+    /// Never reached, such as after a call to a function that does not return
+    Unreachable,
+}
+
+impl Terminator {
+    pub fn operands(&self) -> Vec<Operand> {
+        match self {
+            Self::Return(operands) => operands.clone(),
+            Self::Branch { condition, .. } | Self::Switch { value: condition, .. } => vec![*condition],
+            Self::Jump(_) | Self::Unreachable => Vec::new(),
+        }
+    }
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Self::Return(operands) => operands.iter_mut().collect(),
+            Self::Branch { condition, .. } | Self::Switch { value: condition, .. } => vec![condition],
+            Self::Jump(_) | Self::Unreachable => Vec::new(),
+        }
+    }
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Self::Return(_) | Self::Unreachable => Vec::new(),
+            Self::Jump(target) => vec![*target],
+            Self::Branch { then_block, else_block, .. } => vec![*then_block, *else_block],
+            Self::Switch { cases, default, .. } => {
+                cases.iter().map(|(_, _, target)| *target).chain(std::iter::once(*default)).collect()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BasicBlock {
+    pub id: BlockId,
+    pub instructions: Vec<Instruction>,
+    pub terminator: Terminator,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Function {
+    pub name: String,
+    /// Visible outside the object file, to be called from C
+    pub exported: bool,
+    /// Defined on entry, in argument order
+    pub params: Vec<Value>,
+    /// How many of the last params are passed on the stack rather than in registers
+    pub stack_params: usize,
+    /// Scalars returned in %rax and %rdx; empty if nothing is returned.
+    /// More than two are returned in memory, see `returns_in_memory`.
+    pub return_types: Vec<IrType>,
+    /// Indexed by `Value`
+    pub value_types: Vec<IrType>,
+    /// The entry block comes first
+    pub blocks: Vec<BasicBlock>,
+}
+
+impl Function {
+    pub fn new(name: String, return_types: Vec<IrType>) -> Self {
+        Self {
+            name,
+            exported: false,
+            params: Vec::new(),
+            stack_params: 0,
+            return_types,
+            value_types: Vec::new(),
+            blocks: Vec::new(),
+        }
+    }
+    /// Whether the caller passes the address to return the result at as a
+    /// hidden first parameter, because it does not fit in two registers.
+    pub fn returns_in_memory(&self) -> bool {
+        self.return_types.len() > 2
+    }
+    pub fn new_value(&mut self, ty: IrType) -> Value {
+        self.value_types.push(ty);
+        Value(self.value_types.len() as u32 - 1)
+    }
+    /// The blocks control can reach from the entry block.
+    pub fn reachable(&self) -> HashSet<BlockId> {
+        let mut reachable: HashSet<BlockId> = HashSet::new();
+        let mut worklist: Vec<BlockId> = vec![self.blocks[0].id];
+        while let Some(id) = worklist.pop() {
+            if reachable.insert(id) {
+                let block = self.blocks.iter().find(|block| block.id == id).unwrap();
+                worklist.extend(block.terminator.successors());
+            }
+        }
+        reachable
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GlobalData {
+    Scalar(IrType, u64),
+    /// Raw bytes, such as the contents of a string literal
+    Bytes(Vec<u8>),
+    /// The elements of an array of integers or pointers, in order
+    Array(IrType, Vec<u64>),
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Global {
+    pub name: String,
+    /// Immutable globals are read-only
+    pub mutable: bool,
+    pub data: GlobalData,
+}
+
+/// A function defined outside the program, such as in the C library.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ExternFunction {
+    pub name: String,
+    pub params: Vec<IrType>,
+    /// Like `Function::stack_params`
+    pub stack_params: usize,
+    pub return_types: Vec<IrType>,
+}
+
+impl ExternFunction {
+    pub fn returns_in_memory(&self) -> bool {
+        self.return_types.len() > 2
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Program {
+    pub externs: Vec<ExternFunction>,
+    pub globals: Vec<Global>,
+    pub functions: Vec<Function>,
+}
+
+impl fmt::Display for IrType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::I8 => write!(f, "i8"),
+            Self::I16 => write!(f, "i16"),
+            Self::I32 => write!(f, "i32"),
+            Self::I64 => write!(f, "i64"),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "%{}", self.0)
+    }
+}
+
+impl fmt::Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bb{}", self.0)
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(value) => write!(f, "{}", value),
+            Self::Const(constant) => write!(f, "{}", constant),
+        }
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Add => write!(f, "add"),
+            Self::Sub => write!(f, "sub"),
+            Self::Mul => write!(f, "mul"),
+            Self::UDiv => write!(f, "udiv"),
+            Self::URem => write!(f, "urem"),
+            Self::SDiv => write!(f, "sdiv"),
+            Self::SRem => write!(f, "srem"),
+            Self::Shl => write!(f, "shl"),
+            Self::LShr => write!(f, "lshr"),
+            Self::And => write!(f, "and"),
+            Self::Or => write!(f, "or"),
+            Self::ULt => write!(f, "ult"),
+            Self::ULe => write!(f, "ule"),
+            Self::UAddOverflow => write!(f, "uaddo"),
+            Self::SAddOverflow => write!(f, "saddo"),
+            Self::USubOverflow => write!(f, "usubo"),
+            Self::SSubOverflow => write!(f, "ssubo"),
+            Self::UMulOverflow => write!(f, "umulo"),
+            Self::SMulOverflow => write!(f, "smulo"),
+        }
+    }
+}
+
+impl fmt::Display for CastOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZExt => write!(f, "zext"),
+            Self::SExt => write!(f, "sext"),
+            Self::Trunc => write!(f, "trunc"),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Copy { dest, ty, src } => write!(f, "{} = {} {}", dest, ty, src),
+            Self::Binary { dest, ty, op, lhs, rhs } => {
+                write!(f, "{} = {} {} {}, {}", dest, op, ty, lhs, rhs)
+            }
+            Self::Cast { dest, op, from, to, src } => write!(f, "{} = {} {} {} to {}", dest, op, from, src, to),
+            Self::Call { dests, function, args } => {
+                for (i, dest) in dests.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", dest)?;
+                }
+                if !dests.is_empty() {
+                    write!(f, " = ")?;
+                }
+                write!(f, "call {}(", function)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+            Self::Load { dest, ty, global } => write!(f, "{} = load {} @{}", dest, ty, global),
+            Self::Store { ty, global, src } => write!(f, "store {} {}, @{}", ty, src, global),
+            Self::Address { dest, global } => write!(f, "{} = addr @{}", dest, global),
+            Self::Alloca { dest, size, align } => write!(f, "{} = alloca {}, align {}", dest, size, align),
+            Self::LoadAt { dest, ty, address } => write!(f, "{} = load {} [{}]", dest, ty, address),
+            Self::StoreAt { ty, address, src } => write!(f, "store {} {}, [{}]", ty, src, address),
+            Self::Syscall { dest, ty, args } => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "{} = syscall {} {}", dest, ty, args.join(", "))
+            }
+            Self::Asm { template, inputs, outputs } => {
+                let outputs: Vec<String> =
+                    outputs.iter().map(|(name, value)| format!("{} = {}", name, value)).collect();
+                let inputs: Vec<String> = inputs.iter().map(|(name, operand)| format!("{} = {}", name, operand)).collect();
+                let template: Vec<String> = template.iter().map(|line| format!("{:?}", line)).collect();
+                write!(f, "asm [{}] in({}) out({})", template.join(", "), inputs.join(", "), outputs.join(", "))
+            }
+            Self::Phi { dest, ty, incoming } => {
+                let incoming: Vec<String> =
+                    incoming.iter().map(|(block, operand)| format!("{}: {}", block, operand)).collect();
+                write!(f, "{} = phi {} [{}]", dest, ty, incoming.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Terminator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Return(operands) => {
+                write!(f, "ret")?;
+                for (i, operand) in operands.iter().enumerate() {
+                    write!(f, "{}{}", if i > 0 { ", " } else { " " }, operand)?;
+                }
+                Ok(())
+            }
+            Self::Jump(target) => write!(f, "jmp {}", target),
+            Self::Branch { condition, then_block, else_block } => {
+                write!(f, "br {}, {}, {}", condition, then_block, else_block)
+            }
+            Self::Switch { value, cases, default } => {
+                let cases: Vec<String> = cases
+                    .iter()
+                    .map(|(low, high, target)| {
+                        if low == high {
+                            format!("{} => {}", low, target)
+                        } else {
+                            format!("{}..={} => {}", low, high, target)
+                        }
+                    })
+                    .collect();
+                write!(f, "switch {} [{}], {}", value, cases.join(", "), default)
+            }
+            Self::Unreachable => write!(f, "unreachable"),
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.exported {
+            write!(f, "export ")?;
+        }
+        write!(f, "fn {}(", self.name)?;
+        let stack = self.params.len() - self.stack_params;
+        for (i, param) in self.params.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            if i >= stack {
+                write!(f, "stack ")?;
+            }
+            write!(f, "{}: {}", param, self.value_types[param.0 as usize])?;
+        }
+        match self.return_types.as_slice() {
+            [] => writeln!(f, ") {{")?,
+            [ty] => writeln!(f, ") -> {} {{", ty)?,
+            types => {
+                let types: Vec<String> = types.iter().map(|ty| ty.to_string()).collect();
+                writeln!(f, ") -> ({}) {{", types.join(", "))?
+            }
+        }
+        for block in &self.blocks {
+            writeln!(f, "{}:", block.id)?;
+            for instruction in &block.instructions {
+                writeln!(f, "    {}", instruction)?;
+            }
+            writeln!(f, "    {}", block.terminator)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl fmt::Display for Global {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.mutable { "global" } else { "constant" };
+        match &self.data {
+            GlobalData::Scalar(ty, value) => write!(f, "@{} = {} {} {}", self.name, kind, ty, value),
+            GlobalData::Bytes(bytes) => write!(f, "@{} = {} bytes \"{}\"", self.name, kind, bytes.escape_ascii()),
+            GlobalData::Array(ty, values) => {
+                let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+                write!(f, "@{} = {} [{} x {}] [{}]", self.name, kind, values.len(), ty, values.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for ExternFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stack = self.params.len() - self.stack_params;
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| if i >= stack { format!("stack {}", ty) } else { ty.to_string() })
+            .collect();
+        write!(f, "extern fn {}({})", self.name, params.join(", "))?;
+        match self.return_types.as_slice() {
+            [] => Ok(()),
+            [ty] => write!(f, " -> {}", ty),
+            types => {
+                let types: Vec<String> = types.iter().map(|ty| ty.to_string()).collect();
+                write!(f, " -> ({})", types.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for external in &self.externs {
+            writeln!(f, "{}", external)?;
+        }
+        for global in &self.globals {
+            writeln!(f, "{}", global)?;
+        }
+        if (!self.externs.is_empty() || !self.globals.is_empty()) && !self.functions.is_empty() {
+            writeln!(f)?;
+        }
+        for (i, function) in self.functions.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{}", function)?;
+        }
+        Ok(())
+    }
+}
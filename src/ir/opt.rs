@@ -0,0 +1,373 @@
+//! IR optimizations, selected by `-O<level>`.
+//!
+//! -O0 leaves the IR alone. -O1 folds and propagates constants and copies
+//! and removes dead code (unreachable blocks, such as statements after a
+//! `return` or the other arms of a match on a constant, and unused
+//! results). -O2 additionally inlines small functions, eliminates common
+//! subexpressions and reduces multiplications, divisions and remainders by
+//! powers of two to shifts and masks.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ir::*;
+
+/// Callees with at most this many instructions (and a single block) get inlined.
+const INLINE_LIMIT: usize = 8;
+
+pub fn optimize(program: &mut Program, level: u8) {
+    if level >= 2 {
+        inline(program);
+    }
+    if level >= 1 {
+        for function in &mut program.functions {
+            loop {
+                let mut changed = propagate(function);
+                if level >= 2 {
+                    changed |= eliminate_common_subexpressions(function);
+                }
+                changed |= eliminate_dead_code(function);
+                if !changed {
+                    break;
+                }
+            }
+            if level >= 2 {
+                reduce_strength(function);
+            }
+        }
+    }
+}
+
+/// Replaces calls to small single-block functions with the callee's body.
+fn inline(program: &mut Program) {
+    let candidates: HashMap<String, Function> = program
+        .functions
+        .iter()
+        .filter(|function| {
+            function.blocks.len() == 1
+                && function.blocks[0].instructions.len() <= INLINE_LIMIT
+                && matches!(function.blocks[0].terminator, Terminator::Return(_))
+                // The caller does not pass the hidden result address
+                && !function.returns_in_memory()
+                // Labels in inline assembly must stay unique
+                && !function.blocks[0].instructions.iter().any(|instruction| matches!(instruction, Instruction::Asm { .. }))
+        })
+        .map(|function| (function.name.clone(), function.clone()))
+        .collect();
+
+    for function in &mut program.functions {
+        let mut blocks = std::mem::take(&mut function.blocks);
+        for block in &mut blocks {
+            let mut instructions: Vec<Instruction> = Vec::new();
+            for instruction in block.instructions.drain(..) {
+                let Instruction::Call { dests, function: name, args } = &instruction else {
+                    instructions.push(instruction);
+                    continue;
+                };
+                let Some(callee) = candidates.get(name).filter(|_| *name != function.name) else {
+                    instructions.push(instruction);
+                    continue;
+                };
+                // Callee values map to the arguments or to fresh caller values
+                let mut renamed: HashMap<Value, Operand> = HashMap::new();
+                for (param, arg) in callee.params.iter().zip(args) {
+                    renamed.insert(*param, *arg);
+                }
+                for inlined in &callee.blocks[0].instructions {
+                    let mut inlined = inlined.clone();
+                    for operand in inlined.operands_mut() {
+                        if let Operand::Value(value) = operand {
+                            *operand = renamed[value];
+                        }
+                    }
+                    for dest in inlined.dests_mut() {
+                        let fresh = function.new_value(callee.value_types[dest.0 as usize]);
+                        renamed.insert(*dest, Operand::Value(fresh));
+                        *dest = fresh;
+                    }
+                    instructions.push(inlined);
+                }
+                if let Terminator::Return(results) = &callee.blocks[0].terminator {
+                    for (dest, result) in dests.iter().zip(results) {
+                        let src = match result {
+                            Operand::Value(value) => renamed[value],
+                            constant => *constant,
+                        };
+                        let ty = function.value_types[dest.0 as usize];
+                        instructions.push(Instruction::Copy { dest: *dest, ty, src });
+                    }
+                }
+            }
+            block.instructions = instructions;
+        }
+        function.blocks = blocks;
+    }
+}
+
+/// Constant and copy propagation with constant folding. Returns whether
+/// anything changed.
+fn propagate(function: &mut Function) -> bool {
+    let mut known: HashMap<Value, Operand> = HashMap::new();
+    let mut changed = false;
+    let resolve = |known: &HashMap<Value, Operand>, operand: &mut Operand, changed: &mut bool| {
+        while let Operand::Value(value) = operand {
+            match known.get(value) {
+                Some(replacement) => {
+                    *operand = *replacement;
+                    *changed = true;
+                }
+                None => break,
+            }
+        }
+    };
+    for block in &mut function.blocks {
+        for instruction in &mut block.instructions {
+            for operand in instruction.operands_mut() {
+                resolve(&known, operand, &mut changed);
+            }
+            match instruction {
+                Instruction::Copy { dest, ty, src } => {
+                    let src = match src {
+                        Operand::Const(constant) => Operand::Const(ty.wrap(*constant)),
+                        value => *value,
+                    };
+                    known.insert(*dest, src);
+                }
+                Instruction::Binary { dest, ty, op, lhs, rhs } => {
+                    if let Some(result) = fold(*op, *ty, *lhs, *rhs) {
+                        known.insert(*dest, result);
+                        *instruction = Instruction::Copy { dest: *dest, ty: *ty, src: result };
+                        changed = true;
+                    }
+                }
+                Instruction::Cast { dest, op, from, to, src: Operand::Const(constant) } => {
+                    let result = Operand::Const(cast(*op, *from, *to, *constant));
+                    known.insert(*dest, result);
+                    *instruction = Instruction::Copy { dest: *dest, ty: *to, src: result };
+                    changed = true;
+                }
+                Instruction::Phi { dest, ty, incoming } => {
+                    // The same operand whichever way control came
+                    let first = incoming.first().map(|(_, operand)| *operand);
+                    if let Some(src) = first.filter(|first| incoming.iter().all(|(_, other)| other == first)) {
+                        known.insert(*dest, src);
+                        *instruction = Instruction::Copy { dest: *dest, ty: *ty, src };
+                        changed = true;
+                    }
+                }
+                Instruction::Call { .. }
+                | Instruction::Cast { .. }
+                | Instruction::Load { .. }
+                | Instruction::Store { .. }
+                | Instruction::Address { .. }
+                | Instruction::Alloca { .. }
+                | Instruction::LoadAt { .. }
+                | Instruction::StoreAt { .. }
+                | Instruction::Syscall { .. }
+                | Instruction::Asm { .. } => {}
+            }
+        }
+        for operand in block.terminator.operands_mut() {
+            resolve(&known, operand, &mut changed);
+        }
+        if let Terminator::Branch { condition: Operand::Const(condition), then_block, else_block } = block.terminator {
+            let target = if condition != 0 { then_block } else { else_block };
+            block.terminator = Terminator::Jump(target);
+            changed = true;
+        }
+        if let Terminator::Switch { value: Operand::Const(value), cases, default } = &block.terminator {
+            let case = cases.iter().find(|(low, high, _)| (*low..=*high).contains(value));
+            block.terminator = Terminator::Jump(case.map_or(*default, |(_, _, target)| *target));
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Evaluates a cast of the constant `value`.
+fn cast(op: CastOp, from: IrType, to: IrType, value: u64) -> u64 {
+    match op {
+        CastOp::ZExt => from.wrap(value),
+        CastOp::SExt => to.wrap(from.sign_extend(value) as u64),
+        CastOp::Trunc => to.wrap(value),
+    }
+}
+
+/// Evaluates `lhs op rhs` if it is known at compile time or simplifies to one operand.
+fn fold(op: BinaryOp, ty: IrType, lhs: Operand, rhs: Operand) -> Option<Operand> {
+    if let (Operand::Const(lhs), Operand::Const(rhs)) = (lhs, rhs) {
+        let result = match op {
+            BinaryOp::Add => lhs.wrapping_add(rhs),
+            BinaryOp::Sub => lhs.wrapping_sub(rhs),
+            BinaryOp::Mul => lhs.wrapping_mul(rhs),
+            // Division by zero is left for the program to trap on at runtime
+            BinaryOp::UDiv => lhs.checked_div(rhs)?,
+            BinaryOp::URem => lhs.checked_rem(rhs)?,
+            // So is dividing the most negative int64 by -1, which idiv traps on too
+            BinaryOp::SDiv => ty.sign_extend(lhs).checked_div(ty.sign_extend(rhs))? as u64,
+            BinaryOp::SRem => ty.sign_extend(lhs).checked_rem(ty.sign_extend(rhs))? as u64,
+            BinaryOp::Shl => lhs.checked_shl(rhs as u32).unwrap_or(0),
+            BinaryOp::LShr => lhs.checked_shr(rhs as u32).unwrap_or(0),
+            BinaryOp::And => lhs & rhs,
+            BinaryOp::Or => lhs | rhs,
+            BinaryOp::ULt => (lhs < rhs) as u64,
+            BinaryOp::ULe => (lhs <= rhs) as u64,
+            BinaryOp::UAddOverflow
+            | BinaryOp::SAddOverflow
+            | BinaryOp::USubOverflow
+            | BinaryOp::SSubOverflow
+            | BinaryOp::UMulOverflow
+            | BinaryOp::SMulOverflow => ty.overflows(op, lhs, rhs) as u64,
+        };
+        return Some(Operand::Const(ty.wrap(result)));
+    }
+    match (op, lhs, rhs) {
+        (BinaryOp::Add, x, Operand::Const(0)) | (BinaryOp::Add, Operand::Const(0), x) => Some(x),
+        (BinaryOp::Sub, x, Operand::Const(0)) => Some(x),
+        (BinaryOp::Mul, x, Operand::Const(1)) | (BinaryOp::Mul, Operand::Const(1), x) => Some(x),
+        (BinaryOp::Mul, _, Operand::Const(0)) | (BinaryOp::Mul, Operand::Const(0), _) => Some(Operand::Const(0)),
+        (BinaryOp::UDiv | BinaryOp::SDiv, x, Operand::Const(1)) => Some(x),
+        (BinaryOp::URem | BinaryOp::SRem, _, Operand::Const(1)) => Some(Operand::Const(0)),
+        (BinaryOp::Shl | BinaryOp::LShr, x, Operand::Const(0)) => Some(x),
+        (BinaryOp::Or, x, Operand::Const(0)) | (BinaryOp::Or, Operand::Const(0), x) => Some(x),
+        // Adding or subtracting 0 and multiplying by 0 or 1 never overflow
+        (BinaryOp::UAddOverflow | BinaryOp::SAddOverflow, _, Operand::Const(0))
+        | (BinaryOp::UAddOverflow | BinaryOp::SAddOverflow, Operand::Const(0), _)
+        | (BinaryOp::USubOverflow | BinaryOp::SSubOverflow, _, Operand::Const(0))
+        | (BinaryOp::UMulOverflow | BinaryOp::SMulOverflow, _, Operand::Const(0 | 1))
+        | (BinaryOp::UMulOverflow | BinaryOp::SMulOverflow, Operand::Const(0 | 1), _) => Some(Operand::Const(0)),
+        _ => None,
+    }
+}
+
+/// Removes unreachable blocks and instructions whose results are never used.
+fn eliminate_dead_code(function: &mut Function) -> bool {
+    let mut changed = false;
+
+    let reachable = function.reachable();
+    let block_count = function.blocks.len();
+    function.blocks.retain(|block| reachable.contains(&block.id));
+    changed |= function.blocks.len() != block_count;
+
+    // Phis only keep the blocks that still jump to them
+    let jumps: HashSet<(BlockId, BlockId)> = function
+        .blocks
+        .iter()
+        .flat_map(|block| block.terminator.successors().into_iter().map(move |target| (block.id, target)))
+        .collect();
+    for block in &mut function.blocks {
+        for instruction in &mut block.instructions {
+            if let Instruction::Phi { incoming, .. } = instruction {
+                let count = incoming.len();
+                incoming.retain(|(from, _)| jumps.contains(&(*from, block.id)));
+                changed |= incoming.len() != count;
+            }
+        }
+    }
+
+    let mut used: HashSet<Value> = HashSet::new();
+    for block in &function.blocks {
+        let operands = block
+            .instructions
+            .iter()
+            .flat_map(|instruction| instruction.operands())
+            .chain(block.terminator.operands());
+        for operand in operands {
+            if let Operand::Value(value) = operand {
+                used.insert(value);
+            }
+        }
+    }
+    for block in &mut function.blocks {
+        let instruction_count = block.instructions.len();
+        block.instructions.retain(|instruction| {
+            instruction.has_side_effects() || instruction.dests().iter().any(|dest| used.contains(dest))
+        });
+        changed |= block.instructions.len() != instruction_count;
+        for instruction in &mut block.instructions {
+            if let Instruction::Call { dests, .. } = instruction
+                && !dests.is_empty()
+                && dests.iter().all(|value| !used.contains(value))
+            {
+                dests.clear();
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+/// Local value numbering: within a block, an operation repeated on the same
+/// operands reuses the earlier result.
+fn eliminate_common_subexpressions(function: &mut Function) -> bool {
+    let mut changed = false;
+    for block in &mut function.blocks {
+        let mut seen: HashMap<(BinaryOp, IrType, Operand, Operand), Value> = HashMap::new();
+        for instruction in &mut block.instructions {
+            if let Instruction::Binary { dest, ty, op, lhs, rhs } = *instruction {
+                let (lhs, rhs) = match op {
+                    BinaryOp::Add
+                    | BinaryOp::Mul
+                    | BinaryOp::And
+                    | BinaryOp::Or
+                    | BinaryOp::UAddOverflow
+                    | BinaryOp::SAddOverflow
+                    | BinaryOp::UMulOverflow
+                    | BinaryOp::SMulOverflow
+                        if rhs < lhs =>
+                    {
+                        (rhs, lhs)
+                    }
+                    _ => (lhs, rhs),
+                };
+                match seen.get(&(op, ty, lhs, rhs)) {
+                    Some(previous) => {
+                        *instruction = Instruction::Copy { dest, ty, src: Operand::Value(*previous) };
+                        changed = true;
+                    }
+                    None => {
+                        seen.insert((op, ty, lhs, rhs), dest);
+                    }
+                }
+            }
+        }
+    }
+    changed
+}
+
+/// Turns multiplication, division and remainder by powers of two into
+/// shifts and masks.
+fn reduce_strength(function: &mut Function) {
+    for block in &mut function.blocks {
+        for instruction in &mut block.instructions {
+            let Instruction::Binary { op, lhs, rhs, .. } = instruction else {
+                continue;
+            };
+            if *op == BinaryOp::Mul && matches!(lhs, Operand::Const(_)) {
+                // Put the constant on the right
+                std::mem::swap(lhs, rhs);
+            }
+            let Operand::Const(constant) = *rhs else {
+                continue;
+            };
+            if !constant.is_power_of_two() {
+                continue;
+            }
+            let shift = constant.trailing_zeros() as u64;
+            match op {
+                BinaryOp::Mul => {
+                    *op = BinaryOp::Shl;
+                    *rhs = Operand::Const(shift);
+                }
+                BinaryOp::UDiv => {
+                    *op = BinaryOp::LShr;
+                    *rhs = Operand::Const(shift);
+                }
+                BinaryOp::URem => {
+                    *op = BinaryOp::And;
+                    *rhs = Operand::Const(constant - 1);
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use std::fs::File;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::os::unix::process::CommandExt;
 use std::path::Path;
 use std::process::Command;
@@ -8,7 +8,12 @@ use std::process::exit;
 use crate::err::ErrorHandler;
 
 mod codegen;
+mod consteval;
+mod emit;
 mod err;
+mod ir;
+mod mangle;
+mod stdlib;
 mod structure;
 mod tokenizer;
 mod typing;
@@ -17,12 +22,26 @@ const HELP: &str = "sabbahc - cli for the Sabbah language
 
 Usage:
   sabbahc <INPUT> [OPTIONS]
+  sabbahc demangle [SYMBOLS]
 
 Options:
   -o, --output <file>     Specify output filename
   -s, --asm               Compile only; do not assemble or link
   -b, --object            Compile and assemble; do not link
   -m, --mode <mode>       Manually specify the output mode
+                          (tokens, ast, ir, asm, obj, bin, staticlib,
+                          sharedlib, header)
+  -e, --emit <kinds>      Write each listed stage to its own file; may be
+                          repeated (tokens, tokens-json, ast, ast-json,
+                          ir, asm, obj, exe, staticlib, sharedlib, header)
+  -O0, -O1, -O2           Optimization level (default: -O0)
+      --libc              Link through cc against the C library, whose
+                          startup code then calls main
+      --no-bounds-checks  Leave out the runtime checks on array and slice
+                          indices
+      --overflow <mode>   What integer arithmetic does when its result does
+                          not fit its type: panic (checked, the default) or
+                          wrap around (wrapping)
   -v, --version           Show version information and exit
   -h, --help              Show this help message and exit
   -f, --force             Ignore nonfatal errors
@@ -30,18 +49,74 @@ Options:
 Description:
   sabbahc is a command-line compiler for the Sabbah programming language. It
   supports compiling to assembly, object code, and fully linked executables.
+  `sabbahc demangle` turns mangled symbols back into Sabbah paths; without
+  any symbols, it filters stdin, e.g. `objdump -d prog | sabbahc demangle`.
 
 Examples:
   sabbahc main.sbb         # Compile and link (default output: <filename>.out)
   sabbahc main.sbb -o prog # Compile and link, output to 'prog'
   sabbahc main.sbb -s      # Compile to Assembly only
+  sabbahc main.sbb --emit=tokens,ast,exe
+                           # Dump tokens and AST, and link main
+  sabbahc math.sbb --emit=sharedlib,header
+                           # Build libmath.so and math.h for C callers
 ";
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 enum OutputMode {
+    Tokens,
+    TokensJson,
+    Ast,
+    AstJson,
+    Ir,
     Assembly,
     Object,
     BinaryExecutable,
+    StaticLibrary,
+    SharedLibrary,
+    Header,
+}
+
+impl OutputMode {
+    pub fn from_emit(kind: &str) -> Option<OutputMode> {
+        match kind {
+            "tokens" => Some(OutputMode::Tokens),
+            "tokens-json" => Some(OutputMode::TokensJson),
+            "ast" => Some(OutputMode::Ast),
+            "ast-json" => Some(OutputMode::AstJson),
+            "ir" => Some(OutputMode::Ir),
+            "asm" => Some(OutputMode::Assembly),
+            "obj" => Some(OutputMode::Object),
+            "exe" => Some(OutputMode::BinaryExecutable),
+            "staticlib" => Some(OutputMode::StaticLibrary),
+            "sharedlib" => Some(OutputMode::SharedLibrary),
+            "header" => Some(OutputMode::Header),
+            _ => None,
+        }
+    }
+    pub fn output_name(&self, stem: &str) -> String {
+        match self {
+            OutputMode::Tokens => format!("{}.tokens", stem),
+            OutputMode::TokensJson => format!("{}.tokens.json", stem),
+            OutputMode::Ast => format!("{}.ast", stem),
+            OutputMode::AstJson => format!("{}.ast.json", stem),
+            OutputMode::Ir => format!("{}.ir", stem),
+            OutputMode::Assembly => format!("{}.s", stem),
+            OutputMode::Object => format!("{}.o", stem),
+            OutputMode::BinaryExecutable => stem.to_string(),
+            OutputMode::StaticLibrary => library_name(stem, "a"),
+            OutputMode::SharedLibrary => library_name(stem, "so"),
+            OutputMode::Header => format!("{}.h", stem),
+        }
+    }
+}
+
+/// `dir/stem` becomes `dir/libstem.ext`, the name `-lstem` looks for.
+fn library_name(stem: &str, ext: &str) -> String {
+    match stem.rsplit_once('/') {
+        Some((dir, name)) => format!("{}/lib{}.{}", dir, name, ext),
+        None => format!("lib{}.{}", stem, ext),
+    }
 }
 
 #[derive(Debug)]
@@ -53,10 +128,13 @@ enum EarlyExit {
 #[derive(Debug)]
 struct CLIInstructions {
     input: String,
-    output: String,
-    mode: OutputMode,
+    outputs: Vec<(OutputMode, String)>,
     exit_early: Option<EarlyExit>,
     force: bool,
+    opt_level: u8,
+    libc: bool,
+    bounds_checks: bool,
+    overflow: ir::lower::Overflow,
 }
 
 impl CLIInstructions {
@@ -64,8 +142,13 @@ impl CLIInstructions {
         let mut input: String = String::new();
         let mut output: String = String::new();
         let mut mode: OutputMode = OutputMode::BinaryExecutable;
+        let mut emit: Vec<OutputMode> = Vec::new();
         let mut exit_early: Option<EarlyExit> = None;
         let mut force: bool = false;
+        let mut opt_level: u8 = 0;
+        let mut libc: bool = false;
+        let mut bounds_checks: bool = true;
+        let mut overflow: ir::lower::Overflow = ir::lower::Overflow::Checked;
         let mut i = 1 /* skip commmand */;
         let mut input_set: bool = false;
         while i < args.len() {
@@ -76,6 +159,23 @@ impl CLIInstructions {
                 "-f" | "--force" => {
                     force = true;
                 }
+                "-O0" => opt_level = 0,
+                "-O1" => opt_level = 1,
+                "-O2" => opt_level = 2,
+                "--libc" => libc = true,
+                "--no-bounds-checks" => bounds_checks = false,
+                "--overflow" => {
+                    if i + 1 < args.len() {
+                        overflow = overflow_mode(&args[i + 1]);
+                        i += 1;
+                    } else {
+                        println!("ERROR: --overflow flag requires an argument");
+                        exit(12);
+                    }
+                }
+                arg if arg.starts_with("--overflow=") => {
+                    overflow = overflow_mode(&arg["--overflow=".len()..]);
+                }
                 "-v" | "--version" => {
                     exit_early = Some(EarlyExit::Version);
                 }
@@ -94,12 +194,30 @@ impl CLIInstructions {
                 "-b" | "--object" => {
                     mode = OutputMode::Object;
                 }
+                "-e" | "--emit" => {
+                    if i + 1 < args.len() {
+                        add_emit_kinds(&mut emit, &args[i + 1]);
+                        i += 1;
+                    } else {
+                        println!("ERROR: -e flag requires an argument");
+                        exit(6);
+                    }
+                }
+                arg if arg.starts_with("--emit=") => {
+                    add_emit_kinds(&mut emit, &arg["--emit=".len()..]);
+                }
                 "-m" | "--mode" => {
                     if i + 1 < args.len() {
                         match args[i + 1].as_str() {
+                            "tokens" => mode = OutputMode::Tokens,
+                            "ast" => mode = OutputMode::Ast,
+                            "ir" => mode = OutputMode::Ir,
                             "asm" | "assembly" | "s" => mode = OutputMode::Assembly,
                             "obj" | "object" => mode = OutputMode::Object,
                             "bin" | "binary" => mode = OutputMode::BinaryExecutable,
+                            "staticlib" => mode = OutputMode::StaticLibrary,
+                            "sharedlib" => mode = OutputMode::SharedLibrary,
+                            "header" => mode = OutputMode::Header,
                             _ => {
                                 println!("ERROR: Unrecognized mode: {}", args[i + 1]);
                                 exit(4);
@@ -131,35 +249,81 @@ impl CLIInstructions {
             println!("WARNING: No input file specified, defaulting to stdin");
             input = String::from("/dev/stdin");
         }
-        if output.is_empty() {
-            // Generate output file name from input
-            let input_path = Path::new(&input);
-            let mut output_file_name = input_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("output")
-                .to_string();
-            if output_file_name.ends_with(".sbb") {
-                output_file_name = output_file_name[0..output_file_name.len() - 4].to_string();
-            }
-            match mode {
-                OutputMode::Assembly => output = format!("{}.s", output_file_name),
-                OutputMode::Object => output = format!("{}.o", output_file_name),
-                OutputMode::BinaryExecutable => output = format!("{}", output_file_name),
-            }
+        if emit.is_empty() {
+            emit.push(mode);
         }
+        emit.sort();
+        emit.dedup();
+        // With a single output, -o names it; with several, -o is the shared stem
+        let outputs: Vec<(OutputMode, String)> = if emit.len() == 1 && !output.is_empty() {
+            vec![(emit[0], output)]
+        } else {
+            let stem = if output.is_empty() {
+                // Generate output file name from input
+                let input_path = Path::new(&input);
+                let mut output_file_name = input_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("output")
+                    .to_string();
+                if output_file_name.ends_with(".sbb") {
+                    output_file_name = output_file_name[0..output_file_name.len() - 4].to_string();
+                }
+                output_file_name
+            } else {
+                output
+            };
+            emit.iter().map(|mode| (*mode, mode.output_name(&stem))).collect()
+        };
         return CLIInstructions {
             input,
-            output,
-            mode,
+            outputs,
             exit_early,
             force,
+            opt_level,
+            libc,
+            bounds_checks,
+            overflow,
         };
     }
+    fn output(&self, mode: OutputMode) -> Option<&String> {
+        self.outputs.iter().find(|(m, _)| *m == mode).map(|(_, name)| name)
+    }
+    fn wants_any_of(&self, modes: &[OutputMode]) -> bool {
+        self.outputs.iter().any(|(m, _)| modes.contains(m))
+    }
+}
+
+fn overflow_mode(mode: &str) -> ir::lower::Overflow {
+    match mode {
+        "checked" => ir::lower::Overflow::Checked,
+        "wrapping" => ir::lower::Overflow::Wrapping,
+        _ => {
+            println!("ERROR: Unrecognized overflow mode: {}", mode);
+            exit(13);
+        }
+    }
+}
+
+fn add_emit_kinds(emit: &mut Vec<OutputMode>, kinds: &str) {
+    for kind in kinds.split(',') {
+        match OutputMode::from_emit(kind) {
+            Some(mode) => emit.push(mode),
+            None => {
+                println!("ERROR: Unrecognized emit kind: {}", kind);
+                exit(7);
+            }
+        }
+    }
 }
 
 const VERSION: &str = "0.0.1";
 
+/// The Sabbah runtime, linked into every executable.
+const RUNTIME: &str = include_str!("../runtime/runtime.s");
+/// The entry point, unless the C runtime provides one.
+const START: &str = include_str!("../runtime/start.s");
+
 fn find_free_filename(ext: &str) -> String {
     let mut counter = 0;
     loop {
@@ -171,6 +335,100 @@ fn find_free_filename(ext: &str) -> String {
     }
 }
 
+/// Writes `contents` to `filename`, exiting on failure.
+fn write_output(filename: &str, contents: &str) {
+    let written = File::create(filename).and_then(|mut file| file.write_all(contents.as_bytes()));
+    if let Err(err) = written {
+        println!("ERROR: Could not write {}: {}", filename, err);
+        exit(14);
+    }
+}
+
+/// Assembles `source` into the object file `object`, exiting on failure.
+fn assemble(source: &str, object: &str) {
+    let assembly_filename = find_free_filename("s");
+    write_output(&assembly_filename, source);
+    let assembled = Command::new("as")
+        .arg(assembly_filename.clone())
+        .arg("-o")
+        .arg(object)
+        .output()
+        .expect("Assembler error");
+    // Only a leftover temporary file if it fails
+    let _ = std::fs::remove_file(assembly_filename);
+    if !assembled.status.success() {
+        println!("ERROR: Assembling failed:\n{}", String::from_utf8_lossy(&assembled.stderr));
+        exit(10);
+    }
+}
+
+/// Assembles `source` and the runtime (and, unless the C runtime provides
+/// one, our entry point), returning the object files.
+fn objects(source: &str, start: bool) -> Vec<String> {
+    let mut sources = vec![source, RUNTIME];
+    if start {
+        sources.push(START);
+    }
+    sources
+        .into_iter()
+        .map(|source| {
+            let object_filename = find_free_filename("o");
+            assemble(source, &object_filename);
+            object_filename
+        })
+        .collect()
+}
+
+/// Runs `command` on `objects` and removes them, exiting on failure.
+fn combine(mut command: Command, objects: Vec<String>, what: &str) {
+    let combined = command.args(&objects).output().expect("Linker error");
+    for object in objects {
+        let _ = std::fs::remove_file(object);
+    }
+    if !combined.status.success() {
+        println!("ERROR: {} failed:\n{}", what, String::from_utf8_lossy(&combined.stderr));
+        exit(11);
+    }
+}
+
+/// Assembles `source` and links it with the runtime into the executable
+/// `output`. With `libc`, links through `cc` so that the C library and its
+/// startup code come along; otherwise runs `ld` with our own `_start`.
+fn link(source: &str, output: &str, libc: bool) {
+    let mut command = Command::new(if libc { "cc" } else { "ld" });
+    command.arg("-o").arg(output);
+    combine(command, objects(source, !libc), "Linking");
+}
+
+/// Bundles `source` and the runtime into the static library `output`.
+fn archive(source: &str, output: &str) {
+    let mut command = Command::new("ar");
+    command.arg("rcs").arg(output);
+    combine(command, objects(source, false), "Archiving");
+}
+
+/// Links `source` and the runtime into the shared library `output`. The
+/// generated code only uses RIP-relative addressing, so it is position
+/// independent as it is.
+fn link_shared(source: &str, output: &str, libc: bool) {
+    let mut command = Command::new(if libc { "cc" } else { "ld" });
+    command.arg("-shared").arg("-o").arg(output);
+    combine(command, objects(source, false), "Linking");
+}
+
+/// The `demangle` subcommand: demangles each symbol given, or every symbol
+/// in stdin if there are none.
+fn demangle(symbols: &[String]) {
+    if symbols.is_empty() {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input).expect("Failed to read stdin");
+        print!("{}", mangle::demangle_text(&input));
+    }
+    for symbol in symbols {
+        println!("{}", mangle::demangle(symbol).unwrap_or(symbol.clone()));
+    }
+}
+
 fn main() {
     // Start the timer - how long does it take to compile?
     let start_time = std::time::Instant::now();
@@ -180,6 +438,10 @@ fn main() {
         print!("{}", HELP);
         exit(1);
     }
+    if args[1] == "demangle" {
+        demangle(&args[2..]);
+        exit(0);
+    }
     let instructions: CLIInstructions = CLIInstructions::from(args);
     if let Some(exit_early) = instructions.exit_early {
         match exit_early {
@@ -196,14 +458,15 @@ fn main() {
     /* Check file requirements */
     if !instructions.force {
         let input = &instructions.input.clone();
-        let output = &instructions.output.clone();
         if !std::fs::exists(input.clone()).unwrap() {
             println!("ERROR: Input file {} does not exist", input.as_str());
             exit(8);
         }
-        if std::fs::exists(output).unwrap() {
-            println!("ERROR: Output file {} already exists", output.as_str());
-            exit(9);
+        for (_, output) in &instructions.outputs {
+            if std::fs::exists(output).unwrap() {
+                println!("ERROR: Output file {} already exists", output.as_str());
+                exit(9);
+            }
         }
     }
     let input =
@@ -215,59 +478,87 @@ fn main() {
     );
     let mut tokenizer = tokenizer::Tokenizer::new(&input, instructions.input.clone(), &error_handler);
     let tokenized: Vec<tokenizer::Token> = tokenizer.tokenize();
+    if let Some(output) = instructions.output(OutputMode::Tokens) {
+        write_output(output, &emit::tokens(&tokenized));
+    }
+    if let Some(output) = instructions.output(OutputMode::TokensJson) {
+        write_output(output, &emit::tokens_json(&tokenized));
+    }
     
-    let mut parser = structure::parser::Parser::new(&tokenized, &error_handler);
-    let parsed: structure::Scope = parser.parse();
-    
-    let mut codegener = codegen::CodeGenerator::new(
-        parsed,
-        &error_handler,
-    );
-    let generated = codegener.out();
+    if instructions.wants_any_of(&[
+        OutputMode::Ast,
+        OutputMode::AstJson,
+        OutputMode::Ir,
+        OutputMode::Assembly,
+        OutputMode::Object,
+        OutputMode::BinaryExecutable,
+        OutputMode::StaticLibrary,
+        OutputMode::SharedLibrary,
+        OutputMode::Header,
+    ]) {
+        let types = structure::parser::types(&tokenized, &error_handler);
+        let mut parser = structure::parser::Parser::new(&tokenized, &types, &error_handler);
+        let parsed: structure::Scope = parser.parse();
+        if let Some(output) = instructions.output(OutputMode::Ast) {
+            write_output(output, &emit::ast(&parsed));
+        }
+        if let Some(output) = instructions.output(OutputMode::AstJson) {
+            write_output(output, &emit::ast_json(&parsed));
+        }
 
-    match instructions.mode {
-        OutputMode::Assembly => {
-            {
-                let mut file = File::create(instructions.output.as_str()).unwrap();
-                write!(file, "{}", generated);
+        if instructions.wants_any_of(&[
+            OutputMode::Ir,
+            OutputMode::Assembly,
+            OutputMode::Object,
+            OutputMode::BinaryExecutable,
+            OutputMode::StaticLibrary,
+            OutputMode::SharedLibrary,
+            OutputMode::Header,
+        ]) {
+            // A library on its own has no entry point
+            let executable = !instructions.wants_any_of(&[
+                OutputMode::StaticLibrary,
+                OutputMode::SharedLibrary,
+                OutputMode::Header,
+            ]) || instructions.wants_any_of(&[OutputMode::BinaryExecutable]);
+            let mut lowerer = ir::lower::Lowerer::new(
+                &parsed,
+                &types,
+                &error_handler,
+                executable,
+                instructions.bounds_checks,
+                instructions.overflow,
+            );
+            let mut lowered: ir::Program = lowerer.lower();
+            ir::opt::optimize(&mut lowered, instructions.opt_level);
+            // Only now that lowering has checked the exported signatures
+            if let Some(output) = instructions.output(OutputMode::Header) {
+                write_output(output, &emit::header(&parsed, output));
             }
-        }
-        OutputMode::Object => {
-            let assembly_filename = find_free_filename("s");
-            {
-                let mut file = File::create(assembly_filename.clone()).unwrap();
-                write!(file, "{}", generated);
+            if let Some(output) = instructions.output(OutputMode::Ir) {
+                write_output(output, &lowered.to_string());
             }
-            Command::new("as")
-                .arg(assembly_filename.clone())
-                .arg("-o")
-                .arg(instructions.output)
-                .output()
-                .expect("Assembler error");
-            std::fs::remove_file(assembly_filename);
-        }
-        OutputMode::BinaryExecutable => {
-            let assembly_filename = find_free_filename("s");
-            let object_filename = find_free_filename("o");
-            {
-                let mut file = File::create(assembly_filename.clone()).unwrap();
-                write!(file, "{}", generated);
+
+            let mut codegener = codegen::CodeGenerator::new(
+                lowered,
+                &error_handler,
+            );
+            let generated = codegener.out();
+            if let Some(output) = instructions.output(OutputMode::Assembly) {
+                write_output(output, &generated);
+            }
+            if let Some(output) = instructions.output(OutputMode::Object) {
+                assemble(&generated, output);
+            }
+            if let Some(output) = instructions.output(OutputMode::BinaryExecutable) {
+                link(&generated, output, instructions.libc);
+            }
+            if let Some(output) = instructions.output(OutputMode::StaticLibrary) {
+                archive(&generated, output);
+            }
+            if let Some(output) = instructions.output(OutputMode::SharedLibrary) {
+                link_shared(&generated, output, instructions.libc);
             }
-            Command::new("as")
-                .arg(assembly_filename.clone())
-                .arg("-o")
-                .arg(object_filename.clone())
-                .output()
-                .expect("Assembler error");
-            Command::new("ld")
-                .arg("-o")
-                .arg(instructions.output)
-                .arg("runtime.o")
-                .arg(object_filename.clone())
-                .output()
-                .expect("Linker error");
-            std::fs::remove_file(assembly_filename);
-            std::fs::remove_file(object_filename);
         }
     }
     
@@ -0,0 +1,141 @@
+//! Symbol mangling: how Sabbah functions and statics are named in assembly.
+//!
+//! A function's path (just its name, until there are modules) is spelled
+//! out as length-prefixed identifiers between `_S` and `E`, so `math::twice`
+//! becomes `_S4math5twiceE`. Mangled symbols never collide with C functions
+//! or with the runtime, which is why function names may start with an
+//! underscore. `main`, exported functions and `#[no_mangle]` ones keep
+//! their plain name. Statics are always mangled, like functions.
+//!
+//! Each copy of a generic function gets the types it was made for between
+//! `I` and `E` after its path, so `max<int32>` becomes `_S3maxI5int32EE`.
+//! A named type is spelled like an identifier, `*T` as `P` and then `T`,
+//! `[]T` as `S` and then `T`, and `[T; N]` as `A`, `N`, `_` and then `T`.
+
+const PREFIX: &str = "_S";
+const END: char = 'E';
+const TYPES: char = 'I';
+const POINTER: char = 'P';
+const SLICE: char = 'S';
+const ARRAY: char = 'A';
+
+/// The attribute that keeps a function's symbol unmangled.
+pub const NO_MANGLE: &str = "no_mangle";
+
+pub fn mangle(path: &[&str]) -> String {
+    mangle_generic(path, &[])
+}
+
+/// The symbol of the copy of a generic function made for `types`, given
+/// by name.
+pub fn mangle_generic(path: &[&str], types: &[&str]) -> String {
+    let mut symbol = String::from(PREFIX);
+    for ident in path {
+        symbol.push_str(&format!("{}{}", ident.len(), ident));
+    }
+    if !types.is_empty() {
+        symbol.push(TYPES);
+        for ty in types {
+            mangle_type(ty, &mut symbol);
+        }
+        symbol.push(END);
+    }
+    symbol.push(END);
+    symbol
+}
+
+fn mangle_type(name: &str, symbol: &mut String) {
+    if let Some(pointee) = name.strip_prefix('*') {
+        symbol.push(POINTER);
+        mangle_type(pointee, symbol);
+    } else if let Some(element) = name.strip_prefix("[]") {
+        symbol.push(SLICE);
+        mangle_type(element, symbol);
+    } else if let Some((element, length)) = name.strip_prefix('[').and_then(|name| name.strip_suffix(']')?.rsplit_once("; ")) {
+        symbol.push_str(&format!("{}{}_", ARRAY, length));
+        mangle_type(element, symbol);
+    } else {
+        symbol.push_str(&format!("{}{}", name.len(), name));
+    }
+}
+
+/// Reads a length-prefixed identifier off the front of `rest`.
+fn ident<'s>(rest: &mut &'s str) -> Option<&'s str> {
+    let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 || rest.starts_with('0') {
+        return None;
+    }
+    let len: usize = rest[..digits].parse().ok()?;
+    let ident = rest.get(digits..digits + len)?;
+    *rest = &rest[digits + len..];
+    Some(ident)
+}
+
+/// Reads a mangled type off the front of `rest`, as it is written in Sabbah.
+fn demangle_type(rest: &mut &str) -> Option<String> {
+    if let Some(pointee) = rest.strip_prefix(POINTER) {
+        *rest = pointee;
+        return Some(format!("*{}", demangle_type(rest)?));
+    }
+    if let Some(element) = rest.strip_prefix(SLICE) {
+        *rest = element;
+        return Some(format!("[]{}", demangle_type(rest)?));
+    }
+    if let Some(array) = rest.strip_prefix(ARRAY) {
+        let (length, element) = array.split_once('_')?;
+        if length.is_empty() || !length.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        *rest = element;
+        return Some(format!("[{}; {}]", demangle_type(rest)?, length));
+    }
+    ident(rest).map(String::from)
+}
+
+/// The `::`-separated path a symbol was mangled from, or None if it is not
+/// a mangled symbol.
+pub fn demangle(symbol: &str) -> Option<String> {
+    let mut rest = symbol.strip_prefix(PREFIX)?;
+    let mut path: Vec<&str> = Vec::new();
+    while !rest.starts_with(END) && !rest.starts_with(TYPES) {
+        path.push(ident(&mut rest)?);
+    }
+    if path.is_empty() {
+        return None;
+    }
+    let mut demangled = path.join("::");
+    if let Some(types) = rest.strip_prefix(TYPES) {
+        rest = types;
+        let mut names: Vec<String> = Vec::new();
+        while !rest.starts_with(END) {
+            names.push(demangle_type(&mut rest)?);
+        }
+        if names.is_empty() {
+            return None;
+        }
+        demangled.push_str(&format!("<{}>", names.join(", ")));
+        rest = &rest[END.len_utf8()..];
+    }
+    if rest.len() != END.len_utf8() {
+        return None;
+    }
+    Some(demangled)
+}
+
+/// Demangles every symbol in `text`, leaving everything else untouched,
+/// like `c++filt` does for C++.
+pub fn demangle_text(text: &str) -> String {
+    let mut out = String::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+        out.push_str(&demangle(&word).unwrap_or(word.clone()));
+        word.clear();
+        out.push(c);
+    }
+    out.push_str(&demangle(&word).unwrap_or(word));
+    out
+}
@@ -0,0 +1,70 @@
+//! The standard library: functions implemented in the runtime
+//! (runtime/runtime.s) on top of raw Linux syscalls, so no libc is needed.
+//!
+//! Their symbols start with an underscore, like everything else in the
+//! runtime, so they stay out of the way of Sabbah functions.
+
+use std::sync::LazyLock;
+
+use crate::typing::{Type, STR, UINT64, UINT8};
+
+/// A function of the standard library, called by `name` in Sabbah.
+pub struct Builtin {
+    pub name: &'static str,
+    pub symbol: &'static str,
+    pub params: Vec<&'static Type>,
+    pub return_type: Option<&'static Type>,
+}
+
+/// `syscall(n, a1..a6)` is not a runtime function: it lowers straight to the
+/// `syscall` instruction, and returns %rax as an int64, which is negative
+/// on failure.
+pub const SYSCALL: &str = "syscall";
+
+/// `len(a)` is not a runtime function either: it is the length of an array,
+/// known at compile time, or of a slice or str, as a uint64.
+pub const LEN: &str = "len";
+
+/// `wrapping_add(a, b)`, `checked_add(a, b)` and `saturating_add(a, b)`, and
+/// the same for `sub` and `mul`, are not runtime functions either: they are
+/// arithmetic on two integers of one type that, whatever `--overflow` says,
+/// wraps around, panics or clamps to the type's range if the result does
+/// not fit it.
+pub const ARITHMETIC: [&str; 9] = [
+    "wrapping_add",
+    "wrapping_sub",
+    "wrapping_mul",
+    "checked_add",
+    "checked_sub",
+    "checked_mul",
+    "saturating_add",
+    "saturating_sub",
+    "saturating_mul",
+];
+
+/// The runtime routine called when a runtime check fails, such as an index
+/// being out of bounds or arithmetic overflowing. It writes the str it
+/// takes, saying what went wrong and where, to stderr and exits with code 101.
+pub const PANIC: &str = "_sabbah_panic";
+
+/// Whether `symbol` belongs to the runtime: the entry point, or anything in
+/// its `_sabbah_` namespace. Mangled symbols never are.
+pub fn is_reserved(symbol: &str) -> bool {
+    symbol == "_start" || symbol.starts_with("_sabbah_")
+}
+
+pub static BUILTIN_FUNCTIONS: LazyLock<Vec<Builtin>> = LazyLock::new(|| {
+    vec![
+        // Writes the string to stdout
+        Builtin { name: "print", symbol: "_sabbah_print", params: vec![&*STR], return_type: None },
+        // Writes the string and a newline to stdout
+        Builtin { name: "println", symbol: "_sabbah_println", params: vec![&*STR], return_type: None },
+        // Writes the number in decimal to stdout
+        Builtin { name: "print_int", symbol: "_sabbah_print_int", params: vec![&*UINT64], return_type: None },
+        // Ends the program with the given exit code
+        Builtin { name: "exit", symbol: "_sabbah_exit", params: vec![&*UINT8], return_type: None },
+        // Reads a line from stdin, without the newline. The result lives in a
+        // buffer that the next call overwrites.
+        Builtin { name: "read_line", symbol: "_sabbah_read_line", params: Vec::new(), return_type: Some(&*STR) },
+    ]
+});
@@ -1,6 +1,6 @@
 pub mod parser;
 use crate::typing::Type;
-use crate::typing::UINT8;
+use crate::typing::STR;
 use std::fmt;
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -10,15 +10,29 @@ pub enum AstNodeType {
     Expression,
     FunctionKeyword,
     FunctionIdent,
+    TypeParams,
     ParamTypeTuple,
     TypeIdent,
-    ThinArrow
+    ThinArrow,
+    Const,
+    Let,
+    Static,
+    Assignment,
+    Asm,
+    ExternKeyword,
+    ExportKeyword,
+    Attribute,
+    Struct,
+    Enum,
 }
 
 impl AstNodeType {
     pub fn fk<'a>(line: usize, column: usize) -> AstNode<'a> {
         AstNode { node_type: Self::FunctionKeyword, value: None, line, column }
     }
+    pub fn export<'a>(line: usize, column: usize) -> AstNode<'a> {
+        AstNode { node_type: Self::ExportKeyword, value: None, line, column }
+    }
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -26,8 +40,23 @@ pub enum AstNodeValue<'a> {
     Scope(Scope<'a>),
     Expression(Expression<'a>),
     FunctionIdent(String),
-    ParamTypeTuple(ParamTypeTuple),
-    TypeIdent(&'a Type)
+    /// The names in `<T, U>` after the name of a generic function
+    TypeParams(Vec<String>),
+    ParamTypeTuple(ParamTypeTuple<'a>),
+    TypeIdent(&'a Type),
+    Const(ConstDeclaration<'a>),
+    Let(LetBinding<'a>),
+    Static(StaticDeclaration<'a>),
+    Assignment(Assignment<'a>),
+    Asm(AsmBlock<'a>),
+    /// The calling convention of an `extern` function, e.g. "C"
+    Abi(String),
+    /// The name in `#[name]` before a function
+    Attribute(String),
+    /// A struct declaration, already laid out by the parser's first pass
+    Struct(&'a Type),
+    /// An enum declaration, likewise
+    Enum(&'a Type),
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -51,13 +80,10 @@ impl<'a> AstNode<'a> {
             column
         }
     }
-    pub fn ret(content: u8, line: usize, column: usize) -> AstNode<'a> {
+    pub fn ret(expression: Expression<'a>, line: usize, column: usize) -> AstNode<'a> {
         AstNode {
             node_type: AstNodeType::ReturnKeyword,
-            value: Some(AstNodeValue::Expression(Expression {
-                eval_type: &UINT8,
-                content // Placeholder for return expression
-            })),
+            value: Some(AstNodeValue::Expression(expression)),
             line,
             column
         }
@@ -70,16 +96,104 @@ impl<'a> AstNode<'a> {
             column
         }
     }
-    pub fn tup(line: usize, column: usize) -> AstNode<'a> {
+    pub fn type_params(names: Vec<String>, line: usize, column: usize) -> AstNode<'a> {
+        AstNode {
+            node_type: AstNodeType::TypeParams,
+            value: Some(AstNodeValue::TypeParams(names)),
+            line,
+            column
+        }
+    }
+    pub fn tup(params: Vec<Param<'a>>, line: usize, column: usize) -> AstNode<'a> {
         AstNode {
             node_type: AstNodeType::ParamTypeTuple,
             value: Some(AstNodeValue::ParamTypeTuple(
-                ParamTypeTuple {}
+                ParamTypeTuple { params }
             )),
             line,
             column
         }
     }
+    pub fn expr(expression: Expression<'a>, line: usize, column: usize) -> AstNode<'a> {
+        AstNode {
+            node_type: AstNodeType::Expression,
+            value: Some(AstNodeValue::Expression(expression)),
+            line,
+            column
+        }
+    }
+    pub fn constant(declaration: ConstDeclaration<'a>, line: usize, column: usize) -> AstNode<'a> {
+        AstNode {
+            node_type: AstNodeType::Const,
+            value: Some(AstNodeValue::Const(declaration)),
+            line,
+            column
+        }
+    }
+    pub fn binding(binding: LetBinding<'a>, line: usize, column: usize) -> AstNode<'a> {
+        AstNode {
+            node_type: AstNodeType::Let,
+            value: Some(AstNodeValue::Let(binding)),
+            line,
+            column
+        }
+    }
+    pub fn stat(declaration: StaticDeclaration<'a>, line: usize, column: usize) -> AstNode<'a> {
+        AstNode {
+            node_type: AstNodeType::Static,
+            value: Some(AstNodeValue::Static(declaration)),
+            line,
+            column
+        }
+    }
+    pub fn assign(assignment: Assignment<'a>, line: usize, column: usize) -> AstNode<'a> {
+        AstNode {
+            node_type: AstNodeType::Assignment,
+            value: Some(AstNodeValue::Assignment(assignment)),
+            line,
+            column
+        }
+    }
+    pub fn asm(block: AsmBlock<'a>, line: usize, column: usize) -> AstNode<'a> {
+        AstNode {
+            node_type: AstNodeType::Asm,
+            value: Some(AstNodeValue::Asm(block)),
+            line,
+            column
+        }
+    }
+    pub fn attr(name: String, line: usize, column: usize) -> AstNode<'a> {
+        AstNode {
+            node_type: AstNodeType::Attribute,
+            value: Some(AstNodeValue::Attribute(name)),
+            line,
+            column
+        }
+    }
+    pub fn structure(ty: &'a Type, line: usize, column: usize) -> AstNode<'a> {
+        AstNode {
+            node_type: AstNodeType::Struct,
+            value: Some(AstNodeValue::Struct(ty)),
+            line,
+            column
+        }
+    }
+    pub fn enumeration(ty: &'a Type, line: usize, column: usize) -> AstNode<'a> {
+        AstNode {
+            node_type: AstNodeType::Enum,
+            value: Some(AstNodeValue::Enum(ty)),
+            line,
+            column
+        }
+    }
+    pub fn ext(abi: String, line: usize, column: usize) -> AstNode<'a> {
+        AstNode {
+            node_type: AstNodeType::ExternKeyword,
+            value: Some(AstNodeValue::Abi(abi)),
+            line,
+            column
+        }
+    }
     pub fn ti(typeident: &'a Type, line: usize, column: usize) -> AstNode<'a> {
         AstNode {
             node_type: AstNodeType::TypeIdent,
@@ -92,17 +206,237 @@ impl<'a> AstNode<'a> {
     }
 }
 
+/// `const NAME: type = value;`
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
-pub struct ParamTypeTuple {}
+pub struct ConstDeclaration<'a> {
+    pub name: String,
+    pub const_type: &'a Type,
+    pub value: Expression<'a>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// `let name[: type] = value;` - without a type, it is inferred from the value
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LetBinding<'a> {
+    pub name: String,
+    pub binding_type: Option<&'a Type>,
+    pub value: Expression<'a>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// `static [mut] NAME: type [= value];` - without a value, the global starts out as zero
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StaticDeclaration<'a> {
+    pub name: String,
+    pub mutable: bool,
+    pub static_type: &'a Type,
+    pub value: Option<Expression<'a>>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// `target = value;`, where the target is a static, `*pointer` or `value[index]`
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Assignment<'a> {
+    pub target: Expression<'a>,
+    pub value: Expression<'a>,
+}
+
+/// `asm { "line"; ...; in name = value; out target; }`
+///
+/// Each `{name}` in the template is replaced by the operand bound to it.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AsmBlock<'a> {
+    pub template: Vec<String>,
+    pub inputs: Vec<AsmInput<'a>>,
+    pub outputs: Vec<AsmOutput>,
+}
+
+/// `in name = value;` - `value` is evaluated before the block runs
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AsmInput<'a> {
+    pub name: String,
+    pub value: Expression<'a>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// `out target;` - `{target}` is written by the block and assigned to `target` afterwards
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AsmOutput {
+    pub target: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Param<'a> {
+    pub name: String,
+    pub param_type: &'a Type,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ParamTypeTuple<'a> {
+    pub params: Vec<Param<'a>>,
+}
+
+/// `name: value` in a struct literal
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FieldInit<'a> {
+    pub name: String,
+    pub value: Expression<'a>,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// What a match arm compares the value against.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Pattern {
+    /// `_`, which matches anything
+    Wildcard,
+    /// A name, which matches anything and is bound to the value
+    Binding(String),
+    /// An integer or character literal
+    Int(u64),
+    /// `true` or `false`
+    Bool(bool),
+    /// `low..=high`
+    Range(u64, u64),
+    /// `Enum::Variant` or `Enum::Variant(a, _, ...)`, which binds the
+    /// variant's fields to names
+    Variant(String, String, Vec<String>),
+}
+
+/// `pattern => value` in a match
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MatchArm<'a> {
+    pub pattern: Pattern,
+    pub value: Expression<'a>,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl BinaryOperator {
+    /// Binding power; higher binds tighter.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Add | Self::Subtract => 1,
+            Self::Multiply | Self::Divide | Self::Remainder => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExpressionContent<'a> {
+    IntLiteral(u64),
+    StrLiteral(String),
+    Variable(String),
+    Call(String, Vec<Expression<'a>>),
+    Binary(BinaryOperator, Box<Expression<'a>>, Box<Expression<'a>>),
+    /// `value.field`
+    FieldAccess(Box<Expression<'a>>, String),
+    /// `Name { field: value, ... }`
+    StructLiteral(String, Vec<FieldInit<'a>>),
+    /// `Enum::Variant` or `Enum::Variant(value, ...)`
+    Variant(String, String, Vec<Expression<'a>>),
+    /// `match value { pattern => value, ... }`
+    Match(Box<Expression<'a>>, Vec<MatchArm<'a>>),
+    /// `&name`
+    AddressOf(String),
+    /// `*pointer`
+    Deref(Box<Expression<'a>>),
+    /// `null`, the pointer to nothing
+    Null,
+    /// `true` or `false`
+    BoolLiteral(bool),
+    /// `value as type`
+    Cast(Box<Expression<'a>>, &'a Type),
+    /// `[value, ...]`
+    ArrayLiteral(Vec<Expression<'a>>),
+    /// `value[index]`, of an array or slice
+    Index(Box<Expression<'a>>, Box<Expression<'a>>),
+    /// `value[start..end]`, where either bound may be left out
+    Slice(Box<Expression<'a>>, Option<Box<Expression<'a>>>, Option<Box<Expression<'a>>>),
+}
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Expression<'a> {
-    eval_type: &'a Type,
-    content: u8 // anything else not implemented yet :|
+    /// Known up front only for string, struct and enum literals; the rest
+    /// are typed as they are lowered
+    pub eval_type: Option<&'a Type>,
+    pub content: ExpressionContent<'a>,
+    pub line: usize,
+    pub column: usize,
 }
 impl<'a> Expression<'a> {
-    pub fn new(content: u8) -> Self {
-        Self { eval_type: &UINT8, content }
+    pub fn new(content: ExpressionContent<'a>, line: usize, column: usize) -> Self {
+        let eval_type: Option<&Type> = match content {
+            ExpressionContent::StrLiteral(_) => Some(&STR),
+            _ => None,
+        };
+        Self { eval_type, content, line, column }
+    }
+
+    /// Replaces every type written in this expression, e.g. the type
+    /// parameters of a generic function with the types they stand for.
+    pub fn map_types(&mut self, map: &dyn Fn(&'a Type) -> &'a Type) {
+        self.eval_type = self.eval_type.map(map);
+        match &mut self.content {
+            ExpressionContent::IntLiteral(_)
+            | ExpressionContent::StrLiteral(_)
+            | ExpressionContent::Variable(_)
+            | ExpressionContent::AddressOf(_)
+            | ExpressionContent::Null
+            | ExpressionContent::BoolLiteral(_) => {}
+            ExpressionContent::Call(_, args)
+            | ExpressionContent::Variant(_, _, args)
+            | ExpressionContent::ArrayLiteral(args) => {
+                for arg in args {
+                    arg.map_types(map);
+                }
+            }
+            ExpressionContent::Binary(_, lhs, rhs) | ExpressionContent::Index(lhs, rhs) => {
+                lhs.map_types(map);
+                rhs.map_types(map);
+            }
+            ExpressionContent::FieldAccess(value, _) | ExpressionContent::Deref(value) => {
+                value.map_types(map);
+            }
+            ExpressionContent::StructLiteral(_, fields) => {
+                for field in fields {
+                    field.value.map_types(map);
+                }
+            }
+            ExpressionContent::Match(value, arms) => {
+                value.map_types(map);
+                for arm in arms {
+                    arm.value.map_types(map);
+                }
+            }
+            ExpressionContent::Cast(value, ty) => {
+                value.map_types(map);
+                *ty = map(ty);
+            }
+            ExpressionContent::Slice(value, start, end) => {
+                value.map_types(map);
+                for bound in [start, end].into_iter().flatten() {
+                    bound.map_types(map);
+                }
+            }
+        }
     }
 }
 
@@ -118,6 +452,50 @@ impl<'a> Statement<'a> {
     pub fn push(&mut self, child: AstNode<'a>) {
         self.children.push(child);
     }
+
+    /// Replaces every type written in this statement and the scopes under it.
+    pub fn map_types(&mut self, map: &dyn Fn(&'a Type) -> &'a Type) {
+        for child in &mut self.children {
+            match &mut child.value {
+                Some(AstNodeValue::TypeIdent(ty)) => *ty = map(ty),
+                Some(AstNodeValue::ParamTypeTuple(tuple)) => {
+                    for param in &mut tuple.params {
+                        param.param_type = map(param.param_type);
+                    }
+                }
+                Some(AstNodeValue::Expression(expression)) => expression.map_types(map),
+                Some(AstNodeValue::Const(declaration)) => {
+                    declaration.const_type = map(declaration.const_type);
+                    declaration.value.map_types(map);
+                }
+                Some(AstNodeValue::Let(binding)) => {
+                    binding.binding_type = binding.binding_type.map(map);
+                    binding.value.map_types(map);
+                }
+                Some(AstNodeValue::Static(declaration)) => {
+                    declaration.static_type = map(declaration.static_type);
+                    if let Some(value) = &mut declaration.value {
+                        value.map_types(map);
+                    }
+                }
+                Some(AstNodeValue::Assignment(assignment)) => {
+                    assignment.target.map_types(map);
+                    assignment.value.map_types(map);
+                }
+                Some(AstNodeValue::Asm(block)) => {
+                    for input in &mut block.inputs {
+                        input.value.map_types(map);
+                    }
+                }
+                Some(AstNodeValue::Scope(scope)) => {
+                    for statement in &mut scope.children {
+                        statement.map_types(map);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
@@ -160,14 +538,181 @@ impl<'a> fmt::Display for AstNodeValue<'a> {
             AstNodeValue::Scope(scope) => write!(f, "{}", scope),
             AstNodeValue::Expression(expr) => write!(f, "{}", expr),
             AstNodeValue::FunctionIdent(ident) => write!(f, "FunctionIdent({})", ident),
-            AstNodeValue::ParamTypeTuple(_) => write!(f, "ParamTypeTuple"),
-            AstNodeValue::TypeIdent(ty) => write!(f, "TypeIdent({:?})", ty),
+            AstNodeValue::TypeParams(names) => write!(f, "TypeParams({})", names.join(", ")),
+            AstNodeValue::ParamTypeTuple(tuple) => {
+                write!(f, "ParamTypeTuple(")?;
+                for (i, param) in tuple.params.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", param.name, param.param_type)?;
+                }
+                write!(f, ")")
+            }
+            AstNodeValue::TypeIdent(ty) => write!(f, "TypeIdent({})", ty),
+            AstNodeValue::Const(declaration) => write!(
+                f,
+                "Const({}: {} = {})",
+                declaration.name, declaration.const_type, declaration.value
+            ),
+            AstNodeValue::Let(binding) => {
+                write!(f, "Let({}", binding.name)?;
+                if let Some(ty) = binding.binding_type {
+                    write!(f, ": {}", ty)?;
+                }
+                write!(f, " = {})", binding.value)
+            }
+            AstNodeValue::Static(declaration) => {
+                write!(f, "Static(")?;
+                if declaration.mutable {
+                    write!(f, "mut ")?;
+                }
+                write!(f, "{}: {}", declaration.name, declaration.static_type)?;
+                if let Some(value) = &declaration.value {
+                    write!(f, " = {}", value)?;
+                }
+                write!(f, ")")
+            }
+            AstNodeValue::Assignment(assignment) => {
+                write!(f, "Assignment({} = {})", assignment.target.content, assignment.value)
+            }
+            AstNodeValue::Asm(block) => {
+                let mut parts: Vec<String> = block.template.iter().map(|line| format!("{:?}", line)).collect();
+                parts.extend(block.inputs.iter().map(|input| format!("in {} = {}", input.name, input.value)));
+                parts.extend(block.outputs.iter().map(|output| format!("out {}", output.target)));
+                write!(f, "Asm({})", parts.join("; "))
+            }
+            AstNodeValue::Abi(abi) => write!(f, "Abi({:?})", abi),
+            AstNodeValue::Attribute(name) => write!(f, "Attribute({})", name),
+            AstNodeValue::Struct(ty) => {
+                let fields: Vec<String> =
+                    ty.fields().iter().map(|field| format!("{}: {} @ {}", field.name, field.ty, field.offset)).collect();
+                write!(
+                    f,
+                    "Struct({} {{ {} }}, size {}, align {})",
+                    ty,
+                    fields.join(", "),
+                    ty.size().unwrap_or(0),
+                    ty.align()
+                )
+            }
+            AstNodeValue::Enum(ty) => {
+                let variants: Vec<String> = ty
+                    .variants()
+                    .iter()
+                    .map(|variant| {
+                        let fields: Vec<String> =
+                            variant.fields.iter().map(|field| format!("{} @ {}", field.ty, field.offset)).collect();
+                        if fields.is_empty() {
+                            format!("{} = {}", variant.name, variant.discriminant)
+                        } else {
+                            format!("{}({}) = {}", variant.name, fields.join(", "), variant.discriminant)
+                        }
+                    })
+                    .collect();
+                write!(
+                    f,
+                    "Enum({} {{ {} }}, tag {}, size {}, align {})",
+                    ty,
+                    variants.join(", "),
+                    ty.tag(),
+                    ty.size().unwrap_or(0),
+                    ty.align()
+                )
+            }
         }
     }
 }
 
 impl<'a> fmt::Display for Expression<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Expression(type: {:?}, content: {})", self.eval_type, self.content)
+        match self.eval_type {
+            Some(ty) => write!(f, "Expression(type: {}, content: {})", ty, self.content),
+            None => write!(f, "Expression(type: _, content: {})", self.content),
+        }
+    }
+}
+
+impl<'a> fmt::Display for ExpressionContent<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IntLiteral(value) => write!(f, "{}", value),
+            Self::StrLiteral(value) => write!(f, "{:?}", value),
+            Self::Variable(name) => write!(f, "{}", name),
+            Self::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg.content)?;
+                }
+                write!(f, ")")
+            }
+            Self::Binary(op, lhs, rhs) => write!(f, "({} {} {})", lhs.content, op, rhs.content),
+            Self::FieldAccess(value, field) => write!(f, "{}.{}", value.content, field),
+            Self::StructLiteral(name, fields) => {
+                let fields: Vec<String> =
+                    fields.iter().map(|field| format!("{}: {}", field.name, field.value.content)).collect();
+                write!(f, "{} {{ {} }}", name, fields.join(", "))
+            }
+            Self::Variant(name, variant, values) => {
+                write!(f, "{}::{}", name, variant)?;
+                if !values.is_empty() {
+                    let values: Vec<String> = values.iter().map(|value| value.content.to_string()).collect();
+                    write!(f, "({})", values.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::Match(value, arms) => {
+                let arms: Vec<String> = arms.iter().map(|arm| format!("{} => {}", arm.pattern, arm.value.content)).collect();
+                write!(f, "match {} {{ {} }}", value.content, arms.join(", "))
+            }
+            Self::AddressOf(name) => write!(f, "&{}", name),
+            Self::Deref(pointer) => write!(f, "*{}", pointer.content),
+            Self::Null => write!(f, "null"),
+            Self::BoolLiteral(value) => write!(f, "{}", value),
+            Self::Cast(value, ty) => write!(f, "({} as {})", value.content, ty),
+            Self::ArrayLiteral(values) => {
+                let values: Vec<String> = values.iter().map(|value| value.content.to_string()).collect();
+                write!(f, "[{}]", values.join(", "))
+            }
+            Self::Index(value, index) => write!(f, "{}[{}]", value.content, index.content),
+            Self::Slice(value, start, end) => {
+                let bound = |bound: &Option<Box<Expression>>| bound.as_ref().map(|bound| bound.content.to_string()).unwrap_or_default();
+                write!(f, "{}[{}..{}]", value.content, bound(start), bound(end))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wildcard => write!(f, "_"),
+            Self::Binding(name) => write!(f, "{}", name),
+            Self::Int(value) => write!(f, "{}", value),
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::Range(low, high) => write!(f, "{}..={}", low, high),
+            Self::Variant(name, variant, bindings) => {
+                write!(f, "{}::{}", name, variant)?;
+                if !bindings.is_empty() {
+                    write!(f, "({})", bindings.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Add => write!(f, "+"),
+            Self::Subtract => write!(f, "-"),
+            Self::Multiply => write!(f, "*"),
+            Self::Divide => write!(f, "/"),
+            Self::Remainder => write!(f, "%"),
+        }
     }
 }
\ No newline at end of file
@@ -1,21 +1,628 @@
+use std::collections::HashMap;
+
 use crate::{
-    err::ErrorHandler, structure::*, tokenizer::{Token, TokenType, TokenValue}, typing::UINT8
+    err::{ErrorHandler, REPORT}, structure::*, tokenizer::{Token, TokenType, TokenValue}, typing::{self, Type, TypeTable}
 };
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone)]
 pub struct Parser<'a> {
     input: &'a [Token<'a>],
     index: u64,
+    /// Every type the program declares, from the first pass
+    types: &'a TypeTable,
+    /// The type parameters of the generic function being parsed, which
+    /// `type_ident` resolves before the type table
+    type_params: Vec<String>,
     error_handler: &'a ErrorHandler
 }
 
+/// A struct or enum declaration as written.
+struct TypeTokens<'a> {
+    name: Token<'a>,
+    body: TypeBody<'a>,
+}
+
+enum TypeBody<'a> {
+    /// Each field's name and type
+    Struct(Vec<(Token<'a>, TypeName<'a>)>),
+    Enum(Vec<VariantTokens<'a>>),
+}
+
+/// An enum variant as written: its name, its discriminant if it is given
+/// one, and the types of its fields.
+struct VariantTokens<'a> {
+    name: Token<'a>,
+    discriminant: Option<Token<'a>>,
+    fields: Vec<TypeName<'a>>,
+}
+
+/// A field type as written.
+enum TypeName<'a> {
+    /// A builtin type or the name of a struct or enum
+    Named(Token<'a>),
+    /// `*T`
+    Pointer(Box<TypeName<'a>>),
+    /// `[T; N]`
+    Array(Box<TypeName<'a>>, u64),
+    /// `[]T`
+    Slice(Box<TypeName<'a>>),
+}
+
+impl TypeTokens<'_> {
+    /// "Struct" or "Enum", for error messages
+    fn what(&self) -> &'static str {
+        match self.body {
+            TypeBody::Struct(_) => "Struct",
+            TypeBody::Enum(_) => "Enum",
+        }
+    }
+}
+
+/// The parser's first pass: lays out every struct and enum declared in the
+/// program, so that types can be used before their declaration, and adds
+/// them to the builtin types.
+pub fn types(input: &[Token], error_handler: &ErrorHandler) -> TypeTable {
+    let builtin = TypeTable::new();
+    let mut parser = Parser::new(input, &builtin, error_handler);
+    let mut declarations: Vec<TypeTokens> = Vec::new();
+    let mut depth = 0;
+    while let Some(token) = parser.peek() {
+        parser.index += 1;
+        match token.token_type {
+            TokenType::OpenCurly => depth += 1,
+            TokenType::CloseCurly => depth -= 1,
+            TokenType::StructKeyword | TokenType::EnumKeyword if depth > 0 => error_handler.err(
+                token.line,
+                token.column,
+                format!(
+                    "{} must be declared at the top level",
+                    if token.token_type == TokenType::StructKeyword { "Structs" } else { "Enums" }
+                ),
+                None,
+            ),
+            TokenType::StructKeyword | TokenType::EnumKeyword => {
+                let declaration = parser.type_declaration(&token);
+                let name = declaration.name.get_ident();
+                if let Some(first) = declarations.iter().find(|other| other.name.get_ident() == name) {
+                    error_handler.err_with_note(
+                        declaration.name.line,
+                        declaration.name.column,
+                        format!("{} '{}' is already defined", declaration.what(), name),
+                        (first.name.line, first.name.column, format!("'{}' is first defined here", name)),
+                        Some(String::from("Rename one of them")),
+                    );
+                }
+                declarations.push(declaration);
+            }
+            _ => {}
+        }
+    }
+    let mut layout = Layout {
+        declarations: declarations.iter().map(|declaration| (declaration.name.get_ident(), declaration)).collect(),
+        types: builtin.clone(),
+        in_progress: Vec::new(),
+        error_handler,
+    };
+    for declaration in &declarations {
+        layout.resolve(declaration);
+    }
+    layout.types
+}
+
+/// Lays out types in dependency order, since the layout of a struct or
+/// enum depends on those of its fields.
+struct Layout<'s, 't> {
+    declarations: HashMap<String, &'s TypeTokens<'t>>,
+    types: TypeTable,
+    /// Types currently being laid out, to catch ones that contain themselves
+    in_progress: Vec<String>,
+    error_handler: &'s ErrorHandler,
+}
+
+impl<'s, 't> Layout<'s, 't> {
+    fn resolve(&mut self, declaration: &'s TypeTokens<'t>) -> Type {
+        let name = declaration.name.get_ident();
+        if let Some(ty) = self.types.get(&name) {
+            return ty.clone();
+        }
+        if self.in_progress.contains(&name) {
+            let fix = match declaration.body {
+                TypeBody::Struct(_) => "A struct cannot hold itself, not even through another struct",
+                TypeBody::Enum(_) => "An enum cannot hold itself, not even through another type",
+            };
+            self.error_handler.err(
+                declaration.name.line,
+                declaration.name.column,
+                format!("{} '{}' contains itself", declaration.what(), name),
+                Some(String::from(fix)),
+            );
+        }
+        self.in_progress.push(name.clone());
+        let ty = match &declaration.body {
+            TypeBody::Struct(fields) => self.structure(&declaration.name, fields),
+            TypeBody::Enum(variants) => self.enumeration(&declaration.name, variants),
+        };
+        self.in_progress.pop();
+        self.types.insert(ty.clone());
+        ty
+    }
+    fn structure(&mut self, name: &Token<'t>, declared: &'s [(Token<'t>, TypeName<'t>)]) -> Type {
+        let struct_name = name.get_ident();
+        if declared.is_empty() {
+            self.error_handler.err(
+                name.line,
+                name.column,
+                format!("Struct '{}' has no fields", struct_name),
+                None,
+            );
+        }
+        let mut fields: Vec<(String, Type)> = Vec::new();
+        for (field, ty) in declared {
+            let field_name = field.get_ident();
+            if fields.iter().any(|(other, _)| *other == field_name) {
+                self.error_handler.err(
+                    field.line,
+                    field.column,
+                    format!("Duplicate field '{}' in struct '{}'", field_name, struct_name),
+                    None,
+                );
+            }
+            fields.push((field_name, self.field_type(ty)));
+        }
+        Type::structure(&struct_name, fields)
+    }
+    /// Variants without a discriminant get the one after the previous
+    /// variant's, starting from 0.
+    fn enumeration(&mut self, name: &Token<'t>, declared: &'s [VariantTokens<'t>]) -> Type {
+        let enum_name = name.get_ident();
+        if declared.is_empty() {
+            self.error_handler.err(
+                name.line,
+                name.column,
+                format!("Enum '{}' has no variants", enum_name),
+                None,
+            );
+        }
+        let mut variants: Vec<(String, u64, Vec<Type>)> = Vec::new();
+        let mut next: Option<u64> = Some(0);
+        for variant in declared {
+            let variant_name = variant.name.get_ident();
+            if variants.iter().any(|(other, _, _)| *other == variant_name) {
+                self.error_handler.err(
+                    variant.name.line,
+                    variant.name.column,
+                    format!("Duplicate variant '{}' in enum '{}'", variant_name, enum_name),
+                    None,
+                );
+            }
+            let discriminant = match (&variant.discriminant, next) {
+                (Some(Token { value: Some(TokenValue::IntLiteral(value)), .. }), _) => *value,
+                (Some(token), _) => self.error_handler.comperr(
+                    token.line,
+                    token.column,
+                    String::from("expected discriminant token to have value"),
+                    Some(String::from(REPORT)),
+                ),
+                (None, Some(next)) => next,
+                (None, None) => self.error_handler.err(
+                    variant.name.line,
+                    variant.name.column,
+                    format!("Discriminant of '{}' is too large", variant_name),
+                    Some(format!("The previous variant has the largest discriminant, {}", u64::MAX)),
+                ),
+            };
+            if let Some((other, _, _)) = variants.iter().find(|(_, other, _)| *other == discriminant) {
+                self.error_handler.err(
+                    variant.name.line,
+                    variant.name.column,
+                    format!("Variant '{}' has the same discriminant as '{}' ({})", variant_name, other, discriminant),
+                    Some(String::from("Give each variant a different value")),
+                );
+            }
+            next = discriminant.checked_add(1);
+            let fields = variant.fields.iter().map(|ty| self.field_type(ty)).collect();
+            variants.push((variant_name, discriminant, fields));
+        }
+        Type::enumeration(&enum_name, variants)
+    }
+    /// A pointer or slice field does not need the layout of what it points
+    /// to, so a struct may point to itself.
+    fn field_type(&mut self, ty: &TypeName<'t>) -> Type {
+        match ty {
+            TypeName::Named(token) => match &token.value {
+                Some(TokenValue::TypeIdent(builtin)) => (*builtin).clone(),
+                Some(TokenValue::Ident(other)) => match self.declarations.get(other) {
+                    Some(other) => self.resolve(other),
+                    None => self.unknown(token, other),
+                },
+                _ => self.no_value(token),
+            },
+            TypeName::Pointer(to) => typing::pointer(&self.spelling(to)).clone(),
+            TypeName::Slice(of) => typing::slice(&self.spelling(of)).clone(),
+            TypeName::Array(of, length) => typing::array(&self.field_type(of), *length).clone(),
+        }
+    }
+    /// The name of a type as written, checking that it exists without
+    /// laying it out.
+    fn spelling(&mut self, ty: &TypeName<'t>) -> String {
+        match ty {
+            TypeName::Named(token) => match &token.value {
+                Some(TokenValue::TypeIdent(builtin)) => builtin.name().to_string(),
+                Some(TokenValue::Ident(other)) if self.declarations.contains_key(other) => other.clone(),
+                Some(TokenValue::Ident(other)) => self.unknown(token, other),
+                _ => self.no_value(token),
+            },
+            TypeName::Pointer(to) => format!("*{}", self.spelling(to)),
+            TypeName::Slice(of) => format!("[]{}", self.spelling(of)),
+            TypeName::Array(of, length) => format!("[{}; {}]", self.spelling(of), length),
+        }
+    }
+    fn unknown(&self, token: &Token<'t>, name: &str) -> ! {
+        self.error_handler.err(
+            token.line,
+            token.column,
+            format!("Unknown type '{}'", name),
+            None,
+        )
+    }
+    fn no_value(&self, token: &Token<'t>) -> ! {
+        self.error_handler.comperr(
+            token.line,
+            token.column,
+            String::from("expected field type token to have value"),
+            Some(String::from(REPORT)),
+        )
+    }
+}
+
 impl<'a> Parser<'a> {
-    pub fn new(input: &'a [Token<'a>], error_handler: &'a ErrorHandler) -> Self {
-        Self { input, index: 0, error_handler }
+    pub fn new(input: &'a [Token<'a>], types: &'a TypeTable, error_handler: &'a ErrorHandler) -> Self {
+        Self { input, index: 0, types, type_params: Vec::new(), error_handler }
     }
     fn peek(&self) -> Option<Token<'a>> {
         self.input.get(self.index as usize).cloned()
     }
+    fn expression(&mut self) -> Expression<'a> {
+        self.binary(1)
+    }
+    /// Precedence climbing: parses operators that bind at least as tightly as `min_precedence`.
+    fn binary(&mut self, min_precedence: u8) -> Expression<'a> {
+        let mut lhs = self.cast();
+        while let Some(token) = self.peek() {
+            let operator = match token.token_type {
+                TokenType::Plus => BinaryOperator::Add,
+                TokenType::Minus => BinaryOperator::Subtract,
+                TokenType::Star => BinaryOperator::Multiply,
+                TokenType::Slash => BinaryOperator::Divide,
+                TokenType::Percent => BinaryOperator::Remainder,
+                _ => break,
+            };
+            if operator.precedence() < min_precedence {
+                break;
+            }
+            self.index += 1;
+            let rhs = self.binary(operator.precedence() + 1);
+            lhs = Expression::new(
+                ExpressionContent::Binary(operator, Box::new(lhs), Box::new(rhs)),
+                token.line,
+                token.column,
+            );
+        }
+        lhs
+    }
+    /// Parses a value followed by any number of `as type`s, which bind more
+    /// tightly than any binary operator.
+    fn cast(&mut self) -> Expression<'a> {
+        let mut value = self.postfix();
+        while let Some(token) = self.peek().filter(|next| next.token_type == TokenType::AsKeyword) {
+            self.index += 1;
+            let ty = self.type_ident("type after 'as'");
+            value = Expression::new(ExpressionContent::Cast(Box::new(value), ty), token.line, token.column);
+        }
+        value
+    }
+    /// Parses a primary expression followed by any number of `.field`s,
+    /// `[index]`es and `[start..end]` slices.
+    fn postfix(&mut self) -> Expression<'a> {
+        let mut value = self.primary();
+        while let Some(token) = self.peek() {
+            match token.token_type {
+                TokenType::Dot => {
+                    self.index += 1;
+                    let field = self.expect(TokenType::Ident, "field name after '.'");
+                    value = Expression::new(
+                        ExpressionContent::FieldAccess(Box::new(value), field.get_ident()),
+                        field.line,
+                        field.column,
+                    );
+                }
+                TokenType::OpenSquare => {
+                    self.index += 1;
+                    let content = self.index_or_slice(value);
+                    value = Expression::new(content, token.line, token.column);
+                }
+                _ => break,
+            }
+        }
+        value
+    }
+    /// Parses `index]` or `start..end]` after the opening bracket.
+    fn index_or_slice(&mut self, value: Expression<'a>) -> ExpressionContent<'a> {
+        let is = |parser: &Self, token_type: TokenType| parser.peek().is_some_and(|next| next.token_type == token_type);
+        let start = if is(self, TokenType::DotDot) { None } else { Some(Box::new(self.expression())) };
+        if let Some(index) = start.clone().filter(|_| is(self, TokenType::CloseSquare)) {
+            self.index += 1;
+            return ExpressionContent::Index(Box::new(value), index);
+        }
+        self.expect(TokenType::DotDot, "']' or '..'");
+        let end = if is(self, TokenType::CloseSquare) { None } else { Some(Box::new(self.expression())) };
+        self.expect(TokenType::CloseSquare, "']' after slice");
+        ExpressionContent::Slice(Box::new(value), start, end)
+    }
+    /// Parses `field: expr, ... }` after the opening curly brace of a struct literal.
+    fn field_inits(&mut self) -> Vec<FieldInit<'a>> {
+        let mut fields: Vec<FieldInit<'a>> = Vec::new();
+        loop {
+            let name = self.expect_one_of(&[TokenType::Ident, TokenType::CloseCurly], "field name or '}'");
+            if name.token_type == TokenType::CloseCurly {
+                return fields;
+            }
+            self.expect(TokenType::Colon, "':' after field name");
+            let value = self.expression();
+            fields.push(FieldInit { name: name.get_ident(), value, line: name.line, column: name.column });
+            let separator = self.expect_one_of(&[TokenType::Comma, TokenType::CloseCurly], "',' or '}'");
+            if separator.token_type == TokenType::CloseCurly {
+                return fields;
+            }
+        }
+    }
+    /// Parses the pattern of a match arm.
+    fn pattern(&mut self) -> Pattern {
+        let token = self.expect_one_of(
+            &[TokenType::Ident, TokenType::IntLiteral, TokenType::CharLiteral, TokenType::TrueKeyword, TokenType::FalseKeyword],
+            "pattern",
+        );
+        if let TokenType::TrueKeyword | TokenType::FalseKeyword = token.token_type {
+            return Pattern::Bool(token.token_type == TokenType::TrueKeyword);
+        }
+        if token.token_type != TokenType::Ident {
+            let low = self.literal(&token);
+            if let Some(dots) = self.peek().filter(|next| next.token_type == TokenType::DotDot) {
+                self.error_handler.err(
+                    dots.line,
+                    dots.column,
+                    String::from("Expected '..='"),
+                    Some(String::from("Ranges include both ends and are written `low..=high`")),
+                );
+            }
+            if self.peek().is_none_or(|next| next.token_type != TokenType::DotDotEquals) {
+                return Pattern::Int(low);
+            }
+            self.index += 1;
+            let end = self.expect_one_of(&[TokenType::IntLiteral, TokenType::CharLiteral], "end of range after '..='");
+            return Pattern::Range(low, self.literal(&end));
+        }
+        let name = token.get_ident();
+        if name == "_" {
+            return Pattern::Wildcard;
+        }
+        if self.peek().is_none_or(|next| next.token_type != TokenType::DoubleColon) {
+            return Pattern::Binding(name);
+        }
+        self.index += 1;
+        let variant = self.expect(TokenType::Ident, "variant name after '::'");
+        let mut bindings: Vec<String> = Vec::new();
+        if self.peek().is_some_and(|next| next.token_type == TokenType::OpenParen) {
+            self.index += 1;
+            loop {
+                let binding = self.expect_one_of(&[TokenType::Ident, TokenType::CloseParen], "name or ')'");
+                if binding.token_type == TokenType::CloseParen {
+                    break;
+                }
+                bindings.push(binding.get_ident());
+                let separator = self.expect_one_of(&[TokenType::Comma, TokenType::CloseParen], "',' or ')'");
+                if separator.token_type == TokenType::CloseParen {
+                    break;
+                }
+            }
+        }
+        Pattern::Variant(name, variant.get_ident(), bindings)
+    }
+    /// The value of an integer or character literal token.
+    fn literal(&self, token: &Token<'a>) -> u64 {
+        match token.value {
+            Some(TokenValue::IntLiteral(value)) => value,
+            // The tokenizer only accepts ASCII characters, which fit in a byte
+            Some(TokenValue::CharLiteral(value)) => value as u64,
+            _ => self.error_handler.comperr(
+                token.line,
+                token.column,
+                String::from("expected literal token to have value"),
+                Some(String::from(REPORT)),
+            ),
+        }
+    }
+    /// Parses `expr, ...)` after the opening parenthesis of a call.
+    fn args(&mut self) -> Vec<Expression<'a>> {
+        let mut args: Vec<Expression<'a>> = Vec::new();
+        if self.peek().is_some_and(|token| token.token_type == TokenType::CloseParen) {
+            self.index += 1;
+            return args;
+        }
+        loop {
+            args.push(self.expression());
+            let separator = self.expect_one_of(&[TokenType::Comma, TokenType::CloseParen], "',' or ')'");
+            if separator.token_type == TokenType::CloseParen {
+                return args;
+            }
+        }
+    }
+    fn primary(&mut self) -> Expression<'a> {
+        let Some(token) = self.peek() else {
+            let last = self.input.last().unwrap();
+            self.error_handler.err(
+                last.line,
+                last.column,
+                String::from("Unexpected EOF (expected expression)"),
+                None,
+            );
+        };
+        self.index += 1;
+        match token.token_type {
+            TokenType::IntLiteral => {
+                let Some(TokenValue::IntLiteral(value)) = token.value else {
+                    self.error_handler.comperr(
+                        token.line,
+                        token.column,
+                        String::from("expected integer literal token to have value"),
+                        None,
+                    );
+                };
+                Expression::new(ExpressionContent::IntLiteral(value), token.line, token.column)
+            }
+            TokenType::StrLiteral => {
+                let Some(TokenValue::StrLiteral(value)) = token.value else {
+                    self.error_handler.comperr(
+                        token.line,
+                        token.column,
+                        String::from("expected string literal token to have value"),
+                        Some(String::from(REPORT)),
+                    );
+                };
+                Expression::new(ExpressionContent::StrLiteral(value), token.line, token.column)
+            }
+            TokenType::CharLiteral => {
+                let Some(TokenValue::CharLiteral(value)) = token.value else {
+                    self.error_handler.comperr(
+                        token.line,
+                        token.column,
+                        String::from("expected character literal token to have value"),
+                        Some(String::from(REPORT)),
+                    );
+                };
+                // The tokenizer only accepts ASCII characters, which fit in a byte
+                Expression::new(ExpressionContent::IntLiteral(value as u64), token.line, token.column)
+            }
+            TokenType::Ident => {
+                let name = token.get_ident();
+                if self.peek().is_some_and(|next| next.token_type == TokenType::OpenParen) {
+                    self.index += 1;
+                    let args = self.args();
+                    Expression::new(ExpressionContent::Call(name, args), token.line, token.column)
+                } else if self.peek().is_some_and(|next| next.token_type == TokenType::DoubleColon) {
+                    self.index += 1;
+                    let Some(ty) = self.types.get(&name) else {
+                        self.error_handler.err(
+                            token.line,
+                            token.column,
+                            format!("Unknown type '{}'", name),
+                            None,
+                        );
+                    };
+                    let variant = self.expect(TokenType::Ident, "variant name after '::'");
+                    let mut values: Vec<Expression<'a>> = Vec::new();
+                    if self.peek().is_some_and(|next| next.token_type == TokenType::OpenParen) {
+                        self.index += 1;
+                        values = self.args();
+                    }
+                    let mut literal = Expression::new(
+                        ExpressionContent::Variant(name, variant.get_ident(), values),
+                        token.line,
+                        token.column,
+                    );
+                    literal.eval_type = Some(ty);
+                    literal
+                } else if let Some(ty) = self
+                    .types
+                    .get(&name)
+                    .filter(|_| self.peek().is_some_and(|next| next.token_type == TokenType::OpenCurly))
+                {
+                    self.index += 1;
+                    let fields = self.field_inits();
+                    let mut literal = Expression::new(ExpressionContent::StructLiteral(name, fields), token.line, token.column);
+                    literal.eval_type = Some(ty);
+                    literal
+                } else {
+                    Expression::new(ExpressionContent::Variable(name), token.line, token.column)
+                }
+            }
+            TokenType::MatchKeyword => {
+                let value = self.expression();
+                self.expect(TokenType::OpenCurly, "'{' after the value to match");
+                let mut arms: Vec<MatchArm<'a>> = Vec::new();
+                loop {
+                    if self.peek().is_some_and(|next| next.token_type == TokenType::CloseCurly) {
+                        self.index += 1;
+                        break;
+                    }
+                    let start = self.peek();
+                    let pattern = self.pattern();
+                    // The pattern took a token, so there was one
+                    let start = start.unwrap();
+                    self.expect(TokenType::FatArrow, "'=>' after pattern");
+                    let value = self.expression();
+                    arms.push(MatchArm { pattern, value, line: start.line, column: start.column });
+                    let separator = self.expect_one_of(&[TokenType::Comma, TokenType::CloseCurly], "',' or '}'");
+                    if separator.token_type == TokenType::CloseCurly {
+                        break;
+                    }
+                }
+                Expression::new(ExpressionContent::Match(Box::new(value), arms), token.line, token.column)
+            }
+            TokenType::Ampersand => {
+                let name = self.expect(TokenType::Ident, "variable name after '&'");
+                Expression::new(ExpressionContent::AddressOf(name.get_ident()), token.line, token.column)
+            }
+            // Binds looser than `.field`, so `*p.x` reads through the pointer in `p.x`
+            TokenType::Star => {
+                let pointer = self.postfix();
+                Expression::new(ExpressionContent::Deref(Box::new(pointer)), token.line, token.column)
+            }
+            TokenType::NullKeyword => Expression::new(ExpressionContent::Null, token.line, token.column),
+            TokenType::TrueKeyword | TokenType::FalseKeyword => {
+                let value = token.token_type == TokenType::TrueKeyword;
+                Expression::new(ExpressionContent::BoolLiteral(value), token.line, token.column)
+            }
+            TokenType::OpenSquare => {
+                let mut values: Vec<Expression<'a>> = Vec::new();
+                loop {
+                    values.push(self.expression());
+                    let separator = self.expect_one_of(&[TokenType::Comma, TokenType::CloseSquare], "',' or ']'");
+                    if separator.token_type == TokenType::CloseSquare {
+                        break;
+                    }
+                }
+                Expression::new(ExpressionContent::ArrayLiteral(values), token.line, token.column)
+            }
+            TokenType::OpenParen => {
+                let inner = self.expression();
+                match self.peek() {
+                    Some(close) if close.token_type == TokenType::CloseParen => {
+                        self.index += 1;
+                        inner
+                    }
+                    Some(other) => self.error_handler.err(
+                        other.line,
+                        other.column,
+                        format!("Expected closing parentheses (found {})", other),
+                        None,
+                    ),
+                    None => self.error_handler.err(
+                        token.line,
+                        token.column,
+                        String::from("Unexpected EOF (unclosed parentheses)"),
+                        None,
+                    ),
+                }
+            }
+            _ => self.error_handler.err(
+                token.line,
+                token.column,
+                format!("Expected expression (found {})", token),
+                None,
+            ),
+        }
+    }
     pub fn parse<'b>(&mut self) -> Scope<'b> where 'a: 'b {
         let mut parsed = Scope {
             children: Vec::new()
@@ -31,24 +638,15 @@ impl<'a> Parser<'a> {
             match token.token_type {
                 TokenType::FunctionKeyword => {current.push(AstNodeType::fk(token.line, token.column));}
                 TokenType::FunctionIdent => {current.push(AstNode::fi(token.get_funcid(), token.line, token.column));}
+                TokenType::OpenAngle if current.children.last().is_some_and(|node| node.node_type == AstNodeType::FunctionIdent) => {
+                    // `<T, U>` after the name of a generic function
+                    let names = self.type_params();
+                    current.push(AstNode::type_params(names, token.line, token.column));
+                }
                 TokenType::OpenParen => {
                     // Assume function parameter tuple
-                    // Functions do not yet have parameters
-                    let next_token = self.input.get(self.index as usize).cloned();
-                    self.index += 1;
-                    if let Some(some_token) = next_token {
-                        if let TokenType::CloseParen = some_token.token_type {
-                            current.push(AstNode::tup(token.line, token.column));
-                        }
-                        else {
-                            self.error_handler.err(
-                                some_token.line,
-                                some_token.column,
-                                format!("Expected closing parentheses (found {})", some_token),
-                                None,
-                            )
-                        }
-                    }
+                    let params = self.params();
+                    current.push(AstNode::tup(params, token.line, token.column));
                 }
                 TokenType::CloseParen => {
                     self.error_handler.err(
@@ -62,11 +660,13 @@ impl<'a> Parser<'a> {
                     // Expect following TypeIdent
                     let next_token = self.peek();
                     if let Some(some_token) = next_token {
-                        if let TokenType::TypeIdent = some_token.token_type {
-                            if let TokenValue::TypeIdent(ident) = some_token.value.unwrap() {
-                                current.push(AstNode::ti(ident, token.line, token.column));
-                                self.index += 1;
-                            }
+                        if let TokenType::OpenParen = some_token.token_type {
+                            // `-> ()` returns nothing, like leaving out the return type
+                            self.index += 1;
+                            self.expect(TokenType::CloseParen, "')' after '(' in return type");
+                        } else if let TokenType::TypeIdent | TokenType::Ident | TokenType::Star | TokenType::OpenSquare = some_token.token_type {
+                            let ty = self.type_ident("type identifier after ->");
+                            current.push(AstNode::ti(ty, token.line, token.column));
                         } else {
                             self.error_handler.err(
                                 some_token.line,
@@ -110,52 +710,485 @@ impl<'a> Parser<'a> {
                                 String::from("Unexpected EOF while parsing scope"),
                                 None,
                             );
-                            break;
                         }
                     }
                     // Exclude the last CloseCurly
                     let end_index: usize = self.index as usize - 1;
                     let scope_tokens_slice: &'b [Token<'b>] = &self.input[start_index..end_index];
-                    let mut inner_parser = Parser::<'b>::new(scope_tokens_slice, self.error_handler);
+                    let mut inner_parser = Parser::<'b>::new(scope_tokens_slice, self.types, self.error_handler);
+                    inner_parser.type_params = self.type_params.clone();
                     let inner_scope = inner_parser.parse();
                     current.push(AstNode::scope(inner_scope.clone(), token.line, token.column));
 
                     // Don't handle pro-scope tokens yet
+                    self.end_statement(&current);
                     parsed.children.push(current);
                     current = Statement::new();
                 }
                 TokenType::ReturnKeyword => {
-                    let next_token = self.peek();
-                    if let Some(some_token) = next_token {
-                        if let TokenType::IntLiteral = some_token.token_type {
-                            if let TokenValue::IntLiteral(value) = some_token.value.unwrap() {
-                                current.push(AstNode::ret(value as u8, token.line, token.column));
-                                self.index += 1;
-                            }
-                        } else {
+                    match self.peek() {
+                        Some(some_token) if some_token.token_type == TokenType::Semicolon => {
                             self.error_handler.err(
                                 some_token.line,
                                 some_token.column,
-                                String::from("Expected integer literal after return keyword"),
+                                String::from("Expected expression after return keyword"),
                                 None,
                             );
                         }
-                    } else {
+                        Some(_) => {
+                            let expression = self.expression();
+                            current.push(AstNode::ret(expression, token.line, token.column));
+                        }
+                        None => {
+                            self.error_handler.err(
+                                token.line,
+                                token.column,
+                                String::from("Unexpected EOF (expected statement end after return)"),
+                                None,
+                            );
+                        }
+                    }
+                }
+                TokenType::ConstKeyword => {
+                    let name = self.expect(TokenType::Ident, "constant name");
+                    self.expect(TokenType::Colon, "':' after constant name");
+                    let const_type = self.type_ident("constant type");
+                    self.expect(TokenType::Equals, "'=' after constant type");
+                    let value = self.expression();
+                    current.push(AstNode::constant(
+                        ConstDeclaration {
+                            name: name.get_ident(),
+                            const_type,
+                            value,
+                            line: name.line,
+                            column: name.column,
+                        },
+                        token.line,
+                        token.column,
+                    ));
+                }
+                TokenType::LetKeyword => {
+                    let name = self.expect(TokenType::Ident, "binding name after let");
+                    let binding_type = match self.peek() {
+                        Some(next) if next.token_type == TokenType::Colon => {
+                            self.index += 1;
+                            Some(self.type_ident("binding type"))
+                        }
+                        _ => None,
+                    };
+                    self.expect(TokenType::Equals, "'=' after binding name");
+                    let value = self.expression();
+                    current.push(AstNode::binding(
+                        LetBinding { name: name.get_ident(), binding_type, value, line: name.line, column: name.column },
+                        token.line,
+                        token.column,
+                    ));
+                }
+                TokenType::StaticKeyword => {
+                    let mutable = self.peek().is_some_and(|next| next.token_type == TokenType::MutKeyword);
+                    if mutable {
+                        self.index += 1;
+                    }
+                    let name = self.expect(TokenType::Ident, "static name");
+                    self.expect(TokenType::Colon, "':' after static name");
+                    let static_type = self.type_ident("static type");
+                    let value = match self.peek() {
+                        Some(next) if next.token_type == TokenType::Equals => {
+                            self.index += 1;
+                            Some(self.expression())
+                        }
+                        _ => None,
+                    };
+                    current.push(AstNode::stat(
+                        StaticDeclaration {
+                            name: name.get_ident(),
+                            mutable,
+                            static_type,
+                            value,
+                            line: name.line,
+                            column: name.column,
+                        },
+                        token.line,
+                        token.column,
+                    ));
+                }
+                TokenType::ExternKeyword => {
+                    // `extern "C" f name(params) -> type;`
+                    let abi = self.expect(TokenType::StrLiteral, "ABI string after extern, e.g. \"C\"");
+                    let Some(TokenValue::StrLiteral(abi_name)) = abi.value else {
+                        self.error_handler.comperr(
+                            abi.line,
+                            abi.column,
+                            String::from("expected string literal token to have value"),
+                            Some(String::from(REPORT)),
+                        );
+                    };
+                    if abi_name != "C" {
+                        self.error_handler.err(
+                            abi.line,
+                            abi.column,
+                            format!("Unsupported ABI {:?}", abi_name),
+                            Some(String::from("Only extern \"C\" is supported")),
+                        );
+                    }
+                    // Only checked here; the declaration itself is parsed like any function
+                    self.expect(TokenType::FunctionKeyword, "function declaration after extern");
+                    self.index -= 1;
+                    current.push(AstNode::ext(abi_name, token.line, token.column));
+                }
+                TokenType::ExportKeyword => {
+                    // `export f name(params) -> type { ... }`
+                    self.expect(TokenType::FunctionKeyword, "function definition after export");
+                    self.index -= 1;
+                    current.push(AstNodeType::export(token.line, token.column));
+                }
+                TokenType::Attribute => {
+                    // `#[name]`, applying to the function after it
+                    if current.children.iter().any(|node| node.node_type != AstNodeType::Attribute) {
                         self.error_handler.err(
                             token.line,
                             token.column,
-                            String::from("Unexpected EOF (expected statement end after return)"),
+                            String::from("Attributes must come before the function they apply to"),
                             None,
                         );
                     }
+                    self.expect_one_of(
+                        &[TokenType::Attribute, TokenType::FunctionKeyword, TokenType::ExportKeyword, TokenType::ExternKeyword],
+                        "function after attribute",
+                    );
+                    self.index -= 1;
+                    current.push(AstNode::attr(token.get_ident(), token.line, token.column));
+                }
+                TokenType::StructKeyword | TokenType::EnumKeyword if current.children.is_empty() => {
+                    // Laid out by the first pass already; this only records where it was declared
+                    let declaration = self.type_declaration(&token);
+                    let Some(ty) = self.types.get(&declaration.name.get_ident()) else {
+                        self.error_handler.comperr(
+                            token.line,
+                            token.column,
+                            format!("type '{}' missing from the type table", declaration.name.get_ident()),
+                            Some(String::from(REPORT)),
+                        );
+                    };
+                    match declaration.body {
+                        TypeBody::Struct(_) => current.push(AstNode::structure(ty, token.line, token.column)),
+                        TypeBody::Enum(_) => current.push(AstNode::enumeration(ty, token.line, token.column)),
+                    }
+                    parsed.children.push(current);
+                    current = Statement::new();
+                }
+                TokenType::AsmKeyword => {
+                    let block = self.asm_block();
+                    current.push(AstNode::asm(block, token.line, token.column));
+                    // Like a scope, the block ends the statement
+                    parsed.children.push(current);
+                    current = Statement::new();
                 }
                 TokenType::Semicolon => {
+                    self.end_statement(&current);
                     parsed.children.push(current);
                     current = Statement::new();
                 }
-                _ => {}
+                TokenType::MatchKeyword if current.children.is_empty() => {
+                    // A match for its arms' effects, e.g. calls
+                    self.index -= 1;
+                    let expression = self.expression();
+                    current.push(AstNode::expr(expression, token.line, token.column));
+                }
+                TokenType::Ident | TokenType::Star if current.children.is_empty() => {
+                    // Expression statement, e.g. a call, or an assignment
+                    self.index -= 1;
+                    let expression = self.expression();
+                    match self.peek() {
+                        Some(equals) if equals.token_type == TokenType::Equals => {
+                            self.index += 1;
+                            if !matches!(
+                                expression.content,
+                                ExpressionContent::Variable(_) | ExpressionContent::Deref(_) | ExpressionContent::Index(..)
+                            ) {
+                                self.error_handler.err(
+                                    equals.line,
+                                    equals.column,
+                                    String::from("Can only assign to a variable, through a pointer or to an element"),
+                                    None,
+                                );
+                            }
+                            let value = self.expression();
+                            current.push(AstNode::assign(Assignment { target: expression, value }, token.line, token.column));
+                        }
+                        _ => current.push(AstNode::expr(expression, token.line, token.column)),
+                    }
+                }
+                _ => {
+                    self.error_handler.err(
+                        token.line,
+                        token.column,
+                        format!("Unexpected {}", token),
+                        None,
+                    );
+                }
             }
         }
+        if let Some(last) = current.children.last() {
+            self.error_handler.err(
+                last.line,
+                last.column,
+                String::from("Expected ';' after statement"),
+                None,
+            );
+        }
         parsed
     }
+    /// Forgets the type parameters of a generic function once its statement ends.
+    fn end_statement(&mut self, statement: &Statement) {
+        if statement.children.iter().any(|node| node.node_type == AstNodeType::TypeParams) {
+            self.type_params.clear();
+        }
+    }
+    /// Parses `T, ...>` after the `<` of a generic function, which the
+    /// types in its signature and body can then name.
+    fn type_params(&mut self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        loop {
+            let name = self.expect(TokenType::Ident, "type parameter name");
+            let ident = name.get_ident();
+            if names.contains(&ident) {
+                self.error_handler.err(
+                    name.line,
+                    name.column,
+                    format!("Type parameter '{}' is already declared", ident),
+                    Some(String::from("Rename one of them")),
+                );
+            }
+            if self.types.get(&ident).is_some() {
+                self.error_handler.err(
+                    name.line,
+                    name.column,
+                    format!("Type parameter '{}' has the name of a type", ident),
+                    Some(String::from("Rename the type parameter")),
+                );
+            }
+            names.push(ident);
+            let separator = self.expect_one_of(&[TokenType::Comma, TokenType::CloseAngle], "',' or '>'");
+            if separator.token_type == TokenType::CloseAngle {
+                self.type_params = names.clone();
+                return names;
+            }
+        }
+    }
+    /// Parses `name type, ...)` after the opening parenthesis of a parameter list.
+    fn params(&mut self) -> Vec<Param<'a>> {
+        let mut params: Vec<Param<'a>> = Vec::new();
+        if self.peek().is_some_and(|token| token.token_type == TokenType::CloseParen) {
+            self.index += 1;
+            return params;
+        }
+        loop {
+            let name = self.expect(TokenType::Ident, "parameter name");
+            let param_type = self.type_ident("parameter type");
+            params.push(Param { name: name.get_ident(), param_type, line: name.line, column: name.column });
+            let separator = self.expect_one_of(&[TokenType::Comma, TokenType::CloseParen], "',' or ')'");
+            if separator.token_type == TokenType::CloseParen {
+                return params;
+            }
+        }
+    }
+    /// Parses `{ ... }` after the `asm` keyword: template lines as string
+    /// literals, then `in name = value;` and `out target;` bindings, in any order.
+    fn asm_block(&mut self) -> AsmBlock<'a> {
+        self.expect(TokenType::OpenCurly, "'{' after asm");
+        let mut block = AsmBlock { template: Vec::new(), inputs: Vec::new(), outputs: Vec::new() };
+        loop {
+            let token = self.expect_one_of(
+                &[TokenType::StrLiteral, TokenType::Ident, TokenType::CloseCurly],
+                "assembly string, 'in', 'out' or '}'",
+            );
+            match (&token.token_type, &token.value) {
+                (TokenType::CloseCurly, _) => return block,
+                (TokenType::StrLiteral, Some(TokenValue::StrLiteral(line))) => block.template.push(line.clone()),
+                (TokenType::Ident, Some(TokenValue::Ident(binding))) if binding == "in" => {
+                    let name = self.expect(TokenType::Ident, "operand name after 'in'");
+                    self.expect(TokenType::Equals, "'=' after operand name");
+                    let value = self.expression();
+                    block.inputs.push(AsmInput { name: name.get_ident(), value, line: name.line, column: name.column });
+                }
+                (TokenType::Ident, Some(TokenValue::Ident(binding))) if binding == "out" => {
+                    let target = self.expect(TokenType::Ident, "variable name after 'out'");
+                    block.outputs.push(AsmOutput { target: target.get_ident(), line: target.line, column: target.column });
+                }
+                _ => self.error_handler.err(
+                    token.line,
+                    token.column,
+                    format!("Expected assembly string, 'in', 'out' or '}}' (found {})", token),
+                    None,
+                ),
+            }
+            self.expect(TokenType::Semicolon, "';' in asm block");
+        }
+    }
+    /// Parses a struct or enum declaration after its keyword.
+    fn type_declaration(&mut self, keyword: &Token<'a>) -> TypeTokens<'a> {
+        if keyword.token_type == TokenType::EnumKeyword {
+            self.enum_declaration()
+        } else {
+            self.struct_declaration()
+        }
+    }
+    /// Parses `Name { field type, ... }` after the `struct` keyword.
+    fn struct_declaration(&mut self) -> TypeTokens<'a> {
+        let name = self.expect(TokenType::Ident, "struct name");
+        self.expect(TokenType::OpenCurly, "'{' after struct name");
+        let mut fields: Vec<(Token<'a>, TypeName<'a>)> = Vec::new();
+        loop {
+            let field = self.expect_one_of(&[TokenType::Ident, TokenType::CloseCurly], "field name or '}'");
+            if field.token_type == TokenType::CloseCurly {
+                return TypeTokens { name, body: TypeBody::Struct(fields) };
+            }
+            let ty = self.type_name("field type");
+            fields.push((field, ty));
+            let separator = self.expect_one_of(&[TokenType::Comma, TokenType::CloseCurly], "',' or '}'");
+            if separator.token_type == TokenType::CloseCurly {
+                return TypeTokens { name, body: TypeBody::Struct(fields) };
+            }
+        }
+    }
+    /// Parses `Name { Variant, Variant(type, ...), Variant = value, ... }`
+    /// after the `enum` keyword.
+    fn enum_declaration(&mut self) -> TypeTokens<'a> {
+        let name = self.expect(TokenType::Ident, "enum name");
+        self.expect(TokenType::OpenCurly, "'{' after enum name");
+        let mut variants: Vec<VariantTokens<'a>> = Vec::new();
+        loop {
+            let variant = self.expect_one_of(&[TokenType::Ident, TokenType::CloseCurly], "variant name or '}'");
+            if variant.token_type == TokenType::CloseCurly {
+                return TypeTokens { name, body: TypeBody::Enum(variants) };
+            }
+            let mut fields: Vec<TypeName<'a>> = Vec::new();
+            if self.peek().is_some_and(|token| token.token_type == TokenType::OpenParen) {
+                self.index += 1;
+                loop {
+                    if self.peek().is_some_and(|token| token.token_type == TokenType::CloseParen) {
+                        self.index += 1;
+                        break;
+                    }
+                    fields.push(self.type_name("field type or ')'"));
+                    let separator = self.expect_one_of(&[TokenType::Comma, TokenType::CloseParen], "',' or ')'");
+                    if separator.token_type == TokenType::CloseParen {
+                        break;
+                    }
+                }
+            }
+            let mut discriminant = None;
+            if self.peek().is_some_and(|token| token.token_type == TokenType::Equals) {
+                self.index += 1;
+                discriminant = Some(self.expect(TokenType::IntLiteral, "discriminant after '='"));
+            }
+            variants.push(VariantTokens { name: variant, discriminant, fields });
+            let separator = self.expect_one_of(&[TokenType::Comma, TokenType::CloseCurly], "',' or '}'");
+            if separator.token_type == TokenType::CloseCurly {
+                return TypeTokens { name, body: TypeBody::Enum(variants) };
+            }
+        }
+    }
+    /// Parses a field type in a struct or enum declaration, which is only
+    /// looked up once every declaration has been seen.
+    fn type_name(&mut self, what: &str) -> TypeName<'a> {
+        let token = self.expect_one_of(&[TokenType::TypeIdent, TokenType::Ident, TokenType::Star, TokenType::OpenSquare], what);
+        match token.token_type {
+            TokenType::Star => TypeName::Pointer(Box::new(self.type_name(what))),
+            TokenType::OpenSquare if self.peek().is_some_and(|next| next.token_type == TokenType::CloseSquare) => {
+                self.index += 1;
+                TypeName::Slice(Box::new(self.type_name(what)))
+            }
+            TokenType::OpenSquare => {
+                let element = self.type_name(what);
+                TypeName::Array(Box::new(element), self.array_length())
+            }
+            _ => TypeName::Named(token),
+        }
+    }
+    /// Parses `; N]` after the element type of an array type.
+    fn array_length(&mut self) -> u64 {
+        self.expect(TokenType::Semicolon, "';' after array element type");
+        let length = self.expect(TokenType::IntLiteral, "array length after ';'");
+        self.expect(TokenType::CloseSquare, "']' after array length");
+        match length.value {
+            Some(TokenValue::IntLiteral(0)) => self.error_handler.err(
+                length.line,
+                length.column,
+                String::from("Arrays cannot be empty"),
+                None,
+            ),
+            Some(TokenValue::IntLiteral(value)) => value,
+            _ => self.error_handler.comperr(
+                length.line,
+                length.column,
+                String::from("expected array length token to have value"),
+                Some(String::from(REPORT)),
+            ),
+        }
+    }
+    /// Parses a builtin type, the name of a struct or enum or a type
+    /// parameter, or `*` and the type pointed to, `[T; N]` or `[]T`.
+    fn type_ident(&mut self, what: &str) -> &'a Type {
+        if self.peek().is_some_and(|token| token.token_type == TokenType::Star) {
+            self.index += 1;
+            return typing::pointer(self.type_ident(what).name());
+        }
+        if self.peek().is_some_and(|token| token.token_type == TokenType::OpenSquare) {
+            self.index += 1;
+            if self.peek().is_some_and(|token| token.token_type == TokenType::CloseSquare) {
+                self.index += 1;
+                return typing::slice(self.type_ident(what).name());
+            }
+            let element = self.type_ident(what);
+            return typing::array(element, self.array_length());
+        }
+        let token = self.expect_one_of(&[TokenType::TypeIdent, TokenType::Ident], what);
+        match token.value {
+            Some(TokenValue::TypeIdent(ty)) => ty,
+            Some(TokenValue::Ident(name)) if self.type_params.contains(&name) => typing::parameter(&name),
+            Some(TokenValue::Ident(name)) => match self.types.get(&name) {
+                Some(ty) => ty,
+                None => self.error_handler.err(
+                    token.line,
+                    token.column,
+                    format!("Unknown type '{}'", name),
+                    None,
+                ),
+            },
+            _ => self.error_handler.comperr(
+                token.line,
+                token.column,
+                String::from("expected type identifier token to have value"),
+                Some(String::from(REPORT)),
+            ),
+        }
+    }
+    fn expect(&mut self, token_type: TokenType, what: &str) -> Token<'a> {
+        self.expect_one_of(&[token_type], what)
+    }
+    fn expect_one_of(&mut self, token_types: &[TokenType], what: &str) -> Token<'a> {
+        match self.peek() {
+            Some(token) if token_types.contains(&token.token_type) => {
+                self.index += 1;
+                token
+            }
+            Some(token) => self.error_handler.err(
+                token.line,
+                token.column,
+                format!("Expected {} (found {})", what, token),
+                None,
+            ),
+            None => {
+                let last = self.input.last().unwrap();
+                self.error_handler.err(
+                    last.line,
+                    last.column,
+                    format!("Unexpected EOF (expected {})", what),
+                    None,
+                )
+            }
+        }
+    }
 }
\ No newline at end of file
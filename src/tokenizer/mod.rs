@@ -13,9 +13,54 @@ pub enum TokenType {
     CloseParen,
     OpenCurly,
     CloseCurly,
+    OpenSquare,
+    CloseSquare,
+    /// `<` before the type parameters of a generic function
+    OpenAngle,
+    /// `>` after them
+    CloseAngle,
     ReturnKeyword,
     IntLiteral,
+    StrLiteral,
+    CharLiteral,
     Semicolon,
+    Comma,
+    Colon,
+    Equals,
+    Ident,
+    ConstKeyword,
+    LetKeyword,
+    StaticKeyword,
+    MutKeyword,
+    AsmKeyword,
+    ExternKeyword,
+    ExportKeyword,
+    StructKeyword,
+    EnumKeyword,
+    MatchKeyword,
+    NullKeyword,
+    TrueKeyword,
+    FalseKeyword,
+    /// `as` between a value and the type it is cast to
+    AsKeyword,
+    /// `#[name]`, with the name as an Ident value
+    Attribute,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    Dot,
+    /// `::` between an enum and one of its variants
+    DoubleColon,
+    /// `=>` between a match pattern and its value
+    FatArrow,
+    /// `..=` in an inclusive range pattern
+    DotDotEquals,
+    /// `..` between the bounds of a slice, the end left out
+    DotDot,
+    /// `&` taking the address of a variable
+    Ampersand,
 }
 impl fmt::Display for TokenType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -28,9 +73,45 @@ impl fmt::Display for TokenType {
             Self::CloseParen => write!(f, "CloseParen"),
             Self::OpenCurly => write!(f, "OpenCurly"),
             Self::CloseCurly => write!(f, "CloseCurly"),
+            Self::OpenSquare => write!(f, "OpenSquare"),
+            Self::CloseSquare => write!(f, "CloseSquare"),
+            Self::OpenAngle => write!(f, "OpenAngle"),
+            Self::CloseAngle => write!(f, "CloseAngle"),
             Self::ReturnKeyword => write!(f, "ReturnKeyword"),
             Self::IntLiteral => write!(f, "IntLiteral"),
+            Self::StrLiteral => write!(f, "StrLiteral"),
+            Self::CharLiteral => write!(f, "CharLiteral"),
             Self::Semicolon => write!(f, "Semicolon"),
+            Self::Comma => write!(f, "Comma"),
+            Self::Colon => write!(f, "Colon"),
+            Self::Equals => write!(f, "Equals"),
+            Self::Ident => write!(f, "Ident"),
+            Self::ConstKeyword => write!(f, "ConstKeyword"),
+            Self::LetKeyword => write!(f, "LetKeyword"),
+            Self::StaticKeyword => write!(f, "StaticKeyword"),
+            Self::MutKeyword => write!(f, "MutKeyword"),
+            Self::AsmKeyword => write!(f, "AsmKeyword"),
+            Self::ExternKeyword => write!(f, "ExternKeyword"),
+            Self::ExportKeyword => write!(f, "ExportKeyword"),
+            Self::StructKeyword => write!(f, "StructKeyword"),
+            Self::EnumKeyword => write!(f, "EnumKeyword"),
+            Self::MatchKeyword => write!(f, "MatchKeyword"),
+            Self::NullKeyword => write!(f, "NullKeyword"),
+            Self::TrueKeyword => write!(f, "TrueKeyword"),
+            Self::FalseKeyword => write!(f, "FalseKeyword"),
+            Self::AsKeyword => write!(f, "AsKeyword"),
+            Self::Attribute => write!(f, "Attribute"),
+            Self::Plus => write!(f, "Plus"),
+            Self::Minus => write!(f, "Minus"),
+            Self::Star => write!(f, "Star"),
+            Self::Slash => write!(f, "Slash"),
+            Self::Percent => write!(f, "Percent"),
+            Self::Dot => write!(f, "Dot"),
+            Self::DoubleColon => write!(f, "DoubleColon"),
+            Self::FatArrow => write!(f, "FatArrow"),
+            Self::DotDotEquals => write!(f, "DotDotEquals"),
+            Self::DotDot => write!(f, "DotDot"),
+            Self::Ampersand => write!(f, "Ampersand"),
         }
     }
 }
@@ -40,6 +121,9 @@ pub enum TokenValue<'a> {
     FunctionIdent(String),
     TypeIdent(&'a Type),
     IntLiteral(u64),
+    StrLiteral(String),
+    CharLiteral(char),
+    Ident(String),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -58,11 +142,34 @@ impl<'a> Token<'a> {
             panic!("token is not a function identifier");
         }
     }
+    pub fn get_ident(&self) -> String {
+        if let Some(TokenValue::Ident(ref ident)) = self.value {
+            return ident.clone();
+        } else {
+            panic!("token is not an identifier");
+        }
+    }
+}
+
+impl<'a> fmt::Display for TokenValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FunctionIdent(ident) => write!(f, "{}", ident),
+            Self::TypeIdent(ty) => write!(f, "{}", ty),
+            Self::IntLiteral(value) => write!(f, "{}", value),
+            Self::StrLiteral(value) => write!(f, "{:?}", value),
+            Self::CharLiteral(value) => write!(f, "{:?}", value),
+            Self::Ident(ident) => write!(f, "{}", ident),
+        }
+    }
 }
 
 impl<'a> fmt::Display for Token<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} Token", self.token_type)
+        match &self.value {
+            Some(value) => write!(f, "{} Token ({})", self.token_type, value),
+            None => write!(f, "{} Token", self.token_type),
+        }
     }
 }
 
@@ -88,21 +195,12 @@ impl<'a> Tokenizer<'a> {
         }
     }
     fn peek(&self) -> Option<char> {
-        let peek_index = self.index;
-        if peek_index >= self.input.len() as u64 {
-            return None;
-        }
-        let char = self.input.chars().nth(peek_index as usize).unwrap();
-        Some(char)
+        // `index` counts characters, not bytes
+        self.input.chars().nth(self.index as usize)
     }
     fn consume(&mut self) -> Option<char> {
-        let mut consume_index = self.index;
-        if consume_index >= self.input.len() as u64 {
-            return None;
-        }
-        let char = self.input.chars().nth(consume_index as usize).unwrap();
-        consume_index += 1;
-        self.index = consume_index;
+        let char = self.peek()?;
+        self.index += 1;
         if char == '\n' {
             self.cl = 1;
             self.ln += 1;
@@ -138,6 +236,64 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    /// Reads the escape sequence after a backslash inside a string or character literal.
+    fn escape(&mut self) -> char {
+        let (line, column) = (self.ln, self.cl);
+        let Some(c) = self.consume() else {
+            self.error_handler.err(line, column, String::from("Unexpected EOF (expected escape sequence)"), None);
+        };
+        match c {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' | '"' | '\'' => c,
+            'x' => {
+                let digits: String = (0..2).filter_map(|_| self.consume()).collect();
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(value) if value <= 0x7F => value as char,
+                    Ok(_) => self.error_handler.err(
+                        line,
+                        column,
+                        format!("Escape \\x{} is out of range", digits),
+                        Some(String::from("\\x escapes go up to \\x7F; use \\u{...} for other characters")),
+                    ),
+                    Err(_) => self.error_handler.err(
+                        line,
+                        column,
+                        format!("Invalid escape \\x{}", digits),
+                        Some(String::from("\\x must be followed by two hexadecimal digits")),
+                    ),
+                }
+            }
+            'u' => {
+                let mut digits = String::new();
+                let mut closed = false;
+                if self.peek() == Some('{') {
+                    self.consume();
+                    while let Some(c) = self.consume() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        digits.push(c);
+                    }
+                }
+                let value = u32::from_str_radix(&digits, 16).ok().filter(|_| closed && digits.len() <= 6);
+                match value.and_then(char::from_u32) {
+                    Some(c) => c,
+                    None => self.error_handler.err(
+                        line,
+                        column,
+                        format!("Invalid escape \\u{{{}}}", digits),
+                        Some(String::from("\\u must be followed by a Unicode code point in braces, e.g. \\u{1F600}")),
+                    ),
+                }
+            }
+            _ => self.error_handler.err(line, column, format!("Unknown escape sequence '\\{}'", c), None),
+        }
+    }
+
     pub fn tokenize(&mut self) -> Vec<Token> {
         let mut tokens: Vec<Token> = Vec::new();
 
@@ -180,6 +336,118 @@ impl<'a> Tokenizer<'a> {
                             value: None,
                         });
                     }
+                    "const" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::ConstKeyword,
+                            value: None,
+                        });
+                    }
+                    "let" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::LetKeyword,
+                            value: None,
+                        });
+                    }
+                    "static" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::StaticKeyword,
+                            value: None,
+                        });
+                    }
+                    "mut" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::MutKeyword,
+                            value: None,
+                        });
+                    }
+                    "asm" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::AsmKeyword,
+                            value: None,
+                        });
+                    }
+                    "extern" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::ExternKeyword,
+                            value: None,
+                        });
+                    }
+                    "export" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::ExportKeyword,
+                            value: None,
+                        });
+                    }
+                    "struct" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::StructKeyword,
+                            value: None,
+                        });
+                    }
+                    "enum" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::EnumKeyword,
+                            value: None,
+                        });
+                    }
+                    "match" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::MatchKeyword,
+                            value: None,
+                        });
+                    }
+                    "null" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::NullKeyword,
+                            value: None,
+                        });
+                    }
+                    "true" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::TrueKeyword,
+                            value: None,
+                        });
+                    }
+                    "false" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::FalseKeyword,
+                            value: None,
+                        });
+                    }
+                    "as" => {
+                        tokens.push(Token {
+                            line: self.ln,
+                            column: self.cl,
+                            token_type: TokenType::AsKeyword,
+                            value: None,
+                        });
+                    }
                     _ => {
                         if BUILTIN_TYPES.contains_key(word.as_str()) {
                             tokens.push(Token {
@@ -189,12 +457,12 @@ impl<'a> Tokenizer<'a> {
                                 value: Some(TokenValue::TypeIdent(&BUILTIN_TYPES[word.as_str()])),
                             });
                         } else {
-                            self.error_handler.err(
-                                self.ln,
-                                self.cl,
-                                format!("Unexpected identifier: '{}'", word),
-                                None
-                            );
+                            tokens.push(Token {
+                                line: self.ln,
+                                column: self.cl,
+                                token_type: TokenType::Ident,
+                                value: Some(TokenValue::Ident(word)),
+                            });
                         }
                     }
                 }
@@ -208,13 +476,96 @@ impl<'a> Tokenizer<'a> {
                         break;
                     }
                 }
-                let int_value = num.parse::<u64>().unwrap();
+                let Ok(int_value) = num.parse::<u64>() else {
+                    self.error_handler.err(
+                        self.ln,
+                        self.cl,
+                        format!("Integer literal {} is too large", num),
+                        Some(String::from("The largest integer is 18446744073709551615")),
+                    );
+                };
                 tokens.push(Token {
                     line: self.ln,
                     column: self.cl,
                     token_type: TokenType::IntLiteral,
                     value: Some(TokenValue::IntLiteral(int_value)),
                 });
+            } else if c == '"' {
+                let (line, column) = (self.ln, self.cl);
+                self.consume();
+                let mut value = String::new();
+                loop {
+                    match self.consume() {
+                        Some('"') => break,
+                        Some('\\') => value.push(self.escape()),
+                        Some('\n') | None => self.error_handler.err(
+                            line,
+                            column,
+                            String::from("Unterminated string literal"),
+                            None,
+                        ),
+                        Some(c) => value.push(c),
+                    }
+                }
+                tokens.push(Token {
+                    line,
+                    column,
+                    token_type: TokenType::StrLiteral,
+                    value: Some(TokenValue::StrLiteral(value)),
+                });
+            } else if c == '\'' {
+                let (line, column) = (self.ln, self.cl);
+                self.consume();
+                let value = match self.consume() {
+                    Some('\\') => self.escape(),
+                    Some('\'') | Some('\n') | None => self.error_handler.err(
+                        line,
+                        column,
+                        String::from("Empty or unterminated character literal"),
+                        None,
+                    ),
+                    Some(c) => c,
+                };
+                if self.consume() != Some('\'') {
+                    self.error_handler.err(
+                        line,
+                        column,
+                        String::from("Character literals must hold exactly one character"),
+                        Some(String::from("Use double quotes for strings")),
+                    );
+                }
+                if !value.is_ascii() {
+                    self.error_handler.err(
+                        line,
+                        column,
+                        format!("Character literal {:?} is not ASCII", value),
+                        Some(String::from("Character literals are single bytes")),
+                    );
+                }
+                tokens.push(Token {
+                    line,
+                    column,
+                    token_type: TokenType::CharLiteral,
+                    value: Some(TokenValue::CharLiteral(value)),
+                });
+            } else if c == '#' {
+                let (line, column) = (self.ln, self.cl);
+                self.consume();
+                let name = if self.consume() == Some('[') { self.consume_word() } else { None };
+                let Some(name) = name.filter(|_| self.consume() == Some(']')) else {
+                    self.error_handler.err(
+                        line,
+                        column,
+                        String::from("Malformed attribute"),
+                        Some(String::from("Attributes look like #[no_mangle]")),
+                    );
+                };
+                tokens.push(Token {
+                    line,
+                    column,
+                    token_type: TokenType::Attribute,
+                    value: Some(TokenValue::Ident(name)),
+                });
             } else if c == '(' {
                 tokens.push(Token {
                     line: self.ln,
@@ -231,6 +582,22 @@ impl<'a> Tokenizer<'a> {
                     value: None,
                 });
                 self.consume();
+            } else if c == '[' {
+                tokens.push(Token {
+                    line: self.ln,
+                    column: self.cl,
+                    token_type: TokenType::OpenSquare,
+                    value: None,
+                });
+                self.consume();
+            } else if c == ']' {
+                tokens.push(Token {
+                    line: self.ln,
+                    column: self.cl,
+                    token_type: TokenType::CloseSquare,
+                    value: None,
+                });
+                self.consume();
             } else if c == '{' {
                 tokens.push(Token {
                     line: self.ln,
@@ -247,6 +614,22 @@ impl<'a> Tokenizer<'a> {
                     value: None,
                 });
                 self.consume();
+            } else if c == '<' {
+                tokens.push(Token {
+                    line: self.ln,
+                    column: self.cl,
+                    token_type: TokenType::OpenAngle,
+                    value: None,
+                });
+                self.consume();
+            } else if c == '>' {
+                tokens.push(Token {
+                    line: self.ln,
+                    column: self.cl,
+                    token_type: TokenType::CloseAngle,
+                    value: None,
+                });
+                self.consume();
             } else if c == ';' {
                 tokens.push(Token {
                     line: self.ln,
@@ -255,28 +638,126 @@ impl<'a> Tokenizer<'a> {
                     value: None,
                 });
                 self.consume();
-            } else if c == '-' {
-                self.consume(); // consume the hyphen
-                // consume again for the next character
-                if let Some(next_char) = self.consume() {
-                    if next_char == '>' {
-                        tokens.push(Token {
-                            line: self.ln,
-                            column: self.cl,
-                            token_type: TokenType::ThinArrow,
-                            value: None,
-                        });
-                        self.consume(); // consume the '>'
+            } else if c == ':' {
+                let (line, column) = (self.ln, self.cl);
+                self.consume();
+                let token_type = if self.peek() == Some(':') {
+                    self.consume();
+                    TokenType::DoubleColon
+                } else {
+                    TokenType::Colon
+                };
+                tokens.push(Token { line, column, token_type, value: None });
+            } else if c == '=' {
+                let (line, column) = (self.ln, self.cl);
+                self.consume();
+                let token_type = if self.peek() == Some('>') {
+                    self.consume();
+                    TokenType::FatArrow
+                } else {
+                    TokenType::Equals
+                };
+                tokens.push(Token { line, column, token_type, value: None });
+            } else if c == ',' {
+                tokens.push(Token {
+                    line: self.ln,
+                    column: self.cl,
+                    token_type: TokenType::Comma,
+                    value: None,
+                });
+                self.consume();
+            } else if c == '.' {
+                let (line, column) = (self.ln, self.cl);
+                self.consume();
+                if self.peek() == Some('.') {
+                    self.consume();
+                    let token_type = if self.peek() == Some('=') {
+                        self.consume();
+                        TokenType::DotDotEquals
                     } else {
-                        self.error_handler.err(
-                            self.ln,
-                            self.cl,
-                            format!("Unexpected character after hyphen sign: '{}'", c),
-                            None
-                        );
-                        self.consume(); // consume the '-'
+                        TokenType::DotDot
+                    };
+                    tokens.push(Token { line, column, token_type, value: None });
+                } else {
+                    tokens.push(Token { line, column, token_type: TokenType::Dot, value: None });
+                }
+            } else if c == '+' {
+                tokens.push(Token {
+                    line: self.ln,
+                    column: self.cl,
+                    token_type: TokenType::Plus,
+                    value: None,
+                });
+                self.consume();
+            } else if c == '*' {
+                tokens.push(Token {
+                    line: self.ln,
+                    column: self.cl,
+                    token_type: TokenType::Star,
+                    value: None,
+                });
+                self.consume();
+            } else if c == '&' {
+                tokens.push(Token {
+                    line: self.ln,
+                    column: self.cl,
+                    token_type: TokenType::Ampersand,
+                    value: None,
+                });
+                self.consume();
+            } else if c == '%' {
+                tokens.push(Token {
+                    line: self.ln,
+                    column: self.cl,
+                    token_type: TokenType::Percent,
+                    value: None,
+                });
+                self.consume();
+            } else if c == '/' {
+                let (line, column) = (self.ln, self.cl);
+                self.consume();
+                if self.peek() == Some('/') {
+                    // Line comment - skip to the end of the line
+                    while let Some(c) = self.peek() {
+                        if c == '\n' {
+                            break;
+                        }
+                        self.consume();
                     }
+                } else {
+                    tokens.push(Token {
+                        line,
+                        column,
+                        token_type: TokenType::Slash,
+                        value: None,
+                    });
+                }
+            } else if c == '-' {
+                let (line, column) = (self.ln, self.cl);
+                self.consume(); // consume the hyphen
+                if self.peek() == Some('>') {
+                    tokens.push(Token {
+                        line,
+                        column,
+                        token_type: TokenType::ThinArrow,
+                        value: None,
+                    });
+                    self.consume(); // consume the '>'
+                } else {
+                    tokens.push(Token {
+                        line,
+                        column,
+                        token_type: TokenType::Minus,
+                        value: None,
+                    });
                 }
+            } else {
+                self.error_handler.err(
+                    self.ln,
+                    self.cl,
+                    format!("Unexpected character: '{}'", c),
+                    None
+                );
             }
         }
 
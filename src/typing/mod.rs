@@ -1,28 +1,352 @@
 use std::collections::HashMap;
-use std::sync::LazyLock;
+use std::sync::{LazyLock, Mutex};
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TypeKind {
+    Unsigned,
+    /// Two's complement
+    Signed,
+    /// `true` or `false`, stored as 1 or 0 in a byte
+    Bool,
+    /// A pointer to UTF-8 bytes and their length
+    Str,
+    /// User-defined, with named fields
+    Struct,
+    /// User-defined, one of several variants, each with its own fields
+    Enum,
+    /// The address of a value of the type named after the `*`
+    Pointer,
+    /// A fixed number of values of one type, one after the other
+    Array,
+    /// The address of the first of some values of the type named after the
+    /// `[]`, and how many there are
+    Slice,
+    /// A type parameter of a generic function, which stands for whatever
+    /// type the function is called with
+    Parameter,
+}
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Type {
+    name: String,
+    kind: TypeKind,
     size: Option<u64>, // If a type is stored on the stack, it will have a size
+    align: u64,
+    /// In declaration order; empty unless this is a struct
+    fields: Vec<Field>,
+    /// In declaration order; empty unless this is an enum
+    variants: Vec<Variant>,
+    /// The type of the elements and how many there are, if this is an array
+    element: Option<(Box<Type>, u64)>,
+}
+
+/// A struct field and where it sits in the struct.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Field {
+    pub name: String,
+    pub ty: Type,
+    pub offset: u64,
+}
+
+/// An enum variant: the discriminant stored in the tag when a value is of
+/// this variant, and the fields it carries. Fields are numbered, not named.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Variant {
+    pub name: String,
+    pub discriminant: u64,
+    pub fields: Vec<Field>,
+}
+
+impl Type {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn kind(&self) -> TypeKind {
+        self.kind
+    }
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+    pub fn align(&self) -> u64 {
+        self.align
+    }
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+    pub fn variants(&self) -> &[Variant] {
+        &self.variants
+    }
+    pub fn variant(&self, name: &str) -> Option<&Variant> {
+        self.variants.iter().find(|variant| variant.name == name)
+    }
+    /// The type of an enum's tag: the smallest unsigned integer that holds
+    /// every discriminant.
+    pub fn tag(&self) -> &'static Type {
+        let largest = self.variants.iter().map(|variant| variant.discriminant).max().unwrap_or(0);
+        match largest {
+            0..=0xFF => &UINT8,
+            0x100..=0xFFFF => &UINT16,
+            0x1_0000..=0xFFFF_FFFF => &UINT32,
+            _ => &UINT64,
+        }
+    }
+    /// The type of an array's elements.
+    pub fn element(&self) -> Option<&Type> {
+        self.element.as_ref().map(|(element, _)| &**element)
+    }
+    /// How many elements an array has.
+    pub fn length(&self) -> Option<u64> {
+        self.element.as_ref().map(|(_, length)| *length)
+    }
+    /// The name of the type a pointer points to.
+    pub fn pointee_name(&self) -> Option<&str> {
+        match self.kind {
+            TypeKind::Pointer => Some(&self.name[1..]),
+            _ => None,
+        }
+    }
+    /// The name of the type of a slice's elements.
+    pub fn slice_element_name(&self) -> Option<&str> {
+        match self.kind {
+            TypeKind::Slice => Some(&self.name[2..]),
+            _ => None,
+        }
+    }
+    pub fn is_integer(&self) -> bool {
+        matches!(self.kind, TypeKind::Unsigned | TypeKind::Signed)
+    }
+    /// Whether `value as to` is allowed for a value of this type: between
+    /// integers, from bool to an integer, and between pointers and integers
+    /// or other pointers.
+    pub fn casts_to(&self, to: &Type) -> bool {
+        let pointer_or_integer = |ty: &Type| ty.is_integer() || ty.kind == TypeKind::Pointer;
+        self == to
+            || (to.is_integer() && (pointer_or_integer(self) || self.kind == TypeKind::Bool))
+            || (to.kind == TypeKind::Pointer && pointer_or_integer(self))
+    }
+    /// Lays out a struct like C does: each field at the next offset that is
+    /// a multiple of its alignment, and the whole struct padded to a
+    /// multiple of the largest alignment so that arrays of it stay aligned.
+    pub fn structure(name: &str, fields: Vec<(String, Type)>) -> Type {
+        let mut size: u64 = 0;
+        let mut align: u64 = 1;
+        let mut laid_out: Vec<Field> = Vec::new();
+        for (name, ty) in fields {
+            let field_size = ty.size.unwrap_or(0);
+            let offset = size.next_multiple_of(ty.align);
+            size = offset + field_size;
+            align = align.max(ty.align);
+            laid_out.push(Field { name, ty, offset });
+        }
+        Type {
+            name: String::from(name),
+            kind: TypeKind::Struct,
+            size: Some(size.next_multiple_of(align)),
+            align,
+            fields: laid_out,
+            variants: Vec::new(),
+            element: None,
+        }
+    }
+    /// Lays out an enum like a C struct holding the tag and a union of the
+    /// variants' fields: every variant's fields start at the same offset,
+    /// right after the tag, aligned for the most aligned of them.
+    pub fn enumeration(name: &str, variants: Vec<(String, u64, Vec<Type>)>) -> Type {
+        let mut ty = Type {
+            name: String::from(name),
+            kind: TypeKind::Enum,
+            size: None,
+            align: 1,
+            fields: Vec::new(),
+            variants: variants
+                .iter()
+                .map(|(name, discriminant, _)| Variant { name: name.clone(), discriminant: *discriminant, fields: Vec::new() })
+                .collect(),
+            element: None,
+        };
+        let tag = ty.tag();
+        let payload_align = variants.iter().flat_map(|(_, _, fields)| fields).map(|field| field.align).max().unwrap_or(1);
+        let start = tag.size.unwrap_or(0).next_multiple_of(payload_align);
+        let mut size = tag.size.unwrap_or(0);
+        for ((_, _, fields), variant) in variants.into_iter().zip(&mut ty.variants) {
+            let mut end = start;
+            for (i, field) in fields.into_iter().enumerate() {
+                let offset = end.next_multiple_of(field.align);
+                end = offset + field.size.unwrap_or(0);
+                variant.fields.push(Field { name: i.to_string(), ty: field, offset });
+            }
+            size = size.max(end);
+        }
+        ty.align = tag.align.max(payload_align);
+        ty.size = Some(size.next_multiple_of(ty.align));
+        ty
+    }
 }
 
 impl std::fmt::Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "type with size {:?}", self.size)
+        write!(f, "{}", self.name)
     }
 }
 
-pub static UINT8: LazyLock<Type> = LazyLock::new(|| Type {
+fn integer(name: &str, kind: TypeKind, size: u64) -> Type {
+    Type {
+        name: String::from(name),
+        kind,
+        size: Some(size),
+        align: size,
+        fields: Vec::new(),
+        variants: Vec::new(),
+        element: None,
+    }
+}
+
+pub static UINT8: LazyLock<Type> = LazyLock::new(|| integer("uint8", TypeKind::Unsigned, 1));
+pub static UINT16: LazyLock<Type> = LazyLock::new(|| integer("uint16", TypeKind::Unsigned, 2));
+pub static UINT32: LazyLock<Type> = LazyLock::new(|| integer("uint32", TypeKind::Unsigned, 4));
+pub static UINT64: LazyLock<Type> = LazyLock::new(|| integer("uint64", TypeKind::Unsigned, 8));
+pub static INT8: LazyLock<Type> = LazyLock::new(|| integer("int8", TypeKind::Signed, 1));
+pub static INT16: LazyLock<Type> = LazyLock::new(|| integer("int16", TypeKind::Signed, 2));
+pub static INT32: LazyLock<Type> = LazyLock::new(|| integer("int32", TypeKind::Signed, 4));
+pub static INT64: LazyLock<Type> = LazyLock::new(|| integer("int64", TypeKind::Signed, 8));
+
+pub static BOOL: LazyLock<Type> = LazyLock::new(|| Type {
+    name: String::from("bool"),
+    kind: TypeKind::Bool,
     size: Some(1),
+    align: 1,
+    fields: Vec::new(),
+    variants: Vec::new(),
+    element: None,
 });
 
+pub static STR: LazyLock<Type> = LazyLock::new(|| Type {
+    name: String::from("str"),
+    kind: TypeKind::Str,
+    size: Some(16),
+    align: 8,
+    fields: Vec::new(),
+    variants: Vec::new(),
+    element: None,
+});
+
+/// Pointer, array, slice and parameter types are made as they are needed
+/// and shared, so that there is one `*T` however often it is written.
+static DERIVED: LazyLock<Mutex<HashMap<String, &'static Type>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn derived(name: String, make: impl FnOnce(String) -> Type) -> &'static Type {
+    let mut derived = DERIVED.lock().unwrap();
+    derived.entry(name.clone()).or_insert_with(|| Box::leak(Box::new(make(name))))
+}
+
+/// The type of pointers to the type named `to`. A pointer only knows its
+/// pointee by name, so that a struct can hold a pointer to itself; the
+/// TypeTable looks the pointee up.
+pub fn pointer(to: &str) -> &'static Type {
+    derived(format!("*{}", to), |name| Type {
+        name,
+        kind: TypeKind::Pointer,
+        size: Some(8),
+        align: 8,
+        fields: Vec::new(),
+        variants: Vec::new(),
+        element: None,
+    })
+}
+
+/// The type of slices of the type named `of`, which like a pointer's
+/// pointee is only known by name.
+pub fn slice(of: &str) -> &'static Type {
+    derived(format!("[]{}", of), |name| Type {
+        name,
+        kind: TypeKind::Slice,
+        size: Some(16),
+        align: 8,
+        fields: Vec::new(),
+        variants: Vec::new(),
+        element: None,
+    })
+}
+
+/// The type parameter named `name`. It has no size, as only the copies of
+/// a generic function made for each list of types it is called with are
+/// lowered.
+pub fn parameter(name: &str) -> &'static Type {
+    derived(String::from(name), |name| Type {
+        name,
+        kind: TypeKind::Parameter,
+        size: None,
+        align: 1,
+        fields: Vec::new(),
+        variants: Vec::new(),
+        element: None,
+    })
+}
+
+/// The type of arrays of `length` `element`s, laid out like C lays out
+/// arrays.
+pub fn array(element: &Type, length: u64) -> &'static Type {
+    derived(format!("[{}; {}]", element.name, length), |name| Type {
+        name,
+        kind: TypeKind::Array,
+        size: Some(element.size.unwrap_or(0) * length),
+        align: element.align,
+        fields: Vec::new(),
+        variants: Vec::new(),
+        element: Some((Box::new(element.clone()), length)),
+    })
+}
+
 pub static BUILTIN_TYPES: LazyLock<HashMap<String, Type>> = LazyLock::new(|| {
     let mut map = HashMap::new();
-    map.insert(
-        "uint8".to_string(),
-        Type {
-            size: Some(4),
-        },
-    );
+    for ty in [&*UINT8, &*UINT16, &*UINT32, &*UINT64, &*INT8, &*INT16, &*INT32, &*INT64, &*BOOL, &*STR] {
+        map.insert(ty.name.clone(), ty.clone());
+    }
     map
 });
+
+/// Every type a program can name: the builtin ones and its structs and enums.
+#[derive(Debug, Clone)]
+pub struct TypeTable {
+    types: HashMap<String, Type>,
+}
+
+impl TypeTable {
+    pub fn new() -> Self {
+        Self { types: BUILTIN_TYPES.clone() }
+    }
+    pub fn get(&self, name: &str) -> Option<&Type> {
+        self.types.get(name)
+    }
+    pub fn insert(&mut self, ty: Type) {
+        self.types.insert(ty.name.clone(), ty);
+    }
+    /// The type `ty` points to, if it is a pointer.
+    pub fn pointee(&self, ty: &Type) -> Option<&Type> {
+        self.resolve(ty.pointee_name()?)
+    }
+    /// The type of the elements of an array or slice.
+    pub fn element<'t>(&'t self, ty: &'t Type) -> Option<&'t Type> {
+        match ty.kind {
+            TypeKind::Slice => self.resolve(ty.slice_element_name()?),
+            _ => ty.element(),
+        }
+    }
+    /// Looks a type up by name, including pointer, slice and array types,
+    /// which are made if they have not been yet.
+    pub fn resolve(&self, name: &str) -> Option<&Type> {
+        if let Some(inner) = name.strip_prefix('*') {
+            return Some(pointer(inner));
+        }
+        if let Some(inner) = name.strip_prefix("[]") {
+            return Some(slice(inner));
+        }
+        if let Some((element, length)) = name.strip_prefix('[').and_then(|name| name.strip_suffix(']')?.rsplit_once("; ")) {
+            return Some(array(self.resolve(element)?, length.parse().ok()?));
+        }
+        self.get(name)
+    }
+}
@@ -0,0 +1,43 @@
+//! Tests for `sabbahc demangle`, both with symbols as arguments and as a
+//! filter over stdin.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+fn arguments() {
+    let demangled = Command::new(env!("CARGO_BIN_EXE_sabbahc"))
+        .args(["demangle", "_S6_startE", "_S4math5twiceE", "main", "_S5abcE"])
+        .output()
+        .expect("Failed to run sabbahc");
+    assert!(demangled.status.success());
+    // Anything that is not a well-formed mangled symbol is printed as it is
+    assert_eq!(String::from_utf8_lossy(&demangled.stdout), "_start\nmath::twice\nmain\n_S5abcE\n");
+}
+
+#[test]
+fn generics() {
+    let demangled = Command::new(env!("CARGO_BIN_EXE_sabbahc"))
+        .args(["demangle", "_S3maxI5int32EE", "_S4swapIP5uint8S4int8EE", "_S5firstIA4_6uint16EE", "_S3maxIEE"])
+        .output()
+        .expect("Failed to run sabbahc");
+    assert!(demangled.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&demangled.stdout),
+        "max<int32>\nswap<*uint8, []int8>\nfirst<[uint16; 4]>\n_S3maxIEE\n"
+    );
+}
+
+#[test]
+fn filter() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sabbahc"))
+        .arg("demangle")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to run sabbahc");
+    child.stdin.take().unwrap().write_all(b"    call _S5twiceE\n    call putchar\n").unwrap();
+    let demangled = child.wait_with_output().unwrap();
+    assert!(demangled.status.success());
+    assert_eq!(String::from_utf8_lossy(&demangled.stdout), "    call twice\n    call putchar\n");
+}
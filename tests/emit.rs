@@ -0,0 +1,237 @@
+//! Tests for `--emit` and `-e`: each kind is written to its own file named
+//! after the input, the JSON dumps parse, and a failed write is an error.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const PROGRAM: &str = "f main() -> uint8 {\n    return 7;\n}\n";
+
+/// A scratch directory holding `prog.sbb`, removed when dropped.
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn new(name: &str) -> Scratch {
+        let dir = env::temp_dir().join(format!("sabbahc-emit-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("prog.sbb"), PROGRAM).unwrap();
+        Scratch(dir)
+    }
+    fn sabbahc(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_sabbahc"))
+            .current_dir(&self.0)
+            .arg("prog.sbb")
+            .args(args)
+            .output()
+            .expect("Failed to run sabbahc")
+    }
+    fn read(&self, name: &str) -> String {
+        fs::read_to_string(self.0.join(name)).unwrap_or_else(|err| panic!("{} was not written: {}", name, err))
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn every_kind() {
+    let scratch = Scratch::new("kinds");
+    let compiled = scratch.sabbahc(&["--emit=tokens,tokens-json,ast,ast-json", "-e", "ir,asm", "-e", "obj,exe"]);
+    assert!(compiled.status.success(), "{}", String::from_utf8_lossy(&compiled.stdout));
+
+    assert!(scratch.read("prog.tokens").starts_with("1:2 FunctionKeyword"));
+    let tokens = parse(&scratch.read("prog.tokens.json"));
+    let first = &tokens.items()[1];
+    assert_eq!(first.get("type").string(), "FunctionIdent");
+    assert_eq!(first.get("value").string(), "main");
+
+    assert!(scratch.read("prog.ast").starts_with("Scope {"));
+    let ast = parse(&scratch.read("prog.ast.json"));
+    let nodes = ast.get("statements").items()[0].get("nodes").items();
+    assert_eq!(nodes[1].get("value").string(), "main");
+    assert_eq!(nodes[3].get("value").get("name").string(), "uint8");
+    assert_eq!(nodes[3].get("value").get("size").number(), 1.0);
+
+    assert!(scratch.read("prog.ir").contains("fn main() -> i8 {"));
+    assert!(scratch.read("prog.s").contains("\nmain:\n"));
+    let object = fs::read(scratch.0.join("prog.o")).unwrap();
+    assert!(object.starts_with(b"\x7fELF"));
+    let ran = Command::new(scratch.0.join("prog")).output().unwrap();
+    assert_eq!(ran.status.code(), Some(7));
+}
+
+#[test]
+fn output_names() {
+    let scratch = Scratch::new("names");
+    // With one kind, -o names its file; with several, it is their stem
+    let single = scratch.sabbahc(&["-e", "ir", "-o", "single.txt"]);
+    assert!(single.status.success());
+    assert!(scratch.read("single.txt").contains("fn main()"));
+    let several = scratch.sabbahc(&["--emit=tokens,header", "-o", "out/lib"]);
+    assert!(!several.status.success(), "the out directory does not exist");
+    fs::create_dir(scratch.0.join("out")).unwrap();
+    let several = scratch.sabbahc(&["--emit=tokens,header", "-o", "out/lib"]);
+    assert!(several.status.success());
+    assert!(Path::new(&scratch.0.join("out/lib.tokens")).exists());
+    assert!(Path::new(&scratch.0.join("out/lib.h")).exists());
+}
+
+#[test]
+fn unwritable_output() {
+    let scratch = Scratch::new("unwritable");
+    let failed = scratch.sabbahc(&["-e", "ast-json", "-o", "missing/prog.json"]);
+    assert_eq!(failed.status.code(), Some(14));
+    assert!(String::from_utf8_lossy(&failed.stdout).contains("Could not write missing/prog.json"));
+}
+
+#[test]
+fn unknown_kind() {
+    let scratch = Scratch::new("unknown");
+    assert_eq!(scratch.sabbahc(&["--emit=ir,bytecode"]).status.code(), Some(7));
+}
+
+/// Just enough JSON to look inside the dumps.
+#[derive(Debug)]
+enum Json {
+    Null,
+    Bool,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> &Json {
+        match self {
+            Json::Object(fields) => &fields.iter().find(|(name, _)| name == key).unwrap_or_else(|| panic!("no {:?}", key)).1,
+            _ => panic!("{:?} is not an object", self),
+        }
+    }
+    fn items(&self) -> &[Json] {
+        match self {
+            Json::Array(items) => items,
+            _ => panic!("{:?} is not an array", self),
+        }
+    }
+    fn number(&self) -> f64 {
+        match self {
+            Json::Number(value) => *value,
+            _ => panic!("{:?} is not a number", self),
+        }
+    }
+    fn string(&self) -> &str {
+        match self {
+            Json::String(value) => value,
+            _ => panic!("{:?} is not a string", self),
+        }
+    }
+}
+
+/// Parses `text`, which must be one JSON value and nothing else.
+fn parse(text: &str) -> Json {
+    let mut chars: Vec<char> = text.chars().collect();
+    chars.reverse();
+    let value = value(&mut chars);
+    skip_whitespace(&mut chars);
+    assert!(chars.is_empty(), "trailing characters after the JSON value");
+    value
+}
+
+fn skip_whitespace(chars: &mut Vec<char>) {
+    while chars.last().is_some_and(|c| c.is_whitespace()) {
+        chars.pop();
+    }
+}
+
+fn expect(chars: &mut Vec<char>, expected: char) {
+    skip_whitespace(chars);
+    assert_eq!(chars.pop(), Some(expected));
+}
+
+fn value(chars: &mut Vec<char>) -> Json {
+    skip_whitespace(chars);
+    match chars.last() {
+        Some('{') => {
+            chars.pop();
+            let mut fields = Vec::new();
+            skip_whitespace(chars);
+            if chars.last() == Some(&'}') {
+                chars.pop();
+                return Json::Object(fields);
+            }
+            loop {
+                skip_whitespace(chars);
+                let Json::String(name) = value(chars) else { panic!("object keys are strings") };
+                expect(chars, ':');
+                fields.push((name, value(chars)));
+                skip_whitespace(chars);
+                match chars.pop() {
+                    Some(',') => continue,
+                    Some('}') => return Json::Object(fields),
+                    other => panic!("expected ',' or '}}' (found {:?})", other),
+                }
+            }
+        }
+        Some('[') => {
+            chars.pop();
+            let mut items = Vec::new();
+            skip_whitespace(chars);
+            if chars.last() == Some(&']') {
+                chars.pop();
+                return Json::Array(items);
+            }
+            loop {
+                items.push(value(chars));
+                skip_whitespace(chars);
+                match chars.pop() {
+                    Some(',') => continue,
+                    Some(']') => return Json::Array(items),
+                    other => panic!("expected ',' or ']' (found {:?})", other),
+                }
+            }
+        }
+        Some('"') => {
+            chars.pop();
+            let mut string = String::new();
+            loop {
+                match chars.pop() {
+                    Some('"') => return Json::String(string),
+                    Some('\\') => match chars.pop() {
+                        Some('n') => string.push('\n'),
+                        Some('t') => string.push('\t'),
+                        Some('r') => string.push('\r'),
+                        Some('u') => {
+                            let hex: String = (0..4).map(|_| chars.pop().expect("unterminated escape")).collect();
+                            string.push(char::from_u32(u32::from_str_radix(&hex, 16).unwrap()).unwrap());
+                        }
+                        Some(c @ ('"' | '\\' | '/')) => string.push(c),
+                        other => panic!("invalid escape {:?}", other),
+                    },
+                    Some(c) => string.push(c),
+                    None => panic!("unterminated string"),
+                }
+            }
+        }
+        Some(c) if c.is_ascii_digit() || *c == '-' => {
+            let mut number = String::new();
+            while chars.last().is_some_and(|c| c.is_ascii_digit() || "-+.eE".contains(*c)) {
+                number.push(chars.pop().unwrap());
+            }
+            Json::Number(number.parse().unwrap_or_else(|_| panic!("invalid number {:?}", number)))
+        }
+        _ => {
+            let word: String = chars.iter().rev().take_while(|c| c.is_ascii_alphabetic()).collect();
+            chars.truncate(chars.len() - word.len());
+            match word.as_str() {
+                "null" => Json::Null,
+                "true" | "false" => Json::Bool,
+                _ => panic!("unexpected {:?}", word),
+            }
+        }
+    }
+}
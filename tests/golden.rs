@@ -0,0 +1,140 @@
+//! Golden-file tests for the tokenizer, parser and code generator.
+//!
+//! Every `tests/golden/<name>.sbb` program is run through `sabbahc` once per
+//! stage and the output is compared against the snapshot checked in next to it:
+//!
+//!   <name>.tokens  - token stream (`-m tokens`)
+//!   <name>.ast     - AST dump (`-m ast`)
+//!   <name>.ir      - IR dump (`-m ir`)
+//!   <name>.s       - generated assembly (`-m asm`)
+//!   <name>.stderr  - diagnostics, for programs that fail to compile
+//!   <name>.warnings - warnings, for programs that compile with some
+//!
+//! IR and assembly are also generated at every optimization level above -O0,
+//! as `<name>.O1.ir`, `<name>.O2.s` and so on. Once a stage fails, its
+//! diagnostics are compared and the later stages are skipped. Run `BLESS=1 cargo test --test golden` to write new snapshots.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// (mode, snapshot extension, optimization levels)
+const STAGES: [(&str, &str, &[u8]); 4] = [
+    ("tokens", "tokens", &[0]),
+    ("ast", "ast", &[0]),
+    ("ir", "ir", &[0, 1, 2]),
+    ("asm", "s", &[0, 1, 2]),
+];
+
+#[test]
+fn golden() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+    let bless = env::var_os("BLESS").is_some();
+
+    let mut programs: Vec<PathBuf> = fs::read_dir(&dir)
+        .expect("Failed to read tests/golden")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "sbb"))
+        .collect();
+    programs.sort();
+    assert!(!programs.is_empty(), "no golden programs found in {}", dir.display());
+
+    let mut failures: Vec<String> = Vec::new();
+    for program in &programs {
+        check_program(program, bless, &mut failures);
+    }
+    if !failures.is_empty() {
+        panic!(
+            "{} golden snapshot(s) differ (rerun with BLESS=1 to accept):\n\n{}",
+            failures.len(),
+            failures.join("\n")
+        );
+    }
+}
+
+fn check_program(program: &Path, bless: bool, failures: &mut Vec<String>) {
+    let name = program.file_stem().unwrap().to_str().unwrap();
+    let file_name = program.file_name().unwrap();
+    let scratch = env::temp_dir().join(format!("sabbahc-golden-{}-{}", std::process::id(), name));
+    fs::create_dir_all(&scratch).unwrap();
+
+    // Warnings come from lowering, so every stage from IR on repeats them
+    let mut warnings: Option<String> = None;
+    'stages: for (mode, ext, levels) in STAGES {
+        for level in levels {
+            let ext = match level {
+                0 => ext.to_string(),
+                level => format!("O{}.{}", level, ext),
+            };
+            let output = scratch.join(format!("out.{}", ext));
+            // Run from the program's directory so diagnostics show a stable path
+            let result = Command::new(env!("CARGO_BIN_EXE_sabbahc"))
+                .current_dir(program.parent().unwrap())
+                .arg(file_name)
+                .arg("-m")
+                .arg(mode)
+                .arg(format!("-O{}", level))
+                .arg("-o")
+                .arg(&output)
+                .output()
+                .expect("Failed to run sabbahc");
+            if result.status.success() {
+                let actual = fs::read_to_string(&output).unwrap();
+                compare(&program.with_extension(ext), &actual, bless, failures);
+                if !result.stderr.is_empty() && warnings.is_none() {
+                    warnings = Some(String::from_utf8_lossy(&result.stderr).into_owned());
+                }
+            } else {
+                let actual = String::from_utf8_lossy(&result.stderr);
+                compare(&program.with_extension("stderr"), &actual, bless, failures);
+                break 'stages;
+            }
+        }
+    }
+
+    let snapshot = program.with_extension("warnings");
+    match warnings {
+        Some(actual) => compare(&snapshot, &actual, bless, failures),
+        None if snapshot.exists() && bless => fs::remove_file(&snapshot).unwrap(),
+        None if snapshot.exists() => failures.push(format!("{}: no warnings anymore", snapshot.display())),
+        None => {}
+    }
+
+    fs::remove_dir_all(&scratch).unwrap();
+}
+
+fn compare(snapshot: &Path, actual: &str, bless: bool, failures: &mut Vec<String>) {
+    if bless {
+        fs::write(snapshot, actual).unwrap();
+        return;
+    }
+    let Ok(expected) = fs::read_to_string(snapshot) else {
+        failures.push(format!("{}: snapshot is missing", snapshot.display()));
+        return;
+    };
+    if expected != actual {
+        failures.push(format!("{}:\n{}", snapshot.display(), diff(&expected, actual)));
+    }
+}
+
+/// A minimal line diff: good enough to spot what changed in small snapshots.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut out = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => out.push_str(&format!("   {}\n", e)),
+            (e, a) => {
+                if let Some(e) = e {
+                    out.push_str(&format!(" - {}\n", e));
+                }
+                if let Some(a) = a {
+                    out.push_str(&format!(" + {}\n", a));
+                }
+            }
+        }
+    }
+    out
+}
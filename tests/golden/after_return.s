@@ -0,0 +1,14 @@
+.section .text
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    mov $1, %rax
+    leave
+    ret
+.Lmain_bb1:
+    mov $2, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,21 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at arithmetic.sbb:3:18: multiplication overflowed uint8\012"
+    .balign 1
+str.1:
+    .ascii "panic at arithmetic.sbb:3:14: addition overflowed uint8\012"
+.section .text
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    jmp .Lmain_bb1
+.Lmain_bb1:
+    jmp .Lmain_bb3
+.Lmain_bb3:
+    mov $4, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,87 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at arithmetic.sbb:3:18: multiplication overflowed uint8\012"
+    .balign 1
+str.1:
+    .ascii "panic at arithmetic.sbb:3:14: addition overflowed uint8\012"
+.section .text
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+.Lmain_bb0:
+    mov $10, %rax
+    mov $4, %rcx
+    sub %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rbx
+    mov $3, %rax
+    mov %rbx, %rcx
+    mul %cl
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $62, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov $3, %rax
+    mov %rbx, %rcx
+    imul %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    mov $2, %rcx
+    xor %edx, %edx
+    div %rcx
+    movzbl %al, %eax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    mov $7, %rcx
+    xor %edx, %edx
+    div %rcx
+    mov %rdx, %rax
+    movzbl %al, %eax
+    mov %rax, %rbx
+    mov $2, %rax
+    mov %rbx, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb4
+    jmp .Lmain_bb3
+.Lmain_bb4:
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $56, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb3:
+    mov $2, %rax
+    mov %rbx, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
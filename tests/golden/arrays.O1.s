@@ -0,0 +1,136 @@
+.section .rodata
+    .balign 2
+_S5TABLEE:
+    .word 10, 20, 30
+    .balign 1
+str.0:
+    .ascii "panic at arrays.sbb:7:18: index out of bounds\012"
+    .balign 1
+str.1:
+    .ascii "panic at arrays.sbb:11:36: addition overflowed uint16\012"
+.section .text
+_S2atE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+    push %rdi
+    push %rsi
+    push %rdx
+    pop %r12
+    pop %rsi
+    pop %rbx
+.L_S2atE_bb0:
+    mov %r12, %rax
+    mov %rsi, %rcx
+    cmp %rcx, %rax
+    setb %al
+    movzbl %al, %eax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    test %rax, %rax
+    jnz .L_S2atE_bb1
+    jmp .L_S2atE_bb2
+.L_S2atE_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $46, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S2atE_bb1:
+    mov %r12, %rax
+    mov $2, %rcx
+    imul %rcx, %rax
+    mov %rax, %rsi
+    mov %rbx, %rax
+    mov %rsi, %rcx
+    add %rcx, %rax
+    mov %rax, %rdi
+    mov %rdi, %rcx
+    movzwl (%rcx), %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+.Lmain_bb0:
+    lea _S5TABLEE(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    mov $2, %rcx
+    add %rcx, %rax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    push %rax
+    mov $2, %rax
+    push %rax
+    mov $1, %rax
+    push %rax
+    pop %rdx
+    pop %rsi
+    pop %rdi
+    call _S2atE
+    mov %rax, %rbx
+    lea _S5TABLEE(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rcx
+    movzwl (%rcx), %eax
+    mov %rax, %r12
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %cx, %ax
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $54, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    cmp $40, %rax
+    je .Lmain_bb3
+    jmp .Lmain_bb4
+.Lmain_bb3:
+    mov $0, %rax
+    push %rax
+    pop %rsi
+    jmp .Lmain_bb5
+.Lmain_bb4:
+    mov $1, %rax
+    push %rax
+    pop %rsi
+    jmp .Lmain_bb5
+.Lmain_bb5:
+    mov %rsi, %rax
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
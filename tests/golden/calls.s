@@ -0,0 +1,109 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at calls.sbb:2:14: addition overflowed uint8\012"
+    .balign 1
+str.1:
+    .ascii "panic at calls.sbb:7:30: multiplication overflowed uint8\012"
+.section .text
+_S3addE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+    push %rdi
+    push %rsi
+    pop %r12
+    pop %rbx
+.L_S3addE_bb0:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S3addE_bb2
+    jmp .L_S3addE_bb1
+.L_S3addE_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $51, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S3addE_bb1:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+.Lmain_bb0:
+    mov $1, %rax
+    push %rax
+    mov $2, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _S3addE
+    mov %rax, %rsi
+    mov $1, %rax
+    push %rax
+    mov $2, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _S3addE
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $3, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _S3addE
+    mov %rax, %rbx
+    mov %rbx, %rax
+    mov $2, %rcx
+    mul %cl
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $57, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov %rbx, %rax
+    mov $2, %rcx
+    imul %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,169 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at casts.sbb:8:23: addition overflowed int64\012"
+    .balign 1
+str.1:
+    .ascii "panic at casts.sbb:16:46: addition overflowed uint32\012"
+    .balign 1
+str.2:
+    .ascii "panic at casts.sbb:16:61: addition overflowed uint8\012"
+.section .text
+_S6shrinkE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    pop %rsi
+.L_S6shrinkE_bb0:
+    mov %rsi, %rax
+    movzbl %al, %eax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    leave
+    ret
+_S4growE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+    push %rdi
+    push %rsi
+    pop %rdi
+    pop %rsi
+.L_S4growE_bb0:
+    mov %rsi, %rax
+    movswq %ax, %rax
+    mov %rax, %rbx
+    mov %rdi, %rax
+    mov %rax, %r12
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %rcx, %rax
+    seto %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jo .L_S4growE_bb2
+    jmp .L_S4growE_bb1
+.L_S4growE_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $51, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S4growE_bb1:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %rcx, %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+_S4flagE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    pop %rsi
+.L_S4flagE_bb0:
+    mov %rsi, %rax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+.Lmain_bb0:
+    mov $0, %rax
+    mov $2, %rcx
+    sub %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $300, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _S4growE
+    mov %rax, %rdi
+    mov %rdi, %rax
+    mov %eax, %eax
+    mov %rax, %rbx
+    mov %rbx, %rax
+    mov $2, %rcx
+    add %ecx, %eax
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $53, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov %rbx, %rax
+    mov $2, %rcx
+    add %rcx, %rax
+    mov %eax, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    pop %rdi
+    call _S6shrinkE
+    mov %rax, %rbx
+    mov $1, %rax
+    push %rax
+    pop %rdi
+    call _S4flagE
+    mov %rax, %rsi
+    mov %rsi, %rax
+    movzbl %al, %eax
+    mov %rax, %r12
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb4
+    jmp .Lmain_bb3
+.Lmain_bb4:
+    lea str.2(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $52, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb3:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
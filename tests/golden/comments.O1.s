@@ -0,0 +1,10 @@
+.section .text
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    mov $42, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,16 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at constants.sbb:8:17: addition overflowed uint8\012"
+.section .text
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    jmp .Lmain_bb1
+.Lmain_bb1:
+    mov $34, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,49 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at constants.sbb:8:17: addition overflowed uint8\012"
+.section .text
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+.Lmain_bb0:
+    mov $60, %rax
+    mov $7, %rcx
+    xor %edx, %edx
+    div %rcx
+    mov %rdx, %rax
+    movzbl %al, %eax
+    mov %rax, %rbx
+    mov $30, %rax
+    mov %rbx, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $55, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov $30, %rax
+    mov %rbx, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,252 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at enums.sbb:11:37: multiplication overflowed uint32\012"
+    .balign 1
+str.1:
+    .ascii "panic at enums.sbb:28:21: subtraction overflowed uint8\012"
+    .balign 1
+str.2:
+    .ascii "panic at enums.sbb:28:27: addition overflowed uint8\012"
+    .balign 4
+.L_S5digitE_bb0_table:
+    .long .L_S5digitE_bb1 - .L_S5digitE_bb0_table
+    .long .L_S5digitE_bb2 - .L_S5digitE_bb0_table
+    .long .L_S5digitE_bb3 - .L_S5digitE_bb0_table
+    .long .L_S5digitE_bb4 - .L_S5digitE_bb0_table
+    .long .L_S5digitE_bb4 - .L_S5digitE_bb0_table
+    .long .L_S5digitE_bb5 - .L_S5digitE_bb0_table
+    .long .L_S5digitE_bb5 - .L_S5digitE_bb0_table
+    .long .L_S5digitE_bb5 - .L_S5digitE_bb0_table
+.section .text
+_S4sizeE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+    push %rdi
+    pop %rsi
+.L_S4sizeE_bb0:
+    mov %rsi, %rax
+    mov $255, %rcx
+    and %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rdi
+    mov %rsi, %rax
+    mov $32, %rcx
+    shr %cl, %rax
+    movzbl %al, %eax
+    mov %rax, %r8
+    mov %rsi, %rax
+    mov $32, %rcx
+    shr %cl, %rax
+    mov %eax, %eax
+    mov %rax, %rbx
+    mov %rdi, %rax
+    cmp $0, %rax
+    je .L_S4sizeE_bb1
+    cmp $1, %rax
+    je .L_S4sizeE_bb2
+    jmp .L_S4sizeE_bb3
+.L_S4sizeE_bb1:
+    mov $0, %rax
+    push %rax
+    pop %r12
+    jmp .L_S4sizeE_bb4
+.L_S4sizeE_bb2:
+    mov $1, %rax
+    push %rax
+    pop %r12
+    jmp .L_S4sizeE_bb4
+.L_S4sizeE_bb3:
+    mov %rbx, %rax
+    mov %rbx, %rcx
+    mul %ecx
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S4sizeE_bb6
+    jmp .L_S4sizeE_bb5
+.L_S4sizeE_bb6:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $59, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S4sizeE_bb5:
+    mov %rbx, %rax
+    mov %rbx, %rcx
+    imul %rcx, %rax
+    mov %eax, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    pop %r12
+    jmp .L_S4sizeE_bb4
+.L_S4sizeE_bb4:
+    mov %r12, %rax
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+_S5digitE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    pop %rsi
+.L_S5digitE_bb0:
+    mov %rsi, %rax
+    mov $128, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    sub $128, %rax
+    cmp $7, %rax
+    ja .L_S5digitE_bb6
+    lea .L_S5digitE_bb0_table(%rip), %rcx
+    movslq (%rcx,%rax,4), %rax
+    add %rcx, %rax
+    jmp *%rax
+.L_S5digitE_bb1:
+    mov $122, %rax
+    push %rax
+    pop %rsi
+    jmp .L_S5digitE_bb7
+.L_S5digitE_bb2:
+    mov $111, %rax
+    push %rax
+    pop %rsi
+    jmp .L_S5digitE_bb7
+.L_S5digitE_bb3:
+    mov $116, %rax
+    push %rax
+    pop %rsi
+    jmp .L_S5digitE_bb7
+.L_S5digitE_bb4:
+    mov $102, %rax
+    push %rax
+    pop %rsi
+    jmp .L_S5digitE_bb7
+.L_S5digitE_bb5:
+    mov $115, %rax
+    push %rax
+    pop %rsi
+    jmp .L_S5digitE_bb7
+.L_S5digitE_bb6:
+    mov $63, %rax
+    push %rax
+    pop %rsi
+    jmp .L_S5digitE_bb7
+.L_S5digitE_bb7:
+    mov %rsi, %rax
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+.Lmain_bb0:
+    mov $3, %rax
+    push %rax
+    pop %rdi
+    call _S5digitE
+    mov %rax, %rbx
+    mov %rbx, %rax
+    mov $102, %rcx
+    sub %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $55, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov %rbx, %rax
+    mov $102, %rcx
+    sub %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %r12
+    mov $0, %rax
+    mov $32, %rcx
+    shl %cl, %rax
+    mov %rax, %rsi
+    mov $7, %rax
+    mov %rsi, %rcx
+    or %rcx, %rax
+    mov %rax, %rdi
+    mov $2, %rax
+    mov $32, %rcx
+    shl %cl, %rax
+    mov %rax, %rsi
+    mov %rdi, %rax
+    mov %rsi, %rcx
+    or %rcx, %rax
+    mov %rax, %r8
+    mov %r8, %rax
+    push %rax
+    pop %rdi
+    call _S4sizeE
+    mov %rax, %rsi
+    mov %rsi, %rax
+    cmp $4, %rax
+    je .Lmain_bb3
+    jmp .Lmain_bb4
+.Lmain_bb3:
+    mov $5, %rax
+    push %rax
+    pop %rbx
+    jmp .Lmain_bb5
+.Lmain_bb4:
+    mov $0, %rax
+    push %rax
+    pop %rbx
+    jmp .Lmain_bb5
+.Lmain_bb5:
+    mov %r12, %rax
+    mov %rbx, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb7
+    jmp .Lmain_bb6
+.Lmain_bb7:
+    lea str.2(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $52, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb6:
+    mov %r12, %rax
+    mov %rbx, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
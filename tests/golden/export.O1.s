@@ -0,0 +1,116 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at export.sbb:4:14: multiplication overflowed int32\012"
+    .balign 1
+str.1:
+    .ascii "panic at export.sbb:8:14: addition overflowed uint8\012"
+.section .text
+.globl twice
+twice:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+    push %rdi
+    pop %rax
+    mov %eax, %eax
+    mov %rax, %rbx
+.Ltwice_bb0:
+    mov %rbx, %rax
+    mov $2, %rcx
+    imul %ecx, %eax
+    seto %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jo .Ltwice_bb2
+    jmp .Ltwice_bb1
+.Ltwice_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $58, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Ltwice_bb1:
+    mov %rbx, %rax
+    mov $2, %rcx
+    imul %rcx, %rax
+    mov %eax, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+_S6helperE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+    push %rdi
+    pop %rbx
+.L_S6helperE_bb0:
+    mov %rbx, %rax
+    mov $1, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S6helperE_bb2
+    jmp .L_S6helperE_bb1
+.L_S6helperE_bb2:
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $52, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S6helperE_bb1:
+    mov %rbx, %rax
+    mov $1, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+.globl bump
+bump:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    pop %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+.Lbump_bb0:
+    mov %rsi, %rax
+    push %rax
+    pop %rdi
+    call _S6helperE
+    mov %rax, %rdi
+    mov %rdi, %rax
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    mov $2, %rax
+    push %rax
+    pop %rdi
+    call bump
+    mov %rax, %rsi
+    mov %rsi, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,103 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at extern.sbb:7:19: multiplication overflowed int32\012"
+    .balign 1
+str.1:
+    .ascii "panic at extern.sbb:11:21: subtraction overflowed int32\012"
+.section .text
+_S5twiceE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+    push %rdi
+    pop %rsi
+.L_S5twiceE_bb0:
+    mov %rsi, %rax
+    push %rax
+    pop %rdi
+    call abs
+    mov %eax, %eax
+    mov %rax, %rbx
+    mov %rbx, %rax
+    mov $2, %rcx
+    imul %ecx, %eax
+    seto %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jo .L_S5twiceE_bb2
+    jmp .L_S5twiceE_bb1
+.L_S5twiceE_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $58, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S5twiceE_bb1:
+    mov %rbx, %rax
+    mov $1, %rcx
+    shl %cl, %rax
+    mov %eax, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    mov $3, %rcx
+    movslq %eax, %rax
+    movslq %ecx, %rcx
+    cqo
+    idiv %rcx
+    mov %eax, %eax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+.Lmain_bb0:
+    call getchar
+    mov %eax, %eax
+    mov %rax, %rbx
+    mov %rbx, %rax
+    mov $50, %rcx
+    sub %ecx, %eax
+    seto %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jo .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $56, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov %rbx, %rax
+    mov $50, %rcx
+    sub %rcx, %rax
+    mov %eax, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    pop %rdi
+    call _S5twiceE
+    mov $0, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
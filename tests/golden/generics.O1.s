@@ -0,0 +1,84 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at generics.sbb:9:36: addition overflowed uint8\012"
+.section .text
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+.Lmain_bb0:
+    mov $300, %rax
+    push %rax
+    mov $2, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _S5firstI6uint16EE
+    mov %rax, %rsi
+    mov %rsi, %rax
+    movzbl %al, %eax
+    mov %rax, %rbx
+    mov $1, %rax
+    push %rax
+    mov $2, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _S5firstI5uint8EE
+    mov %rax, %r12
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $54, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+_S5firstI6uint16EE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    push %rsi
+    pop %rdi
+    pop %rsi
+.L_S5firstI6uint16EE_bb0:
+    mov %rsi, %rax
+    leave
+    ret
+_S5firstI5uint8EE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    push %rsi
+    pop %rdi
+    pop %rsi
+.L_S5firstI5uint8EE_bb0:
+    mov %rsi, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,38 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at generics.sbb:9:36: addition overflowed uint8\012"
+.section .text
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    jmp .Lmain_bb1
+.Lmain_bb1:
+    mov $45, %rax
+    leave
+    ret
+_S5firstI6uint16EE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    push %rsi
+    pop %rdi
+    pop %rsi
+.L_S5firstI6uint16EE_bb0:
+    mov %rsi, %rax
+    leave
+    ret
+_S5firstI5uint8EE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    push %rsi
+    pop %rdi
+    pop %rsi
+.L_S5firstI5uint8EE_bb0:
+    mov %rsi, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,29 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "Hello, world!"
+.section .text
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $13, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_println
+    mov $42, %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    pop %rdi
+    call _sabbah_print_int
+    mov $0, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,71 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at let.sbb:5:18: multiplication overflowed uint16\012"
+.section .text
+_S5twiceE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+    push %rdi
+    pop %rbx
+.L_S5twiceE_bb0:
+    mov %rbx, %rax
+    mov $2, %rcx
+    mul %cx
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S5twiceE_bb2
+    jmp .L_S5twiceE_bb1
+.L_S5twiceE_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $56, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S5twiceE_bb1:
+    mov %rbx, %rax
+    mov $2, %rcx
+    imul %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    mov $100, %rax
+    push %rax
+    pop %rdi
+    call _S5twiceE
+    mov %rax, %rsi
+    mov %rsi, %rax
+    cmp $200, %rax
+    je .Lmain_bb1
+    jmp .Lmain_bb2
+.Lmain_bb1:
+    mov $0, %rax
+    push %rax
+    pop %rsi
+    jmp .Lmain_bb3
+.Lmain_bb2:
+    mov $1, %rax
+    push %rax
+    pop %rsi
+    jmp .Lmain_bb3
+.Lmain_bb3:
+    mov %rsi, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,38 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "hi"
+.section .text
+_S5greetE:
+    push %rbp
+    mov %rsp, %rbp
+.L_S5greetE_bb0:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $2, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_println
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $2, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_println
+    mov $0, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
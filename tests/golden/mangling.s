@@ -0,0 +1,43 @@
+.section .text
+_S6_startE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    pop %rsi
+.L_S6_startE_bb0:
+    mov %rsi, %rax
+    leave
+    ret
+plain:
+    push %rbp
+    mov %rsp, %rbp
+.Lplain_bb0:
+    mov $3, %rax
+    push %rax
+    pop %rdi
+    call _S6_startE
+    mov %rax, %rsi
+    mov %rsi, %rax
+    leave
+    ret
+.globl shared
+shared:
+    push %rbp
+    mov %rsp, %rbp
+.Lshared_bb0:
+    call plain
+    mov %rax, %rsi
+    mov %rsi, %rax
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    call shared
+    mov %rax, %rsi
+    mov %rsi, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
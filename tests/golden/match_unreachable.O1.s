@@ -0,0 +1,41 @@
+.section .text
+_S8classifyE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    pop %rsi
+.L_S8classifyE_bb0:
+    mov %rsi, %rax
+    mov %rax, %rdx
+    sub $0, %rdx
+    cmp $9, %rdx
+    jbe .L_S8classifyE_bb1
+    jmp .L_S8classifyE_bb3
+.L_S8classifyE_bb1:
+    mov $1, %rax
+    push %rax
+    pop %rsi
+    jmp .L_S8classifyE_bb5
+.L_S8classifyE_bb3:
+    mov $3, %rax
+    push %rax
+    pop %rsi
+    jmp .L_S8classifyE_bb5
+.L_S8classifyE_bb5:
+    mov %rsi, %rax
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    mov $4, %rax
+    push %rax
+    pop %rdi
+    call _S8classifyE
+    mov %rax, %rsi
+    mov %rsi, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
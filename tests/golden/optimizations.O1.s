@@ -0,0 +1,247 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at optimizations.sbb:2:14: multiplication overflowed uint8\012"
+    .balign 1
+str.1:
+    .ascii "panic at optimizations.sbb:6:14: multiplication overflowed uint8\012"
+    .balign 1
+str.2:
+    .ascii "panic at optimizations.sbb:6:18: addition overflowed uint8\012"
+    .balign 1
+str.3:
+    .ascii "panic at optimizations.sbb:6:26: addition overflowed uint8\012"
+    .balign 1
+str.4:
+    .ascii "panic at optimizations.sbb:6:40: multiplication overflowed uint8\012"
+    .balign 1
+str.5:
+    .ascii "panic at optimizations.sbb:6:45: multiplication overflowed uint8\012"
+    .balign 1
+str.6:
+    .ascii "panic at optimizations.sbb:6:35: addition overflowed uint8\012"
+    .balign 1
+str.7:
+    .ascii "panic at optimizations.sbb:10:38: addition overflowed uint8\012"
+    .balign 1
+str.8:
+    .ascii "panic at optimizations.sbb:10:43: multiplication overflowed uint8\012"
+    .balign 1
+str.9:
+    .ascii "panic at optimizations.sbb:10:29: addition overflowed uint8\012"
+.section .text
+_S6squareE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+    push %rdi
+    pop %rbx
+.L_S6squareE_bb0:
+    mov %rbx, %rax
+    mov %rbx, %rcx
+    mul %cl
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S6squareE_bb2
+    jmp .L_S6squareE_bb1
+.L_S6squareE_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $65, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S6squareE_bb1:
+    mov %rbx, %rax
+    mov %rbx, %rcx
+    imul %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+_S5scaleE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+    push %r13
+    push %r14
+    push %rdi
+    pop %rbx
+.L_S5scaleE_bb0:
+    mov %rbx, %rax
+    mov $8, %rcx
+    mul %cl
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S5scaleE_bb2
+    jmp .L_S5scaleE_bb1
+.L_S5scaleE_bb2:
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $65, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S5scaleE_bb1:
+    mov %rbx, %rax
+    mov $8, %rcx
+    imul %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %r12
+    mov %rbx, %rax
+    mov $4, %rcx
+    xor %edx, %edx
+    div %rcx
+    movzbl %al, %eax
+    mov %rax, %r13
+    mov %r12, %rax
+    mov %r13, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S5scaleE_bb4
+    jmp .L_S5scaleE_bb3
+.L_S5scaleE_bb4:
+    lea str.2(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $59, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S5scaleE_bb3:
+    mov %r12, %rax
+    mov %r13, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %r14
+    mov %rbx, %rax
+    mov $16, %rcx
+    xor %edx, %edx
+    div %rcx
+    mov %rdx, %rax
+    movzbl %al, %eax
+    mov %rax, %r12
+    mov %r14, %rax
+    mov %r12, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S5scaleE_bb6
+    jmp .L_S5scaleE_bb5
+.L_S5scaleE_bb6:
+    lea str.3(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $59, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S5scaleE_bb5:
+    mov %r14, %rax
+    mov %r12, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %r13
+    mov %rbx, %rax
+    mov $8, %rcx
+    mul %cl
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S5scaleE_bb8
+    jmp .L_S5scaleE_bb7
+.L_S5scaleE_bb8:
+    lea str.4(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $65, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S5scaleE_bb7:
+    mov %rbx, %rax
+    mov $8, %rcx
+    imul %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %r12
+    jmp .L_S5scaleE_bb9
+.L_S5scaleE_bb9:
+    mov %r13, %rax
+    mov %r12, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S5scaleE_bb12
+    jmp .L_S5scaleE_bb11
+.L_S5scaleE_bb12:
+    lea str.6(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $59, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S5scaleE_bb11:
+    mov %r13, %rax
+    mov %r12, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -32(%rbp), %rsp
+    pop %r14
+    pop %r13
+    pop %r12
+    pop %rbx
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    mov $3, %rax
+    push %rax
+    pop %rdi
+    call _S6squareE
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    pop %rdi
+    call _S5scaleE
+    mov %rax, %rdi
+    jmp .Lmain_bb1
+.Lmain_bb1:
+    jmp .Lmain_bb3
+.Lmain_bb3:
+    jmp .Lmain_bb5
+.Lmain_bb5:
+    mov %rdi, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
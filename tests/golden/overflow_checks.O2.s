@@ -0,0 +1,166 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at overflow_checks.sbb:2:14: addition overflowed uint8\012"
+    .balign 1
+str.1:
+    .ascii "panic at overflow_checks.sbb:6:14: multiplication overflowed int32\012"
+    .balign 1
+str.2:
+    .ascii "panic at overflow_checks.sbb:6:18: subtraction overflowed int32\012"
+    .balign 1
+str.3:
+    .ascii "panic at overflow_checks.sbb:10:22: addition overflowed uint8\012"
+.section .text
+_S3addE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+    push %rdi
+    push %rsi
+    pop %r12
+    pop %rbx
+.L_S3addE_bb0:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S3addE_bb2
+    jmp .L_S3addE_bb1
+.L_S3addE_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $61, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S3addE_bb1:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+_S5scaleE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+    push %r13
+    sub $8, %rsp
+    push %rdi
+    push %rsi
+    pop %r12
+    pop %rbx
+.L_S5scaleE_bb0:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    imul %ecx, %eax
+    seto %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jo .L_S5scaleE_bb2
+    jmp .L_S5scaleE_bb1
+.L_S5scaleE_bb2:
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $67, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S5scaleE_bb1:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    imul %rcx, %rax
+    mov %eax, %eax
+    mov %rax, %r13
+    mov %r13, %rax
+    mov $1, %rcx
+    sub %ecx, %eax
+    seto %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jo .L_S5scaleE_bb4
+    jmp .L_S5scaleE_bb3
+.L_S5scaleE_bb4:
+    lea str.2(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $64, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S5scaleE_bb3:
+    mov %r13, %rax
+    mov $1, %rcx
+    sub %rcx, %rax
+    mov %eax, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -24(%rbp), %rsp
+    pop %r13
+    pop %r12
+    pop %rbx
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+.Lmain_bb0:
+    mov $1, %rax
+    push %rax
+    mov $2, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _S3addE
+    mov %rax, %rbx
+    mov %rbx, %rax
+    mov $3, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.3(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $62, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov %rbx, %rax
+    mov $3, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,132 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at overflow_intrinsics.sbb:14:23: addition overflowed uint8\012"
+.section .text
+_S5clampE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    push %rsi
+    pop %rdi
+    pop %rsi
+.L_S5clampE_bb0:
+    mov %rsi, %rax
+    mov %rdi, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %r8
+    mov %rsi, %rax
+    mov %rdi, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %r9
+    jc .L_S5clampE_bb2
+    jmp .L_S5clampE_bb1
+.L_S5clampE_bb1:
+    mov %r8, %rax
+    push %rax
+    pop %rsi
+    jmp .L_S5clampE_bb3
+.L_S5clampE_bb2:
+    mov $255, %rax
+    push %rax
+    pop %rsi
+    jmp .L_S5clampE_bb3
+.L_S5clampE_bb3:
+    mov %rsi, %rax
+    leave
+    ret
+_S12clamp_signedE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    push %rsi
+    pop %rdi
+    pop %rsi
+.L_S12clamp_signedE_bb0:
+    mov %rsi, %rax
+    mov %rdi, %rcx
+    imul %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %r8
+    mov %rsi, %rax
+    mov %rdi, %rcx
+    imul %cx, %ax
+    seto %al
+    movzbl %al, %eax
+    mov %rax, %r9
+    jo .L_S12clamp_signedE_bb2
+    jmp .L_S12clamp_signedE_bb1
+.L_S12clamp_signedE_bb1:
+    mov %r8, %rax
+    push %rax
+    pop %r9
+    jmp .L_S12clamp_signedE_bb3
+.L_S12clamp_signedE_bb2:
+    mov %rsi, %rax
+    mov $15, %rcx
+    shr %cl, %rax
+    movzwl %ax, %eax
+    mov %rax, %r8
+    mov %rdi, %rax
+    mov $15, %rcx
+    shr %cl, %rax
+    movzwl %ax, %eax
+    mov %rax, %rsi
+    mov %r8, %rax
+    mov %rsi, %rcx
+    add %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    mov $1, %rcx
+    and %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %rsi
+    mov $32767, %rax
+    mov %rsi, %rcx
+    add %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    push %rax
+    pop %r9
+    jmp .L_S12clamp_signedE_bb3
+.L_S12clamp_signedE_bb3:
+    mov %r9, %rax
+    leave
+    ret
+_S4wrapE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    pop %rsi
+.L_S4wrapE_bb0:
+    mov %rsi, %rax
+    mov $1, %rcx
+    sub %rcx, %rax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    mov $250, %rax
+    push %rax
+    mov $10, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _S5clampE
+    mov %rax, %rsi
+    jmp .Lmain_bb1
+.Lmain_bb1:
+    mov %rsi, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
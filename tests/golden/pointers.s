@@ -0,0 +1,152 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at pointers.sbb:9:25: addition overflowed uint32\012"
+.section .bss
+    .balign 8
+_S4seenE:
+    .zero 8
+.section .text
+_S4bumpE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+    push %rdi
+    pop %rbx
+.L_S4bumpE_bb0:
+    mov %rbx, %rcx
+    mov (%rcx), %eax
+    mov %rax, %r12
+    mov %r12, %rax
+    mov $1, %rcx
+    add %ecx, %eax
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S4bumpE_bb2
+    jmp .L_S4bumpE_bb1
+.L_S4bumpE_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $55, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S4bumpE_bb1:
+    mov %r12, %rax
+    mov $1, %rcx
+    add %rcx, %rax
+    mov %eax, %eax
+    mov %rax, %rsi
+    mov %rbx, %rcx
+    mov %rsi, %rax
+    mov %eax, (%rcx)
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+_S6secondE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    pop %rsi
+.L_S6secondE_bb0:
+    mov $1, %rax
+    mov $2, %rcx
+    imul %rcx, %rax
+    mov %rax, %rdi
+    mov %rsi, %rax
+    mov %rdi, %rcx
+    add %rcx, %rax
+    mov %rax, %r8
+    mov %r8, %rcx
+    movzwl (%rcx), %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    leave
+    ret
+_S5storeE:
+    push %rbp
+    mov %rsp, %rbp
+    sub $16, %rsp
+    push %rdi
+    pop %rsi
+.L_S5storeE_bb0:
+    mov %rsi, %rax
+    mov $255, %rcx
+    and %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rdi
+    mov %rsi, %rax
+    mov $16, %rcx
+    shr %cl, %rax
+    movzwl %ax, %eax
+    mov %rax, %r8
+    lea -4(%rbp), %rax
+    mov %rax, %rsi
+    mov %r8, %rax
+    mov $16, %rcx
+    shl %cl, %rax
+    mov %rax, %r9
+    mov %rdi, %rax
+    mov %r9, %rcx
+    or %rcx, %rax
+    mov %rax, %r8
+    mov %rsi, %rcx
+    mov %r8, %rax
+    mov %eax, (%rcx)
+    mov %rsi, %rax
+    mov %rax, _S4seenE(%rip)
+    mov _S4seenE(%rip), %rax
+    mov %rax, %rdi
+    mov $4, %rax
+    mov $16, %rcx
+    shl %cl, %rax
+    mov %rax, %r8
+    mov $3, %rax
+    mov %r8, %rcx
+    or %rcx, %rax
+    mov %rax, %r9
+    mov %rdi, %rcx
+    mov %r9, %rax
+    mov %eax, (%rcx)
+    mov %rsi, %rcx
+    movzbl (%rcx), %eax
+    mov %rax, %rdi
+    mov %rsi, %rax
+    mov $2, %rcx
+    add %rcx, %rax
+    mov %rax, %r8
+    mov %r8, %rcx
+    movzwl (%rcx), %eax
+    mov %rax, %rsi
+    mov %rdi, %rax
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    mov $2, %rax
+    mov $16, %rcx
+    shl %cl, %rax
+    mov %rax, %rsi
+    mov $1, %rax
+    mov %rsi, %rcx
+    or %rcx, %rax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    push %rax
+    pop %rdi
+    call _S5storeE
+    mov %rax, %rsi
+    mov %rsi, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
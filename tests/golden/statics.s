@@ -0,0 +1,149 @@
+.section .data
+    .balign 1
+_S5totalE:
+    .byte 5
+.section .rodata
+    .balign 1
+_S5LIMITE:
+    .byte 200
+    .balign 1
+str.0:
+    .ascii "panic at statics.sbb:6:19: addition overflowed uint8\012"
+    .balign 1
+str.1:
+    .ascii "panic at statics.sbb:7:19: addition overflowed uint8\012"
+    .balign 1
+str.2:
+    .ascii "panic at statics.sbb:13:18: addition overflowed uint8\012"
+.section .bss
+    .balign 1
+_S5callsE:
+    .zero 1
+.section .text
+_S5countE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+    push %rdi
+    pop %rbx
+.L_S5countE_bb0:
+    movzbl _S5callsE(%rip), %eax
+    mov %rax, %r12
+    mov %r12, %rax
+    mov $1, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S5countE_bb2
+    jmp .L_S5countE_bb1
+.L_S5countE_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $53, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S5countE_bb1:
+    mov %r12, %rax
+    mov $1, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    mov %al, _S5callsE(%rip)
+    movzbl _S5totalE(%rip), %eax
+    mov %rax, %r12
+    mov %r12, %rax
+    mov %rbx, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S5countE_bb4
+    jmp .L_S5countE_bb3
+.L_S5countE_bb4:
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $53, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S5countE_bb3:
+    mov %r12, %rax
+    mov %rbx, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    mov %al, _S5totalE(%rip)
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+.Lmain_bb0:
+    movzbl _S5LIMITE(%rip), %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    mov $10, %rcx
+    xor %edx, %edx
+    div %rcx
+    movzbl %al, %eax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    push %rax
+    pop %rdi
+    call _S5countE
+    mov $3, %rax
+    push %rax
+    pop %rdi
+    call _S5countE
+    movzbl _S5totalE(%rip), %eax
+    mov %rax, %rbx
+    movzbl _S5callsE(%rip), %eax
+    mov %rax, %r12
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.2(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $54, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,148 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at strings.sbb:2:18: subtraction overflowed uint8\012"
+    .balign 1
+str.1:
+    .ascii "tab\011quote\"back\\slash\012"
+    .balign 1
+str.2:
+    .ascii "A\303\251\360\237\230\200"
+    .balign 1
+str.3:
+    .zero 0
+    .balign 1
+str.4:
+    .ascii "panic at strings.sbb:9:52: addition overflowed uint8\012"
+.section .text
+_S4pickE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+    push %rdi
+    push %rsi
+    push %rdx
+    pop %rbx
+    pop %rdi
+    pop %rsi
+.L_S4pickE_bb0:
+    mov %rbx, %rax
+    mov $48, %rcx
+    sub %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .L_S4pickE_bb2
+    jmp .L_S4pickE_bb1
+.L_S4pickE_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $56, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.L_S4pickE_bb1:
+    mov %rbx, %rax
+    mov $48, %rcx
+    sub %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+.Lmain_bb0:
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $21, %rax
+    push %rax
+    mov $55, %rax
+    push %rax
+    pop %rdx
+    pop %rsi
+    pop %rdi
+    call _S4pickE
+    mov %rax, %rdi
+    lea str.2(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $7, %rax
+    push %rax
+    mov $57, %rax
+    push %rax
+    pop %rdx
+    pop %rsi
+    pop %rdi
+    call _S4pickE
+    mov %rax, %rdi
+    lea str.1(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $21, %rax
+    push %rax
+    mov $39, %rax
+    push %rax
+    pop %rdx
+    pop %rsi
+    pop %rdi
+    call _S4pickE
+    mov %rax, %rbx
+    lea str.3(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $0, %rax
+    push %rax
+    mov $65, %rax
+    push %rax
+    pop %rdx
+    pop %rsi
+    pop %rdi
+    call _S4pickE
+    mov %rax, %r12
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.4(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $53, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov %rbx, %rax
+    mov %r12, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -16(%rbp), %rsp
+    pop %r12
+    pop %rbx
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,242 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "panic at structs.sbb:22:66: addition overflowed uint8\012"
+.section .text
+_S5firstE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    push %rsi
+    pop %rdi
+    pop %rsi
+.L_S5firstE_bb0:
+    mov %rsi, %rax
+    mov $255, %rcx
+    and %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %r8
+    mov %rsi, %rax
+    mov $32, %rcx
+    shr %cl, %rax
+    mov %eax, %eax
+    mov %rax, %r9
+    mov %rdi, %rax
+    mov $65535, %rcx
+    and %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %rsi
+    mov %r8, %rax
+    leave
+    ret
+_S5widenE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    push %rsi
+    push %rdx
+    push %rcx
+    pop %r9
+    pop %r8
+    pop %rdi
+    pop %rsi
+.L_S5widenE_bb0:
+    mov %rdi, %rax
+    mov $255, %rcx
+    and %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %r10
+    mov %rdi, %rax
+    mov $32, %rcx
+    shr %cl, %rax
+    mov %eax, %eax
+    mov %rax, %r11
+    mov %r8, %rax
+    mov $65535, %rcx
+    and %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %rdi
+    mov %r11, %rax
+    mov $32, %rcx
+    shl %cl, %rax
+    mov %rax, %r8
+    mov %r10, %rax
+    mov %r8, %rcx
+    or %rcx, %rax
+    mov %rax, %r11
+    mov %rsi, %rcx
+    mov %r11, %rax
+    mov %rax, 0(%rcx)
+    mov %rdi, %rax
+    mov %rax, 8(%rcx)
+    mov %r9, %rax
+    mov %rax, 16(%rcx)
+    mov %rcx, %rax
+    leave
+    ret
+_S3lowE:
+    push %rbp
+    mov %rsp, %rbp
+    mov 16(%rbp), %rax
+    mov %rax, %rsi
+    mov 24(%rbp), %rax
+    mov %rax, %rdi
+    mov 32(%rbp), %rax
+    mov %rax, %r8
+.L_S3lowE_bb0:
+    mov %rsi, %rax
+    mov $255, %rcx
+    and %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %r8
+    mov %rsi, %rax
+    mov $32, %rcx
+    shr %cl, %rax
+    mov %eax, %eax
+    mov %rax, %r9
+    mov %rdi, %rax
+    mov $65535, %rcx
+    and %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %rsi
+    mov %r9, %rax
+    mov $32, %rcx
+    shl %cl, %rax
+    mov %rax, %rdi
+    mov %r8, %rax
+    mov %rdi, %rcx
+    or %rcx, %rax
+    mov %rax, %r9
+    mov %r9, %rax
+    mov %rsi, %rdx
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    sub $8, %rsp
+.Lmain_bb0:
+    mov $70000, %rax
+    mov $32, %rcx
+    shl %cl, %rax
+    mov %rax, %rsi
+    mov $3, %rax
+    mov %rsi, %rcx
+    or %rcx, %rax
+    mov %rax, %rdi
+    sub $32, %rsp
+    mov %rdi, %rax
+    push %rax
+    mov $2, %rax
+    push %rax
+    mov $9, %rax
+    push %rax
+    pop %rcx
+    pop %rdx
+    pop %rsi
+    lea 0(%rsp), %rdi
+    call _S5widenE
+    mov 0(%rsp), %rax
+    mov %rax, %rsi
+    mov 8(%rsp), %rax
+    mov %rax, %r8
+    mov 16(%rsp), %rax
+    mov %rax, %r9
+    add $32, %rsp
+    mov %rsi, %rax
+    mov $255, %rcx
+    and %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rdi
+    mov %rsi, %rax
+    mov $32, %rcx
+    shr %cl, %rax
+    mov %eax, %eax
+    mov %rax, %r10
+    mov %r8, %rax
+    mov $65535, %rcx
+    and %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %rsi
+    mov %r10, %rax
+    mov $32, %rcx
+    shl %cl, %rax
+    mov %rax, %r8
+    mov %rdi, %rax
+    mov %r8, %rcx
+    or %rcx, %rax
+    mov %rax, %r10
+    sub $8, %rsp
+    mov %r9, %rax
+    push %rax
+    mov %rsi, %rax
+    push %rax
+    mov %r10, %rax
+    push %rax
+    call _S3lowE
+    add $32, %rsp
+    mov %rax, %rdi
+    mov %rdx, %r8
+    mov %rdi, %rax
+    mov $255, %rcx
+    and %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rdi, %rax
+    mov $32, %rcx
+    shr %cl, %rax
+    mov %eax, %eax
+    mov %rax, %r9
+    mov %r8, %rax
+    mov $65535, %rcx
+    and %rcx, %rax
+    movzwl %ax, %eax
+    mov %rax, %rdi
+    mov %r9, %rax
+    mov $32, %rcx
+    shl %cl, %rax
+    mov %rax, %r8
+    mov %rsi, %rax
+    mov %r8, %rcx
+    or %rcx, %rax
+    mov %rax, %r9
+    mov %r9, %rax
+    push %rax
+    mov %rdi, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _S5firstE
+    mov %rax, %rbx
+    mov %rbx, %rax
+    mov $1, %rcx
+    add %cl, %al
+    setc %al
+    movzbl %al, %eax
+    mov %rax, %rsi
+    jc .Lmain_bb2
+    jmp .Lmain_bb1
+.Lmain_bb2:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    mov $54, %rax
+    push %rax
+    pop %rsi
+    pop %rdi
+    call _sabbah_panic
+.Lmain_bb1:
+    mov %rbx, %rax
+    mov $1, %rcx
+    add %rcx, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    lea -8(%rbp), %rsp
+    pop %rbx
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,103 @@
+.section .rodata
+    .balign 1
+str.0:
+    .ascii "hi\012"
+.section .bss
+    .balign 1
+_S7doubledE:
+    .zero 1
+.section .text
+_S5writeE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    push %rsi
+    pop %rdi
+    pop %rsi
+.L_S5writeE_bb0:
+    mov $1, %rax
+    push %rax
+    mov $1, %rax
+    push %rax
+    mov %rsi, %rax
+    push %rax
+    mov %rdi, %rax
+    push %rax
+    pop %rdx
+    pop %rsi
+    pop %rdi
+    pop %rax
+    syscall
+    mov %rax, %r8
+    mov %r8, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    leave
+    ret
+_S6doubleE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rbx
+    push %r12
+    push %r13
+    push %r14
+    push %r15
+    sub $8, %rsp
+    push %rdi
+    pop %rsi
+.L_S6doubleE_bb0:
+    mov %rsi, %rax
+    push %rax
+    sub $8, %rsp
+    mov 8(%rsp), %rax
+    add %rax, %rax
+    mov %rax, 0(%rsp)
+    mov 0(%rsp), %rax
+    movzbl %al, %eax
+    mov %rax, %rdi
+    add $16, %rsp
+    mov %rdi, %rax
+    mov %al, _S7doubledE(%rip)
+    lea -40(%rbp), %rsp
+    pop %r15
+    pop %r14
+    pop %r13
+    pop %r12
+    pop %rbx
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    lea str.0(%rip), %rax
+    mov %rax, %rsi
+    mov $1, %rax
+    push %rax
+    mov $1, %rax
+    push %rax
+    mov %rsi, %rax
+    push %rax
+    mov $3, %rax
+    push %rax
+    pop %rdx
+    pop %rsi
+    pop %rdi
+    pop %rax
+    syscall
+    mov %rax, %rdi
+    mov %rdi, %rax
+    movzbl %al, %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    push %rax
+    pop %rdi
+    call _S6doubleE
+    movzbl _S7doubledE(%rip), %eax
+    mov %rax, %rsi
+    mov %rsi, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
@@ -0,0 +1,17 @@
+.section .text
+_S6helperE:
+    push %rbp
+    mov %rsp, %rbp
+.L_S6helperE_bb0:
+    mov $7, %rax
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    mov $0, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
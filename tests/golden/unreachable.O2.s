@@ -0,0 +1,35 @@
+.section .text
+_S3logE:
+    push %rbp
+    mov %rsp, %rbp
+    push %rdi
+    pop %rsi
+.L_S3logE_bb0:
+    mov %rsi, %rax
+    mov %rax, %rdi
+    mov %rdi, %rax
+    push %rax
+    pop %rdi
+    call _sabbah_print_int
+    leave
+    ret
+_S14return_nothingE:
+    push %rbp
+    mov %rsp, %rbp
+.L_S14return_nothingE_bb0:
+    leave
+    ret
+.globl main
+main:
+    push %rbp
+    mov %rsp, %rbp
+.Lmain_bb0:
+    mov $1, %rax
+    push %rax
+    pop %rdi
+    call _sabbah_print_int
+    call _S14return_nothingE
+    mov $0, %rax
+    leave
+    ret
+.section .note.GNU-stack,"",@progbits
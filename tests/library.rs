@@ -0,0 +1,82 @@
+//! Library tests: build `tests/library/math.sbb` as a static and as a shared
+//! library along with its C header, link `tests/library/main.c` against each
+//! with `cc`, and check what the C program prints.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const EXPECTED: &str = "-42 -3 0\n15 -20\n8 18 34 44\n";
+
+#[test]
+fn library() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("library");
+    // The driver writes its temporary files to the working directory
+    let scratch = env::temp_dir().join(format!("sabbahc-library-{}", std::process::id()));
+    fs::create_dir_all(&scratch).unwrap();
+    let result = build_and_run(&dir, &scratch);
+    fs::remove_dir_all(&scratch).unwrap();
+    if let Err(failure) = result {
+        panic!("{}", failure);
+    }
+}
+
+fn build_and_run(dir: &Path, scratch: &Path) -> Result<(), String> {
+    let compiled = Command::new(env!("CARGO_BIN_EXE_sabbahc"))
+        .current_dir(scratch)
+        .arg(dir.join("math.sbb"))
+        .arg("--emit=staticlib,sharedlib,header")
+        .arg("-O2")
+        .output()
+        .expect("Failed to run sabbahc");
+    if !compiled.status.success() {
+        return Err(format!("compilation failed:\n{}", String::from_utf8_lossy(&compiled.stdout)));
+    }
+    non_executable_stack(&scratch.join("libmath.so"))?;
+    for (name, library) in [("static", "libmath.a"), ("shared", "libmath.so")] {
+        let executable = scratch.join(format!("main-{}", name));
+        let linked = Command::new("cc")
+            .current_dir(scratch)
+            .arg(dir.join("main.c"))
+            .arg("-I.")
+            .arg(library)
+            .arg("-o")
+            .arg(&executable)
+            .output()
+            .expect("Failed to run cc");
+        if !linked.status.success() {
+            return Err(format!("linking the {} library failed:\n{}", name, String::from_utf8_lossy(&linked.stderr)));
+        }
+        // The linker warns about objects that do not say the stack is not executable
+        let warnings = String::from_utf8_lossy(&linked.stderr);
+        if warnings.contains("executable stack") {
+            return Err(format!("linking the {} library warned:\n{}", name, warnings));
+        }
+        non_executable_stack(&executable)?;
+        let ran = Command::new(&executable)
+            .env("LD_LIBRARY_PATH", scratch)
+            .output()
+            .map_err(|err| format!("failed to execute: {}", err))?;
+        let stdout = String::from_utf8_lossy(&ran.stdout);
+        if !ran.status.success() || stdout != EXPECTED {
+            return Err(format!("{} library: expected {:?}, got {:?} ({})", name, EXPECTED, stdout, ran.status));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `binary`'s GNU_STACK segment is not executable.
+fn non_executable_stack(binary: &Path) -> Result<(), String> {
+    let headers = Command::new("readelf").arg("-lW").arg(binary).output().expect("Failed to run readelf");
+    let headers = String::from_utf8_lossy(&headers.stdout);
+    let Some(stack) = headers.lines().find(|line| line.trim_start().starts_with("GNU_STACK")) else {
+        return Err(format!("{} has no GNU_STACK segment", binary.display()));
+    };
+    // Type, offset, addresses and sizes, then flags such as "RW " and the alignment
+    let fields: Vec<&str> = stack.split_whitespace().collect();
+    if fields.len() < 8 || fields[6..fields.len() - 1].concat().contains('E') {
+        return Err(format!("{} has an executable stack: {}", binary.display(), stack.trim()));
+    }
+    Ok(())
+}
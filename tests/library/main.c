@@ -0,0 +1,12 @@
+#include <stdio.h>
+
+#include "math.h"
+
+int main(void) {
+    printf("%d %d %u\n", twice(-21), halve(-7), next(255));
+    Vec2 v = scale((Vec2){3, -4}, 5);
+    printf("%d %d\n", v.x, v.y);
+    Rect r = grow((Rect){10, 20, 30, 40}, 2);
+    printf("%lld %lld %lld %lld\n", (long long)r.x, (long long)r.y, (long long)r.w, (long long)r.h);
+    return 0;
+}
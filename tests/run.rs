@@ -0,0 +1,133 @@
+//! Execution tests: compile, assemble, link and run Sabbah programs.
+//!
+//! Every `tests/run/<name>.sbb` program is built as a binary executable with
+//! `sabbahc` (so through `as`, `ld` and the bundled runtime) at each
+//! optimization level, executed, and its exit code and stdout are checked
+//! against annotations in the program itself:
+//!
+//!   // exit: 42
+//!   // stdout: hello
+//!
+//! `exit` defaults to 0 and `stdout` to nothing. Several `stdout` lines are
+//! joined with newlines, each one ending in a newline. `// stdin:` lines are
+//! fed to the program the same way, and `// flags:` are passed to `sabbahc`.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+struct Expectation {
+    exit: i32,
+    stdout: String,
+    stdin: String,
+    flags: Vec<String>,
+}
+
+impl Expectation {
+    fn from(source: &str) -> Expectation {
+        let mut exit = 0;
+        let mut stdout = String::new();
+        let mut stdin = String::new();
+        let mut flags: Vec<String> = Vec::new();
+        for line in source.lines() {
+            let line = line.trim();
+            if let Some(code) = line.strip_prefix("// exit:") {
+                exit = code.trim().parse().expect("exit annotation must be an integer");
+            } else if let Some(text) = line.strip_prefix("// stdout:") {
+                stdout.push_str(text.strip_prefix(' ').unwrap_or(text));
+                stdout.push('\n');
+            } else if let Some(text) = line.strip_prefix("// stdin:") {
+                stdin.push_str(text.strip_prefix(' ').unwrap_or(text));
+                stdin.push('\n');
+            } else if let Some(text) = line.strip_prefix("// flags:") {
+                flags.extend(text.split_whitespace().map(String::from));
+            }
+        }
+        Expectation { exit, stdout, stdin, flags }
+    }
+}
+
+#[test]
+fn run() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("run");
+
+    let mut programs: Vec<PathBuf> = fs::read_dir(&dir)
+        .expect("Failed to read tests/run")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "sbb"))
+        .collect();
+    programs.sort();
+    assert!(!programs.is_empty(), "no programs found in {}", dir.display());
+
+    let mut failures: Vec<String> = Vec::new();
+    for program in &programs {
+        for level in 0..=2 {
+            if let Err(failure) = run_program(program, level) {
+                failures.push(format!("{} (-O{}): {}", program.display(), level, failure));
+            }
+        }
+    }
+    if !failures.is_empty() {
+        panic!("{} program(s) failed:\n\n{}", failures.len(), failures.join("\n\n"));
+    }
+}
+
+fn run_program(program: &Path, level: u8) -> Result<(), String> {
+    let name = program.file_stem().unwrap().to_str().unwrap();
+    let expected = Expectation::from(&fs::read_to_string(program).unwrap());
+
+    // The driver writes its temporary files to the working directory
+    let scratch = env::temp_dir().join(format!("sabbahc-run-{}-{}-O{}", std::process::id(), name, level));
+    fs::create_dir_all(&scratch).unwrap();
+    let result = build_and_run(program, level, &scratch, &expected);
+    fs::remove_dir_all(&scratch).unwrap();
+    result
+}
+
+fn build_and_run(program: &Path, level: u8, scratch: &Path, expected: &Expectation) -> Result<(), String> {
+    let executable = scratch.join("program");
+    let compiled = Command::new(env!("CARGO_BIN_EXE_sabbahc"))
+        .current_dir(scratch)
+        .arg(program)
+        .arg("-m")
+        .arg("bin")
+        .arg(format!("-O{}", level))
+        .arg("-o")
+        .arg(&executable)
+        .args(&expected.flags)
+        .output()
+        .expect("Failed to run sabbahc");
+    if !compiled.status.success() {
+        return Err(format!(
+            "compilation failed:\n{}{}",
+            String::from_utf8_lossy(&compiled.stdout),
+            String::from_utf8_lossy(&compiled.stderr)
+        ));
+    }
+
+    let mut child = Command::new(&executable)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to execute: {}", err))?;
+    // The program may exit without reading everything, so a failed write is fine
+    let _ = child.stdin.take().unwrap().write_all(expected.stdin.as_bytes());
+    let ran = child.wait_with_output().map_err(|err| format!("failed to execute: {}", err))?;
+    let mut problems: Vec<String> = Vec::new();
+    match ran.status.code() {
+        Some(code) if code == expected.exit => {}
+        Some(code) => problems.push(format!("expected exit code {}, got {}", expected.exit, code)),
+        None => problems.push(format!("terminated by a signal ({})", ran.status)),
+    }
+    let stdout = String::from_utf8_lossy(&ran.stdout);
+    if stdout != expected.stdout {
+        problems.push(format!("expected stdout {:?}, got {:?}", expected.stdout, stdout));
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("; "))
+    }
+}